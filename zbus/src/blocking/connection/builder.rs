@@ -15,8 +15,13 @@ use zvariant::{ObjectPath, Str};
 #[cfg(feature = "p2p")]
 use crate::Guid;
 use crate::{
-    address::Address, blocking::Connection, connection::socket::BoxedSplit, names::WellKnownName,
-    object_server::Interface, utils::block_on, AuthMechanism, Error, Result,
+    address::Address,
+    blocking::Connection,
+    connection::{socket::BoxedSplit, Interceptor},
+    names::WellKnownName,
+    object_server::{DynamicInterface, Interface},
+    utils::block_on,
+    AuthMechanism, Error, Result,
 };
 
 /// A builder for [`zbus::blocking::Connection`].
@@ -179,6 +184,16 @@ impl<'a> Builder<'a> {
         Self(self.0.max_queued(max))
     }
 
+    /// Add an [`Interceptor`] for all the messages sent and received by the connection.
+    ///
+    /// Interceptors are run in the order they're added in. See [`Interceptor`] for details.
+    pub fn interceptor<I>(self, interceptor: I) -> Self
+    where
+        I: Interceptor,
+    {
+        Self(self.0.interceptor(interceptor))
+    }
+
     /// Register a D-Bus [`Interface`] to be served at a given path.
     ///
     /// This is similar to [`zbus::blocking::ObjectServer::at`], except that it allows you to have
@@ -194,6 +209,17 @@ impl<'a> Builder<'a> {
         self.0.serve_at(path, iface).map(Self)
     }
 
+    /// Register a [`DynamicInterface`] to be served at a given path.
+    ///
+    /// Same as [`Builder::serve_at`], for interfaces whose name is only known at run time.
+    pub fn serve_dynamic_at<P>(self, path: P, iface: DynamicInterface) -> Result<Self>
+    where
+        P: TryInto<ObjectPath<'a>>,
+        P::Error: Into<Error>,
+    {
+        self.0.serve_dynamic_at(path, iface).map(Self)
+    }
+
     /// Register a well-known name for this connection on the bus.
     ///
     /// This is similar to [`zbus::blocking::Connection::request_name`], except the name is
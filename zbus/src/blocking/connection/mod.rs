@@ -9,6 +9,7 @@ use zvariant::ObjectPath;
 
 use crate::{
     blocking::ObjectServer,
+    connection::stats::Statistics,
     fdo::{ConnectionCredentials, RequestNameFlags, RequestNameReply},
     message::Message,
     utils::block_on,
@@ -244,6 +245,13 @@ impl Connection {
         self.inner.monitor_activity()
     }
 
+    /// A snapshot of the statistics of the connection.
+    ///
+    /// See [`Statistics`] for details.
+    pub fn statistics(&self) -> Statistics {
+        self.inner.statistics()
+    }
+
     /// Returns the peer credentials.
     ///
     /// The fields are populated on the best effort basis. Some or all fields may not even make
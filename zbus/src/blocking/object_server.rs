@@ -1,10 +1,13 @@
 //! The object server API.
 
 use static_assertions::assert_impl_all;
+use zbus_names::InterfaceName;
 use zvariant::ObjectPath;
 
 use crate::{
-    object_server::{Interface, InterfaceDeref, InterfaceDerefMut, SignalContext},
+    object_server::{
+        DynamicInterface, Interface, InterfaceDeref, InterfaceDerefMut, NodeInfo, SignalContext,
+    },
     utils::block_on,
     Error, Result,
 };
@@ -157,6 +160,30 @@ impl ObjectServer {
         block_on(self.azync.at(path, iface))
     }
 
+    /// Register a [`DynamicInterface`] at a given path.
+    ///
+    /// See [`crate::ObjectServer::at_dynamic`] for details.
+    pub fn at_dynamic<'p, P>(&self, path: P, iface: DynamicInterface) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.at_dynamic(path, iface))
+    }
+
+    /// Register several D-Bus [`Interface`]s at a given path at once.
+    ///
+    /// See [`crate::ObjectServer::object`] for details.
+    pub fn object<'p, P>(&self, path: P) -> Result<ObjectBuilder<'_>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        Ok(ObjectBuilder {
+            azync: self.azync.object(path)?,
+        })
+    }
+
     /// Unregister a D-Bus [`Interface`] at a given path.
     ///
     /// If there are no more interfaces left at that path, destroys the object as well.
@@ -172,6 +199,52 @@ impl ObjectServer {
         block_on(self.azync.remove::<I, P>(path))
     }
 
+    /// Unregister the interface named `name` at a given path.
+    ///
+    /// See [`crate::ObjectServer::remove_interface`] for details.
+    pub fn remove_interface<'p, 'i, P, N>(&self, path: P, name: N) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+        N: TryInto<InterfaceName<'i>>,
+        N::Error: Into<Error>,
+    {
+        block_on(self.azync.remove_interface(path, name))
+    }
+
+    /// Unregister all the interfaces at a given path and all its descendants.
+    ///
+    /// See [`crate::ObjectServer::remove_subtree`] for details.
+    pub fn remove_subtree<'p, P>(&self, path: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.remove_subtree(path))
+    }
+
+    /// Whether there is an object at the given path.
+    ///
+    /// See [`crate::ObjectServer::has_object`] for details.
+    pub fn has_object<'p, P>(&self, path: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.has_object(path))
+    }
+
+    /// Walk the node at the given path and all its descendants.
+    ///
+    /// See [`crate::ObjectServer::nodes`] for details.
+    pub fn nodes<'p, P>(&self, path: P) -> Result<Vec<NodeInfo>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.nodes(path))
+    }
+
     /// Get the interface at the given path.
     ///
     /// # Errors
@@ -222,6 +295,19 @@ impl ObjectServer {
         })
     }
 
+    /// Get the [`DynamicInterface`] named `name` at the given path.
+    ///
+    /// See [`crate::ObjectServer::dynamic_interface`] for details.
+    pub fn dynamic_interface<'p, 'i, P, N>(&self, path: P, name: N) -> Result<DynamicInterface>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+        N: TryInto<InterfaceName<'i>>,
+        N::Error: Into<Error>,
+    {
+        block_on(self.azync.dynamic_interface(path, name))
+    }
+
     /// Get a reference to the underlying async ObjectServer.
     pub fn inner(&self) -> &crate::ObjectServer {
         &self.azync
@@ -233,6 +319,41 @@ impl ObjectServer {
     }
 }
 
+/// A builder for registering several interfaces at the same path at once.
+///
+/// Created by [`ObjectServer::object`]. See [`crate::ObjectServer::object`] for details.
+#[derive(Debug)]
+#[must_use = "the interfaces are only registered once `ObjectBuilder::register` is called"]
+pub struct ObjectBuilder<'s> {
+    azync: crate::object_server::ObjectBuilder<'s>,
+}
+
+impl ObjectBuilder<'_> {
+    /// Add an [`Interface`] to the object.
+    pub fn interface<I>(self, iface: I) -> Self
+    where
+        I: Interface,
+    {
+        Self {
+            azync: self.azync.interface(iface),
+        }
+    }
+
+    /// Add a [`DynamicInterface`] to the object.
+    pub fn dynamic_interface(self, iface: DynamicInterface) -> Self {
+        Self {
+            azync: self.azync.dynamic_interface(iface),
+        }
+    }
+
+    /// Register all the added interfaces.
+    ///
+    /// See [`crate::object_server::ObjectBuilder::register`] for details.
+    pub fn register(self) -> Result<()> {
+        block_on(self.azync.register())
+    }
+}
+
 impl From<crate::ObjectServer> for ObjectServer {
     fn from(azync: crate::ObjectServer) -> Self {
         Self { azync }
@@ -26,13 +26,14 @@ use zvariant::{ObjectPath, Str};
 use crate::{
     address::{self, Address},
     names::{InterfaceName, WellKnownName},
-    object_server::{ArcInterface, Interface},
+    object_server::{ArcInterface, DynamicInterface, Interface},
     Connection, Error, Executor, Guid, OwnedGuid, Result,
 };
 
 use super::{
     handshake::{AuthMechanism, Authenticated},
     socket::{BoxedSplit, ReadHalf, Split, WriteHalf},
+    Interceptor,
 };
 
 const DEFAULT_MAX_QUEUED: usize = 64;
@@ -72,6 +73,7 @@ pub struct Builder<'a> {
     unique_name: Option<crate::names::UniqueName<'a>>,
     cookie_context: Option<super::handshake::CookieContext<'a>>,
     cookie_id: Option<usize>,
+    interceptors: Vec<Box<dyn Interceptor>>,
 }
 
 assert_impl_all!(Builder<'_>: Send, Sync, Unpin);
@@ -304,6 +306,18 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Add an [`Interceptor`] for all the messages sent and received by the connection.
+    ///
+    /// Interceptors are run in the order they're added in. See [`Interceptor`] for details.
+    pub fn interceptor<I>(mut self, interceptor: I) -> Self
+    where
+        I: Interceptor,
+    {
+        self.interceptors.push(Box::new(interceptor));
+
+        self
+    }
+
     /// Register a D-Bus [`Interface`] to be served at a given path.
     ///
     /// This is similar to [`zbus::ObjectServer::at`], except that it allows you to have your
@@ -325,6 +339,23 @@ impl<'a> Builder<'a> {
         Ok(self)
     }
 
+    /// Register a [`DynamicInterface`] to be served at a given path.
+    ///
+    /// Same as [`Builder::serve_at`], for interfaces whose name is only known at run time.
+    pub fn serve_dynamic_at<P>(mut self, path: P, iface: DynamicInterface) -> Result<Self>
+    where
+        P: TryInto<ObjectPath<'a>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let (name, arc_iface) = iface.into_arc_interface();
+        self.interfaces
+            .entry(path)
+            .or_default()
+            .insert(name, arc_iface);
+        Ok(self)
+    }
+
     /// Register a well-known name for this connection on the bus.
     ///
     /// This is similar to [`zbus::Connection::request_name`], except the name is requested as part
@@ -453,21 +484,16 @@ impl<'a> Builder<'a> {
         let socket_read = auth.socket_read.take().unwrap();
         let already_received_bytes = auth.already_received_bytes.drain(..).collect();
 
-        let mut conn = Connection::new(auth, is_bus_conn, executor).await?;
+        let mut conn = Connection::new(auth, is_bus_conn, executor, self.interceptors).await?;
         conn.set_max_queued(self.max_queued.unwrap_or(DEFAULT_MAX_QUEUED));
 
         if !self.interfaces.is_empty() {
             let object_server = conn.sync_object_server(false, None);
             for (path, interfaces) in self.interfaces {
-                for (name, iface) in interfaces {
-                    let added = object_server
-                        .inner()
-                        .add_arc_interface(path.clone(), name.clone(), iface.clone())
-                        .await?;
-                    if !added {
-                        return Err(Error::InterfaceExists(name.clone(), path.to_owned()));
-                    }
-                }
+                object_server
+                    .inner()
+                    .add_arc_interfaces(path, interfaces.into_iter().collect())
+                    .await?;
             }
 
             let started_event = Event::new();
@@ -502,6 +528,7 @@ impl<'a> Builder<'a> {
             unique_name: None,
             cookie_id: None,
             cookie_context: None,
+            interceptors: vec![],
         }
     }
 
@@ -0,0 +1,187 @@
+//! Connection-level message interception.
+//!
+//! See [`Interceptor`] for details.
+
+use std::fmt;
+
+use tracing::trace;
+
+use crate::{DBusError, Message};
+
+/// A hook into all the messages going through a [`Connection`].
+///
+/// Interceptors are added to a connection through [`Builder::interceptor`]. They see every message
+/// the connection sends, right before it's written to the socket, and every message it receives,
+/// right after it's read from the socket and before it's dispatched to any message stream, proxy or
+/// to the [`ObjectServer`]. This makes them a good fit for cross-cutting concerns, such as
+/// auditing, collecting metrics, rewriting headers or enforcing access policies.
+///
+/// Each hook returns an [`Action`], telling the connection what to do with the message. The
+/// default implementations let all messages through unchanged.
+///
+/// When several interceptors are added, they're run in the order they were added in. The message
+/// returned by an interceptor is handed to the next one and an interceptor that doesn't let the
+/// message through, stops the chain.
+///
+/// # Example
+///
+/// An interceptor that rejects all calls to a specific interface:
+///
+/// ```
+/// use zbus::{
+///     connection::interceptor::{Action, Interceptor},
+///     fdo, Message,
+/// };
+///
+/// #[derive(Debug)]
+/// struct Firewall;
+///
+/// #[zbus::export::async_trait::async_trait]
+/// impl Interceptor for Firewall {
+///     async fn incoming(&self, msg: Message) -> Action {
+///         match msg.header().interface() {
+///             Some(iface) if iface == "org.example.Admin" => Action::reject(
+///                 fdo::Error::AccessDenied("admin interface is disabled".to_string()),
+///             ),
+///             _ => Action::Pass(msg),
+///         }
+///     }
+/// }
+///
+/// # zbus::block_on(async {
+/// let server = zbus::connection::Builder::session()?
+///     .interceptor(Firewall)
+///     .build()
+///     .await?;
+/// let client = zbus::Connection::session().await?;
+///
+/// let err = client
+///     .call_method(
+///         server.unique_name(),
+///         "/org/example/Admin",
+///         Some("org.example.Admin"),
+///         "Shutdown",
+///         &(),
+///     )
+///     .await
+///     .unwrap_err();
+/// assert!(matches!(fdo::Error::from(err), fdo::Error::AccessDenied(_)));
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`Connection`]: crate::Connection
+/// [`Builder::interceptor`]: crate::connection::Builder::interceptor
+/// [`ObjectServer`]: crate::ObjectServer
+#[async_trait::async_trait]
+pub trait Interceptor: fmt::Debug + Send + Sync + 'static {
+    /// Intercept a message about to be sent.
+    ///
+    /// If a method call is rejected, the error is returned to the caller as if it came from the
+    /// peer. Note that method calls are matched with their replies through their serial number, so
+    /// it must be kept when modifying them (e.g by building the new message from
+    /// [`message::Builder::from`] the original header). Dropping a method call leaves the caller
+    /// waiting for a reply that will never come, so it's best to reject them instead.
+    ///
+    /// [`message::Builder::from`]: crate::message::Builder
+    async fn outgoing(&self, msg: Message) -> Action {
+        Action::Pass(msg)
+    }
+
+    /// Intercept a received message.
+    ///
+    /// If a method call is rejected, the error is replied to the caller, unless it doesn't expect a
+    /// reply. Other rejected messages are dropped.
+    async fn incoming(&self, msg: Message) -> Action {
+        Action::Pass(msg)
+    }
+}
+
+/// What to do with an intercepted message.
+///
+/// See [`Interceptor`] for details.
+pub enum Action {
+    /// Let the message through, possibly modified.
+    Pass(Message),
+    /// Drop the message silently.
+    Drop,
+    /// Drop the message and short-circuit with an error reply.
+    Reject(Box<dyn DBusError + Send + Sync>),
+}
+
+impl Action {
+    /// Reject the message with `error`.
+    pub fn reject<E>(error: E) -> Self
+    where
+        E: DBusError + Send + Sync + 'static,
+    {
+        Self::Reject(Box::new(error))
+    }
+}
+
+impl fmt::Debug for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pass(msg) => f.debug_tuple("Pass").field(msg).finish(),
+            Self::Drop => f.write_str("Drop"),
+            Self::Reject(e) => f
+                .debug_struct("Reject")
+                .field("name", &e.name())
+                .field("description", &e.description())
+                .finish(),
+        }
+    }
+}
+
+/// The chain of interceptors of a connection.
+#[derive(Debug, Default)]
+pub(crate) struct Interceptors(Vec<Box<dyn Interceptor>>);
+
+impl Interceptors {
+    pub fn new(interceptors: Vec<Box<dyn Interceptor>>) -> Self {
+        Self(interceptors)
+    }
+
+    /// Whether there are no interceptors, i.e messages don't need to go through the chain.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub async fn outgoing(&self, mut msg: Message) -> Action {
+        for interceptor in &self.0 {
+            match interceptor.outgoing(msg).await {
+                Action::Pass(m) => msg = m,
+                action => {
+                    trace!(
+                        "Outgoing message intercepted by {:?}: {:?}",
+                        interceptor,
+                        action
+                    );
+
+                    return action;
+                }
+            }
+        }
+
+        Action::Pass(msg)
+    }
+
+    pub async fn incoming(&self, mut msg: Message) -> Action {
+        for interceptor in &self.0 {
+            match interceptor.incoming(msg).await {
+                Action::Pass(m) => msg = m,
+                action => {
+                    trace!(
+                        "Incoming message intercepted by {:?}: {:?}",
+                        interceptor,
+                        action
+                    );
+
+                    return action;
+                }
+            }
+        }
+
+        Action::Pass(msg)
+    }
+}
@@ -39,6 +39,13 @@ pub use socket::Socket;
 mod socket_reader;
 use socket_reader::SocketReader;
 
+pub mod interceptor;
+pub use interceptor::Interceptor;
+use interceptor::{Action, Interceptors};
+
+pub mod stats;
+use stats::{Collector, PendingCall, Statistics};
+
 pub(crate) mod handshake;
 use handshake::Authenticated;
 
@@ -58,6 +65,8 @@ pub(crate) struct ConnectionInner {
 
     activity_event: Arc<Event>,
     socket_write: Mutex<Box<dyn socket::WriteHalf>>,
+    interceptors: Arc<Interceptors>,
+    stats: Arc<Collector>,
 
     // Our executor
     executor: Executor<'static>,
@@ -204,6 +213,7 @@ assert_impl_all!(Connection: Send, Sync, Unpin);
 pub(crate) struct PendingMethodCall {
     stream: Option<MessageStream>,
     serial: NonZeroU32,
+    call: Option<PendingCall>,
 }
 
 impl Future for PendingMethodCall {
@@ -246,6 +256,9 @@ impl OrderedFuture for PendingMethodCall {
                             _ => continue,
                         };
                         this.stream = None;
+                        if let Some(call) = this.call.take() {
+                            call.replied();
+                        }
                         return Poll::Ready(Some((ordering, res)));
                     }
                     Poll::Ready(PollResult::Item {
@@ -272,6 +285,20 @@ impl OrderedFuture for PendingMethodCall {
 impl Connection {
     /// Send `msg` to the peer.
     pub async fn send(&self, msg: &Message) -> Result<()> {
+        // Only clone the message if an interceptor actually needs it.
+        let intercepted;
+        let msg = if self.inner.interceptors.is_empty() {
+            msg
+        } else {
+            intercepted = match self.inner.interceptors.outgoing(msg.clone()).await {
+                Action::Pass(msg) => msg,
+                Action::Drop => return Ok(()),
+                Action::Reject(e) => return Err(e.create_reply(&msg.header())?.into()),
+            };
+
+            &intercepted
+        };
+
         #[cfg(unix)]
         if !msg.data().fds().is_empty() && !self.inner.cap_unix_fd {
             return Err(Error::Unsupported);
@@ -280,7 +307,10 @@ impl Connection {
         self.inner.activity_event.notify(usize::MAX);
         let mut write = self.inner.socket_write.lock().await;
 
-        write.send_message(msg).await
+        write.send_message(msg).await?;
+        self.inner.stats.message_sent(msg);
+
+        Ok(())
     }
 
     /// Send a method call.
@@ -374,11 +404,17 @@ impl Connection {
             self,
         ));
         let serial = msg.primary_header().serial_num();
+        let call = (!flags.contains(Flags::NoReplyExpected))
+            .then(|| PendingCall::new(self.inner.stats.clone(), &msg));
         self.send(&msg).await?;
         if flags.contains(Flags::NoReplyExpected) {
             Ok(None)
         } else {
-            Ok(Some(PendingMethodCall { stream, serial }))
+            Ok(Some(PendingMethodCall {
+                stream,
+                serial,
+                call,
+            }))
         }
     }
 
@@ -1032,6 +1068,7 @@ impl Connection {
                         .await?;
                 }
                 e.insert((1, receiver.clone().deactivate()));
+                self.inner.stats.subscribed();
                 self.inner
                     .msg_senders
                     .lock()
@@ -1075,6 +1112,7 @@ impl Connection {
                             .await?;
                     }
                     e.remove();
+                    self.inner.stats.unsubscribed();
                     self.inner
                         .msg_senders
                         .lock()
@@ -1098,6 +1136,7 @@ impl Connection {
         auth: Authenticated,
         #[allow(unused)] bus_connection: bool,
         executor: Executor<'static>,
+        interceptors: Vec<Box<dyn Interceptor>>,
     ) -> Result<Self> {
         #[cfg(unix)]
         let cap_unix_fd = auth.cap_unix_fd;
@@ -1133,6 +1172,8 @@ impl Connection {
             inner: Arc::new(ConnectionInner {
                 activity_event: Arc::new(Event::new()),
                 socket_write: Mutex::new(auth.socket_write),
+                interceptors: Arc::new(Interceptors::new(interceptors)),
+                stats: Arc::new(Collector::default()),
                 server_guid: auth.server_guid,
                 #[cfg(unix)]
                 cap_unix_fd,
@@ -1175,6 +1216,13 @@ impl Connection {
         self.inner.activity_event.listen()
     }
 
+    /// A snapshot of the statistics of the connection.
+    ///
+    /// See [`Statistics`] for details.
+    pub fn statistics(&self) -> Statistics {
+        self.inner.stats.snapshot()
+    }
+
     /// Returns the peer credentials.
     ///
     /// The fields are populated on the best effort basis. Some or all fields may not even make
@@ -1220,6 +1268,9 @@ impl Connection {
                     inner.msg_senders.clone(),
                     already_read,
                     inner.activity_event.clone(),
+                    inner.interceptors.clone(),
+                    inner.stats.clone(),
+                    WeakConnection::from(self),
                 )
                 .spawn(&inner.executor),
             )
@@ -1701,4 +1752,185 @@ mod p2p_tests {
 
         (conn1, conn2)
     }
+
+    #[derive(Debug)]
+    struct Firewall;
+
+    #[async_trait::async_trait]
+    impl Interceptor for Firewall {
+        async fn incoming(&self, msg: Message) -> Action {
+            match msg.header().interface() {
+                Some(iface) if iface == "org.zbus.Secret" => {
+                    Action::reject(fdo::Error::AccessDenied("no secrets".to_string()))
+                }
+                _ => Action::Pass(msg),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct Rewriter;
+
+    #[async_trait::async_trait]
+    impl Interceptor for Rewriter {
+        async fn outgoing(&self, msg: Message) -> Action {
+            let header = msg.header();
+            match header.member().map(|m| m.as_str()) {
+                Some("Dropped") => Action::Drop,
+                // Keeps the serial number of the original call.
+                Some("Ding") => crate::message::Builder::from(header.clone())
+                    .member("Ping")
+                    .and_then(|b| b.build(&()))
+                    .map_or_else(|e| Action::reject(fdo::Error::from(e)), Action::Pass),
+                _ => Action::Pass(msg),
+            }
+        }
+    }
+
+    struct Pinger;
+
+    #[crate::interface(name = "org.zbus.Pinger")]
+    impl Pinger {
+        fn ping(&self) -> &str {
+            "pong"
+        }
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn interceptors() {
+        crate::utils::block_on(test_interceptors()).unwrap();
+    }
+
+    async fn test_interceptors() -> Result<()> {
+        let (a, b) = socket::Channel::pair();
+        let guid = crate::Guid::generate();
+        let server = Builder::authenticated_socket(a, guid.clone())?
+            .p2p()
+            .serve_at("/org/zbus/Pinger", Pinger)?
+            .interceptor(Firewall)
+            .build()
+            .await?;
+        let client = Builder::authenticated_socket(b, guid)?
+            .p2p()
+            .interceptor(Rewriter)
+            .build()
+            .await?;
+
+        // Rejected by the server, before reaching the object server.
+        let err = client
+            .call_method(
+                None::<()>,
+                "/org/zbus/Pinger",
+                Some("org.zbus.Secret"),
+                "Ping",
+                &(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(fdo::Error::from(err), fdo::Error::AccessDenied(_)));
+
+        // Rewritten by the client.
+        let reply = client
+            .call_method(
+                None::<()>,
+                "/org/zbus/Pinger",
+                Some("org.zbus.Pinger"),
+                "Ding",
+                &(),
+            )
+            .await?;
+        assert_eq!(reply.body().deserialize::<&str>()?, "pong");
+
+        // Dropped by the client.
+        let mut stream = MessageStream::for_match_rule(
+            MatchRule::builder().msg_type(Type::Signal).build(),
+            &server,
+            None,
+        )
+        .await?;
+        for member in ["Dropped", "Kept"] {
+            client
+                .emit_signal(
+                    None::<()>,
+                    "/org/zbus/Pinger",
+                    "org.zbus.Pinger",
+                    member,
+                    &(),
+                )
+                .await?;
+        }
+        let signal = stream.next().await.unwrap()?;
+        assert_eq!(signal.header().member().unwrap(), "Kept");
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn statistics() {
+        crate::utils::block_on(test_statistics()).unwrap();
+    }
+
+    async fn test_statistics() -> Result<()> {
+        let (a, b) = socket::Channel::pair();
+        let guid = crate::Guid::generate();
+        let server = Builder::authenticated_socket(a, guid.clone())?
+            .p2p()
+            .serve_at("/org/zbus/Pinger", Pinger)?
+            .serve_at("/org/zbus/Pinger", fdo::Stats)?
+            .build()
+            .await?;
+        let client = Builder::authenticated_socket(b, guid)?
+            .p2p()
+            .build()
+            .await?;
+
+        for _ in 0..2 {
+            client
+                .call_method(
+                    None::<()>,
+                    "/org/zbus/Pinger",
+                    Some("org.zbus.Pinger"),
+                    "Ping",
+                    &(),
+                )
+                .await?;
+        }
+
+        let stats = client.statistics();
+        assert_eq!(stats.sent().method_calls(), 2);
+        assert_eq!(stats.sent().messages(), 2);
+        assert_eq!(stats.received().method_returns(), 2);
+        assert_eq!(stats.received().messages(), 2);
+        assert!(stats.sent().bytes() > 0);
+        assert_eq!(stats.pending_calls(), 0);
+        let pings = stats.method_call(Some("org.zbus.Pinger"), "Ping").unwrap();
+        assert_eq!(pings.count(), 2);
+        assert_eq!(pings.buckets().map(|(_, count)| count).sum::<u64>(), 2);
+        assert!(pings.max() >= pings.mean().unwrap());
+
+        // Query the server remotely.
+        let reply = client
+            .call_method(
+                None::<()>,
+                "/org/zbus/Pinger",
+                Some("org.freedesktop.DBus.Debug.Stats"),
+                "GetStats",
+                &(),
+            )
+            .await?;
+        let body = reply.body();
+        let remote: HashMap<String, zvariant::OwnedValue> = body.deserialize()?;
+        assert_eq!(u64::try_from(&remote["IncomingMethodCalls"])?, 3);
+        assert_eq!(u64::try_from(&remote["OutgoingMethodReturns"])?, 2);
+        assert_eq!(u32::try_from(&remote["IncomingMessages"])?, 3);
+        assert!(u32::try_from(&remote["MatchRules"])? >= 1);
+        assert_eq!(
+            server.statistics().received().bytes(),
+            client.statistics().sent().bytes()
+        );
+
+        Ok(())
+    }
 }
@@ -4,10 +4,18 @@ use event_listener::Event;
 use tracing::{debug, instrument, trace};
 
 use crate::{
-    async_lock::Mutex, connection::MsgBroadcaster, Executor, Message, OwnedMatchRule, Task,
+    async_lock::Mutex,
+    connection::MsgBroadcaster,
+    message::{Flags, Type},
+    DBusError, Executor, Message, OwnedMatchRule, Task,
 };
 
-use super::socket::ReadHalf;
+use super::{
+    interceptor::{Action, Interceptors},
+    socket::ReadHalf,
+    stats::Collector,
+    WeakConnection,
+};
 
 #[derive(Debug)]
 pub(crate) struct SocketReader {
@@ -16,6 +24,9 @@ pub(crate) struct SocketReader {
     already_received_bytes: Vec<u8>,
     prev_seq: u64,
     activity_event: Arc<Event>,
+    interceptors: Arc<Interceptors>,
+    stats: Arc<Collector>,
+    conn: WeakConnection,
 }
 
 impl SocketReader {
@@ -24,6 +35,9 @@ impl SocketReader {
         senders: Arc<Mutex<HashMap<Option<OwnedMatchRule>, MsgBroadcaster>>>,
         already_received_bytes: Vec<u8>,
         activity_event: Arc<Event>,
+        interceptors: Arc<Interceptors>,
+        stats: Arc<Collector>,
+        conn: WeakConnection,
     ) -> Self {
         Self {
             socket,
@@ -31,6 +45,9 @@ impl SocketReader {
             already_received_bytes,
             prev_seq: 0,
             activity_event,
+            interceptors,
+            stats,
+            conn,
         }
     }
 
@@ -43,7 +60,13 @@ impl SocketReader {
     async fn receive_msg(mut self) {
         loop {
             trace!("Waiting for message on the socket..");
-            let msg = self.read_socket().await;
+            let msg = match self.read_socket().await {
+                Ok(msg) => match self.intercept(msg).await {
+                    Some(msg) => Ok(msg),
+                    None => continue,
+                },
+                Err(e) => Err(e),
+            };
             match &msg {
                 Ok(msg) => trace!("Message received on the socket: {:?}", msg),
                 Err(e) => trace!("Error reading from the socket: {:?}", e),
@@ -65,6 +88,9 @@ impl SocketReader {
                     }
                 }
 
+                if sender.is_full() {
+                    self.stats.queue_overflowed();
+                }
                 if let Err(e) = sender.broadcast_direct(msg.clone()).await {
                     // An error would be due to either of these:
                     //
@@ -90,6 +116,60 @@ impl SocketReader {
         }
     }
 
+    // Run the message through the interceptors, returning it if it should be broadcasted.
+    async fn intercept(&self, msg: Message) -> Option<Message> {
+        if self.interceptors.is_empty() {
+            return Some(msg);
+        }
+
+        match self.interceptors.incoming(msg.clone()).await {
+            // Keep the receive sequence of the original message, for ordering.
+            Action::Pass(msg) => Some(msg.with_recv_seq(self.prev_seq)),
+            Action::Drop => None,
+            Action::Reject(e) => {
+                if msg.message_type() == Type::MethodCall
+                    && !msg
+                        .primary_header()
+                        .flags()
+                        .contains(Flags::NoReplyExpected)
+                {
+                    if let Err(e) = self.reply_error(&msg, &*e) {
+                        debug!("Error replying to a rejected method call: {:?}", e);
+                    }
+                }
+
+                None
+            }
+        }
+    }
+
+    // The reply is sent from a separate task, so that reading isn't held up by a full socket or
+    // output queue.
+    fn reply_error(
+        &self,
+        call: &Message,
+        error: &(dyn DBusError + Send + Sync),
+    ) -> crate::Result<()> {
+        let reply = error.create_reply(&call.header())?;
+        let conn = match self.conn.upgrade() {
+            Some(conn) => conn,
+            None => return Ok(()),
+        };
+        let executor = conn.executor().clone();
+        executor
+            .spawn(
+                async move {
+                    if let Err(e) = conn.send(&reply).await {
+                        debug!("Error replying to a rejected method call: {:?}", e);
+                    }
+                },
+                "rejected method call reply",
+            )
+            .detach();
+
+        Ok(())
+    }
+
     #[instrument]
     async fn read_socket(&mut self) -> crate::Result<Message> {
         self.activity_event.notify(usize::MAX);
@@ -99,6 +179,7 @@ impl SocketReader {
             .receive_message(seq, &mut self.already_received_bytes)
             .await?;
         self.prev_seq = seq;
+        self.stats.message_received(&msg);
 
         Ok(msg)
     }
@@ -0,0 +1,346 @@
+//! Connection statistics.
+//!
+//! See [`Statistics`] for details.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use zbus_names::{OwnedInterfaceName, OwnedMemberName};
+
+use crate::message::{Message, Type};
+
+/// The upper bounds of the [`LatencyHistogram`] buckets, the last bucket being unbounded.
+const LATENCY_BUCKETS: [Duration; 8] = [
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+];
+
+/// The interface (if any) and member of a method.
+pub type Method = (Option<OwnedInterfaceName>, OwnedMemberName);
+
+/// A snapshot of the statistics of a [`Connection`].
+///
+/// Use [`Connection::statistics`] to get one. All the counters start at zero when the connection
+/// is created and are never reset.
+///
+/// [`Connection`]: crate::Connection
+/// [`Connection::statistics`]: crate::Connection::statistics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Statistics {
+    sent: Traffic,
+    received: Traffic,
+    pending_calls: u64,
+    subscriptions: u64,
+    queue_overflows: u64,
+    method_calls: HashMap<Method, LatencyHistogram>,
+}
+
+impl Statistics {
+    /// The traffic sent by the connection.
+    ///
+    /// This only includes the messages actually written to the socket, so messages dropped by an
+    /// [`Interceptor`] are not counted.
+    ///
+    /// [`Interceptor`]: crate::connection::Interceptor
+    pub fn sent(&self) -> &Traffic {
+        &self.sent
+    }
+
+    /// The traffic received by the connection.
+    ///
+    /// This includes all the messages read from the socket, even the ones dropped by an
+    /// [`Interceptor`] afterwards.
+    ///
+    /// [`Interceptor`]: crate::connection::Interceptor
+    pub fn received(&self) -> &Traffic {
+        &self.received
+    }
+
+    /// The number of method calls awaiting a reply.
+    pub fn pending_calls(&self) -> u64 {
+        self.pending_calls
+    }
+
+    /// The number of match rules the connection is subscribed to.
+    pub fn subscriptions(&self) -> u64 {
+        self.subscriptions
+    }
+
+    /// The number of times a received message couldn't be queued right away, because a queue was
+    /// full.
+    ///
+    /// While a queue is full, the connection stops reading from the socket. A high number here
+    /// usually means that some message streams are not polled often enough, or that their
+    /// capacity (see [`Connection::set_max_queued`]) is too low.
+    ///
+    /// [`Connection::set_max_queued`]: crate::Connection::set_max_queued
+    pub fn queue_overflows(&self) -> u64 {
+        self.queue_overflows
+    }
+
+    /// The latencies of the method calls made through the connection, per method.
+    ///
+    /// The latency of a call is the time from sending it to receiving its reply. Calls that don't
+    /// expect a reply, or for which the reply was not awaited, are not included.
+    pub fn method_calls(&self) -> &HashMap<Method, LatencyHistogram> {
+        &self.method_calls
+    }
+
+    /// The latencies of the calls to `member` of `interface`.
+    pub fn method_call(&self, interface: Option<&str>, member: &str) -> Option<&LatencyHistogram> {
+        self.method_calls
+            .iter()
+            .find(|((i, m), _)| i.as_ref().map(|i| i.as_str()) == interface && m.as_str() == member)
+            .map(|(_, h)| h)
+    }
+}
+
+/// Message traffic in one direction.
+///
+/// See [`Statistics`] for details.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Traffic {
+    method_calls: u64,
+    method_returns: u64,
+    errors: u64,
+    signals: u64,
+    bytes: u64,
+    fds: u64,
+}
+
+impl Traffic {
+    /// The total number of messages.
+    pub fn messages(&self) -> u64 {
+        self.method_calls + self.method_returns + self.errors + self.signals
+    }
+
+    /// The number of method calls.
+    pub fn method_calls(&self) -> u64 {
+        self.method_calls
+    }
+
+    /// The number of method returns.
+    pub fn method_returns(&self) -> u64 {
+        self.method_returns
+    }
+
+    /// The number of errors.
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// The number of signals.
+    pub fn signals(&self) -> u64 {
+        self.signals
+    }
+
+    /// The number of bytes, headers included.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// The number of file descriptors passed.
+    pub fn fds(&self) -> u64 {
+        self.fds
+    }
+}
+
+/// A histogram of method call latencies.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    counts: [u64; LATENCY_BUCKETS.len() + 1],
+    sum: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    /// The number of calls.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The sum of the latencies of all the calls.
+    pub fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    /// The highest latency.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// The mean latency, if there was any call.
+    pub fn mean(&self) -> Option<Duration> {
+        match self.count() {
+            0 => None,
+            count => Some(self.sum / count as u32),
+        }
+    }
+
+    /// The buckets of the histogram.
+    ///
+    /// Each bucket is its inclusive upper bound and the number of calls with a latency in it. The
+    /// last bucket has no upper bound.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        LATENCY_BUCKETS
+            .iter()
+            .copied()
+            .map(Some)
+            .chain(std::iter::once(None))
+            .zip(self.counts.iter().copied())
+    }
+
+    fn record(&mut self, latency: Duration) {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| latency <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.counts[bucket] += 1;
+        self.sum += latency;
+        self.max = self.max.max(latency);
+    }
+}
+
+/// Collects the statistics of a connection.
+#[derive(Debug, Default)]
+pub(crate) struct Collector {
+    sent: TrafficCounters,
+    received: TrafficCounters,
+    pending_calls: AtomicU64,
+    subscriptions: AtomicU64,
+    queue_overflows: AtomicU64,
+    method_calls: Mutex<HashMap<Method, LatencyHistogram>>,
+}
+
+impl Collector {
+    pub fn message_sent(&self, msg: &Message) {
+        self.sent.record(msg);
+    }
+
+    pub fn message_received(&self, msg: &Message) {
+        self.received.record(msg);
+    }
+
+    pub fn subscribed(&self) {
+        self.subscriptions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn unsubscribed(&self) {
+        self.subscriptions.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn queue_overflowed(&self) {
+        self.queue_overflows.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> Statistics {
+        Statistics {
+            sent: self.sent.snapshot(),
+            received: self.received.snapshot(),
+            pending_calls: self.pending_calls.load(Ordering::Relaxed),
+            subscriptions: self.subscriptions.load(Ordering::Relaxed),
+            queue_overflows: self.queue_overflows.load(Ordering::Relaxed),
+            method_calls: self.method_calls.lock().expect("lock poisoned").clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct TrafficCounters {
+    method_calls: AtomicU64,
+    method_returns: AtomicU64,
+    errors: AtomicU64,
+    signals: AtomicU64,
+    bytes: AtomicU64,
+    fds: AtomicU64,
+}
+
+impl TrafficCounters {
+    fn record(&self, msg: &Message) {
+        let counter = match msg.message_type() {
+            Type::MethodCall => &self.method_calls,
+            Type::MethodReturn => &self.method_returns,
+            Type::Error => &self.errors,
+            Type::Signal => &self.signals,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        let data = msg.data();
+        self.bytes.fetch_add(data.len() as u64, Ordering::Relaxed);
+        #[cfg(unix)]
+        self.fds
+            .fetch_add(data.fds().len() as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> Traffic {
+        Traffic {
+            method_calls: self.method_calls.load(Ordering::Relaxed),
+            method_returns: self.method_returns.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            signals: self.signals.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            fds: self.fds.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Tracks a method call awaiting its reply.
+///
+/// The call is counted as pending until this is dropped.
+#[derive(Debug)]
+pub(crate) struct PendingCall {
+    collector: Arc<Collector>,
+    method: Option<Method>,
+    started: Instant,
+}
+
+impl PendingCall {
+    pub fn new(collector: Arc<Collector>, call: &Message) -> Self {
+        collector.pending_calls.fetch_add(1, Ordering::Relaxed);
+        let header = call.header();
+        let method = header.member().map(|m| {
+            (
+                header.interface().map(|i| i.to_owned().into()),
+                m.to_owned().into(),
+            )
+        });
+
+        Self {
+            collector,
+            method,
+            started: Instant::now(),
+        }
+    }
+
+    /// Record the latency of the call, now that its reply was received.
+    pub fn replied(mut self) {
+        let latency = self.started.elapsed();
+        if let Some(method) = self.method.take() {
+            self.collector
+                .method_calls
+                .lock()
+                .expect("lock poisoned")
+                .entry(method)
+                .or_default()
+                .record(latency);
+        }
+    }
+}
+
+impl Drop for PendingCall {
+    fn drop(&mut self) {
+        self.collector.pending_calls.fetch_sub(1, Ordering::Relaxed);
+    }
+}
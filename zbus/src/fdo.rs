@@ -17,8 +17,8 @@ use zvariant::{
 };
 
 use crate::{
-    interface, message::Header, object_server::SignalContext, proxy, DBusError, ObjectServer,
-    OwnedGuid,
+    connection::stats::LatencyHistogram, interface, message::Header, object_server::SignalContext,
+    proxy, Connection, DBusError, ObjectServer, OwnedGuid,
 };
 
 #[rustfmt::skip]
@@ -173,7 +173,10 @@ impl Properties {
                 )));
             }
             zbus::object_server::DispatchResult::Async(f) => {
-                return f.await.map_err(Into::into);
+                return f.await.map_err(|e| match e {
+                    zbus::Error::FDO(e) => *e,
+                    e => e.into(),
+                });
             }
         }
         let res = iface
@@ -460,6 +463,95 @@ macro_rules! gen_stats_proxy {
 gen_stats_proxy!(true, false);
 assert_impl_all!(StatsProxy<'_>: Send, Sync, Unpin);
 
+/// Server-side implementation of the `GetStats` method of the `org.freedesktop.DBus.Debug.Stats`
+/// interface.
+///
+/// Serve it on a connection to let peers query the [`Statistics`] of that connection, e.g. to debug
+/// the traffic of a service. The statistics are returned as a dictionary, using the same keys as
+/// the per-connection statistics of the reference bus implementation where applicable:
+///
+/// * `IncomingMessages`, `IncomingBytes`, `IncomingFDs`, `OutgoingMessages`, `OutgoingBytes` and
+///   `OutgoingFDs` (`u`): the received and sent traffic.
+/// * `IncomingMethodCalls`, `IncomingMethodReturns`, `IncomingErrors`, `IncomingSignals` and the
+///   corresponding `Outgoing*` keys (`t`): the number of messages, per type.
+/// * `MatchRules` (`u`): the number of subscribed match rules.
+/// * `PendingMethodCalls` (`t`): the number of method calls awaiting a reply.
+/// * `QueueOverflows` (`t`): see [`Statistics::queue_overflows`].
+/// * `LatencyBuckets` (`at`): the upper bounds of the method call latency buckets, in
+///   microseconds. The last bucket is unbounded.
+/// * `MethodCallLatencies` (`a{sat}`): the number of calls in each latency bucket, per
+///   `interface.member` (or just `member` for calls without an interface).
+///
+/// [`Statistics`]: crate::connection::stats::Statistics
+/// [`Statistics::queue_overflows`]: crate::connection::stats::Statistics::queue_overflows
+#[derive(Debug, Clone)]
+pub struct Stats;
+
+#[interface(name = "org.freedesktop.DBus.Debug.Stats")]
+impl Stats {
+    fn get_stats(
+        &self,
+        #[zbus(connection)] conn: &Connection,
+    ) -> Result<HashMap<String, OwnedValue>> {
+        let stats = conn.statistics();
+        let mut dict = HashMap::new();
+        for (direction, traffic) in [("Incoming", stats.received()), ("Outgoing", stats.sent())] {
+            // The reference implementation uses 32-bit counters for these.
+            let clamp = |n: u64| OwnedValue::from(u32::try_from(n).unwrap_or(u32::MAX));
+            dict.insert(format!("{direction}Messages"), clamp(traffic.messages()));
+            dict.insert(format!("{direction}Bytes"), clamp(traffic.bytes()));
+            dict.insert(format!("{direction}FDs"), clamp(traffic.fds()));
+            for (kind, count) in [
+                ("MethodCalls", traffic.method_calls()),
+                ("MethodReturns", traffic.method_returns()),
+                ("Errors", traffic.errors()),
+                ("Signals", traffic.signals()),
+            ] {
+                dict.insert(format!("{direction}{kind}"), OwnedValue::from(count));
+            }
+        }
+        let match_rules = u32::try_from(stats.subscriptions()).unwrap_or(u32::MAX);
+        dict.insert("MatchRules".to_string(), OwnedValue::from(match_rules));
+        dict.insert(
+            "PendingMethodCalls".to_string(),
+            OwnedValue::from(stats.pending_calls()),
+        );
+        dict.insert(
+            "QueueOverflows".to_string(),
+            OwnedValue::from(stats.queue_overflows()),
+        );
+
+        let buckets: Vec<u64> = LatencyHistogram::default()
+            .buckets()
+            .filter_map(|(bound, _)| bound.map(|b| b.as_micros() as u64))
+            .collect();
+        let latencies: HashMap<String, Vec<u64>> = stats
+            .method_calls()
+            .iter()
+            .map(|((interface, member), histogram)| {
+                let method = match interface {
+                    Some(interface) => format!("{interface}.{member}"),
+                    None => member.to_string(),
+                };
+
+                (
+                    method,
+                    histogram.buckets().map(|(_, count)| count).collect(),
+                )
+            })
+            .collect();
+        for (key, value) in [
+            ("LatencyBuckets", Value::from(buckets)),
+            ("MethodCallLatencies", Value::from(latencies)),
+        ] {
+            let value = OwnedValue::try_from(value).map_err(crate::Error::from)?;
+            dict.insert(key.to_string(), value);
+        }
+
+        Ok(dict)
+    }
+}
+
 /// The flags used by the bus [`request_name`] method.
 ///
 /// [`request_name`]: struct.DBusProxy.html#method.request_name
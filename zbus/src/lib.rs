@@ -94,6 +94,9 @@ pub use abstractions::*;
 pub mod match_rule;
 pub use match_rule::{MatchRule, OwnedMatchRule};
 
+pub mod monitor;
+pub use monitor::Monitor;
+
 #[deprecated(since = "4.0.0", note = "Use `match_rule::Builder` instead")]
 #[doc(hidden)]
 pub use match_rule::Builder as MatchRuleBuilder;
@@ -169,6 +172,11 @@ pub use connection::Socket;
 
 pub mod blocking;
 
+pub mod pcap;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
 pub use zbus_macros::{interface, proxy, DBusError};
 // Old names used for backwards compatibility
 pub use zbus_macros::{dbus_interface, dbus_proxy};
@@ -1050,4 +1058,381 @@ mod tests {
             fn bar(&self) -> zbus::Result<(u32, zbus::zvariant::OwnedValue)>;
         }
     }
+
+    #[test]
+    #[timeout(15000)]
+    fn object_server_subtree() {
+        block_on(test_object_server_subtree()).unwrap();
+    }
+
+    async fn test_object_server_subtree() -> Result<()> {
+        use crate::fdo::{ObjectManager, ObjectManagerProxy};
+        use futures_util::StreamExt;
+
+        struct Device;
+
+        #[crate::interface(name = "org.zbus.Subtree.Device")]
+        impl Device {}
+
+        struct Battery;
+
+        #[crate::interface(name = "org.zbus.Subtree.Battery")]
+        impl Battery {}
+
+        let service = crate::connection::Builder::session()?
+            .serve_at("/org/zbus/Subtree", ObjectManager)?
+            .serve_at("/org/zbus/Subtree/devices/0", Device)?
+            .serve_at("/org/zbus/Subtree/devices/0", Battery)?
+            .serve_at("/org/zbus/Subtree/devices/0/child", Device)?
+            .serve_at("/org/zbus/Subtree/devices/1", Device)?
+            .build()
+            .await?;
+        let server = service.object_server();
+
+        assert!(server.has_object("/org/zbus/Subtree/devices").await?);
+        assert!(!server.has_object("/org/zbus/Subtree/devices/2").await?);
+
+        let nodes = server.nodes("/org/zbus/Subtree").await?;
+        let paths: Vec<_> = nodes.iter().map(|n| n.path().as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "/org/zbus/Subtree",
+                "/org/zbus/Subtree/devices",
+                "/org/zbus/Subtree/devices/0",
+                "/org/zbus/Subtree/devices/0/child",
+                "/org/zbus/Subtree/devices/1",
+            ]
+        );
+        assert_eq!(
+            nodes[0].interfaces(),
+            ["org.freedesktop.DBus.ObjectManager"]
+        );
+        assert!(nodes[1].interfaces().is_empty());
+        assert_eq!(nodes[1].children().len(), 2);
+        assert_eq!(
+            nodes[2].interfaces(),
+            ["org.zbus.Subtree.Battery", "org.zbus.Subtree.Device"]
+        );
+        assert!(server.nodes("/org/zbus/Nowhere").await?.is_empty());
+
+        let client = Connection::session().await?;
+        let manager = ObjectManagerProxy::builder(&client)
+            .destination(service.unique_name().unwrap())?
+            .path("/org/zbus/Subtree")?
+            .build()
+            .await?;
+        let mut removed = manager.receive_interfaces_removed().await?;
+
+        assert!(server.remove_subtree("/org/zbus/Subtree/devices/0").await?);
+        assert!(!server.remove_subtree("/org/zbus/Subtree/devices/0").await?);
+        assert!(
+            !server
+                .has_object("/org/zbus/Subtree/devices/0/child")
+                .await?
+        );
+        assert!(server.has_object("/org/zbus/Subtree/devices/1").await?);
+        // Empty ancestors are pruned, but only once they have no children left.
+        assert!(server.remove_subtree("/org/zbus/Subtree/devices/1").await?);
+        assert!(!server.has_object("/org/zbus/Subtree/devices").await?);
+        assert!(server.has_object("/org/zbus/Subtree").await?);
+
+        let mut signals = HashMap::new();
+        for _ in 0..3 {
+            let signal = removed.next().await.unwrap();
+            let args = signal.args()?;
+            let mut interfaces: Vec<String> =
+                args.interfaces().iter().map(|i| i.to_string()).collect();
+            interfaces.sort();
+            signals.insert(args.object_path().to_string(), interfaces);
+        }
+        assert_eq!(
+            signals["/org/zbus/Subtree/devices/0"],
+            ["org.zbus.Subtree.Battery", "org.zbus.Subtree.Device"]
+        );
+        assert_eq!(
+            signals["/org/zbus/Subtree/devices/0/child"],
+            ["org.zbus.Subtree.Device"]
+        );
+        assert_eq!(
+            signals["/org/zbus/Subtree/devices/1"],
+            ["org.zbus.Subtree.Device"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn object_server_atomic_object() {
+        block_on(test_object_server_atomic_object()).unwrap();
+    }
+
+    async fn test_object_server_atomic_object() -> Result<()> {
+        use crate::fdo::{ObjectManager, ObjectManagerProxy};
+        use futures_util::StreamExt;
+
+        struct Device;
+
+        #[crate::interface(name = "org.zbus.Atomic.Device")]
+        impl Device {}
+
+        struct Battery(u8);
+
+        #[crate::interface(name = "org.zbus.Atomic.Battery")]
+        impl Battery {
+            #[zbus(property)]
+            fn percentage(&self) -> u8 {
+                self.0
+            }
+        }
+
+        let service = crate::connection::Builder::session()?
+            .serve_at("/org/zbus/Atomic", ObjectManager)?
+            .build()
+            .await?;
+        let server = service.object_server();
+
+        let client = Connection::session().await?;
+        let manager = ObjectManagerProxy::builder(&client)
+            .destination(service.unique_name().unwrap())?
+            .path("/org/zbus/Atomic")?
+            .build()
+            .await?;
+        let mut added = manager.receive_interfaces_added().await?;
+
+        server
+            .object("/org/zbus/Atomic/0")?
+            .interface(Device)
+            .interface(Battery(42))
+            .register()
+            .await?;
+
+        let signal = added.next().await.unwrap();
+        let args = signal.args()?;
+        assert_eq!(args.object_path().as_str(), "/org/zbus/Atomic/0");
+        let interfaces = args.interfaces_and_properties();
+        assert_eq!(interfaces.len(), 2);
+        assert!(interfaces["org.zbus.Atomic.Device"].is_empty());
+        assert_eq!(
+            interfaces["org.zbus.Atomic.Battery"]["Percentage"],
+            zvariant::Value::U8(42)
+        );
+
+        // Nothing is registered if any of the interfaces already exists.
+        let err = server
+            .object("/org/zbus/Atomic/0")?
+            .interface(Device)
+            .interface(Battery(0))
+            .register()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::InterfaceExists(_, _)));
+        server.remove::<Battery, _>("/org/zbus/Atomic/0").await?;
+        let err = server
+            .object("/org/zbus/Atomic/0")?
+            .interface(Battery(0))
+            .interface(Device)
+            .register()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::InterfaceExists(_, _)));
+        assert_eq!(
+            server.nodes("/org/zbus/Atomic/0").await?[0].interfaces(),
+            ["org.zbus.Atomic.Device"]
+        );
+
+        // No object is created on errors, or without interfaces.
+        server
+            .object("/org/zbus/Atomic/1")?
+            .interface(Device)
+            .interface(Device)
+            .register()
+            .await
+            .unwrap_err();
+        server.object("/org/zbus/Atomic/2")?.register().await?;
+        assert!(!server.has_object("/org/zbus/Atomic/1").await?);
+        assert!(!server.has_object("/org/zbus/Atomic/2").await?);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn dynamic_interface() {
+        block_on(test_dynamic_interface()).unwrap();
+    }
+
+    #[test]
+    fn dynamic_property_signature() {
+        use crate::object_server::DynamicProperty;
+
+        assert!(DynamicProperty::new("a{sv}").is_ok());
+        assert!(matches!(
+            DynamicProperty::new("ss").unwrap_err(),
+            crate::Error::Failure(_)
+        ));
+        assert!(DynamicProperty::new("").is_err());
+        assert!(DynamicProperty::new("a{").is_err());
+    }
+
+    async fn test_dynamic_interface() -> Result<()> {
+        use crate::{
+            fdo::{self, PropertiesProxy},
+            object_server::{DynamicInterface, DynamicProperty},
+            zvariant::Structure,
+        };
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let brightness = Arc::new(AtomicU32::new(80));
+        let (get_brightness, set_brightness) = (brightness.clone(), brightness.clone());
+        let iface = DynamicInterface::builder("org.zbus.DynamicLamp")?
+            .method("Add", "uu", "u", |body| async move {
+                let args: Structure<'_> = body.deserialize()?;
+                let mut sum = 0;
+                for field in args.fields() {
+                    sum += u32::try_from(field).map_err(crate::Error::from)?;
+                }
+
+                Ok(vec![OwnedValue::from(sum)])
+            })?
+            .method("Broken", "", "s", |_| async move {
+                Ok(vec![OwnedValue::from(42u32)])
+            })?
+            .property(
+                "Brightness",
+                DynamicProperty::new("u")?
+                    .getter(move || {
+                        let brightness = get_brightness.clone();
+                        async move { Ok(OwnedValue::from(brightness.load(Ordering::SeqCst))) }
+                    })
+                    .setter(move |value| {
+                        let brightness = set_brightness.clone();
+                        async move {
+                            let value = u32::try_from(value).map_err(crate::Error::from)?;
+                            brightness.store(value, Ordering::SeqCst);
+                            Ok(())
+                        }
+                    }),
+            )?
+            .property(
+                "Model",
+                DynamicProperty::new("s")?
+                    .getter(|| async { Ok(OwnedValue::from(zvariant::Str::from("zbus"))) }),
+            )?
+            .signal("Flashed", "u")?
+            .build();
+
+        let path = "/org/zbus/DynamicLamp";
+        let service = crate::connection::Builder::session()?
+            .serve_dynamic_at(path, iface.clone())?
+            .build()
+            .await?;
+        let client = Connection::session().await?;
+        let dest = service.unique_name().unwrap().to_owned();
+        let registered = service
+            .object_server()
+            .dynamic_interface(path, "org.zbus.DynamicLamp")
+            .await?;
+        assert_eq!(registered.interface_name(), iface.interface_name());
+        service
+            .object_server()
+            .dynamic_interface(path, "org.zbus.Lamp")
+            .await
+            .unwrap_err();
+
+        let reply = client
+            .call_method(
+                Some(&dest),
+                path,
+                Some("org.zbus.DynamicLamp"),
+                "Add",
+                &(1u32, 2u32),
+            )
+            .await?;
+        assert_eq!(reply.body().deserialize::<u32>()?, 3);
+
+        // Wrong input arguments are rejected before reaching the handler.
+        let err = client
+            .call_method(Some(&dest), path, Some("org.zbus.DynamicLamp"), "Add", &"1")
+            .await
+            .unwrap_err();
+        assert!(matches!(fdo::Error::from(err), fdo::Error::InvalidArgs(_)));
+
+        // So are output values not matching the declared signature.
+        let err = client
+            .call_method(
+                Some(&dest),
+                path,
+                Some("org.zbus.DynamicLamp"),
+                "Broken",
+                &(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(fdo::Error::from(err), fdo::Error::Failed(_)));
+
+        let props = PropertiesProxy::builder(&client)
+            .destination(&dest)?
+            .path(path)?
+            .build()
+            .await?;
+        let iface_name = iface.interface_name().as_ref();
+        let value = props.get(iface_name.clone(), "Brightness").await?;
+        assert_eq!(u32::try_from(value)?, 80);
+        props
+            .set(
+                iface_name.clone(),
+                "Brightness",
+                &zvariant::Value::from(100u32),
+            )
+            .await?;
+        assert_eq!(brightness.load(Ordering::SeqCst), 100);
+        let err = props
+            .set(iface_name.clone(), "Model", &zvariant::Value::from("foo"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, fdo::Error::PropertyReadOnly(_)));
+        let all = props.get_all(Some(iface_name.clone()).into()).await?;
+        assert_eq!(all.len(), 2);
+
+        let xml = fdo::IntrospectableProxy::builder(&client)
+            .destination(&dest)?
+            .path(path)?
+            .build()
+            .await?
+            .introspect()
+            .await?;
+        let node = zbus_xml::Node::from_reader(xml.as_bytes()).unwrap();
+        let intro = node
+            .interfaces()
+            .iter()
+            .find(|i| i.name() == "org.zbus.DynamicLamp")
+            .unwrap();
+        let add = intro.methods().iter().find(|m| m.name() == "Add").unwrap();
+        assert_eq!(add.args().len(), 3);
+        assert_eq!(intro.signals()[0].name(), "Flashed");
+        let model = intro
+            .properties()
+            .iter()
+            .find(|p| p.name() == "Model")
+            .unwrap();
+        assert_eq!(model.access(), zbus_xml::PropertyAccess::Read);
+
+        let ctxt = SignalContext::new(&service, path)?;
+        iface.emit_signal(&ctxt, "Flashed", &[3u32.into()]).await?;
+        iface
+            .emit_signal(&ctxt, "Flashed", &["3".into()])
+            .await
+            .unwrap_err();
+
+        assert!(
+            service
+                .object_server()
+                .remove_interface(path, iface.interface_name())
+                .await?
+        );
+
+        Ok(())
+    }
 }
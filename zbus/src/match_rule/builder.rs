@@ -260,6 +260,30 @@ impl<'m> Builder<'m> {
         Ok(self)
     }
 
+    /// Set whether to eavesdrop on messages not meant for the subscriber.
+    ///
+    /// This is only useful with old buses, that don't support
+    /// [`fdo::MonitoringProxy::become_monitor`]. Most buses deny eavesdropping nowadays.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use zbus::MatchRule;
+    /// let rule = MatchRule::builder()
+    ///     .msg_type(zbus::message::Type::MethodCall)
+    ///     .eavesdrop(true)
+    ///     .build();
+    /// assert_eq!(rule.to_string(), "type='method_call',eavesdrop='true'");
+    /// assert_eq!(MatchRule::try_from("type='method_call',eavesdrop='true'").unwrap(), rule);
+    /// ```
+    ///
+    /// [`fdo::MonitoringProxy::become_monitor`]: crate::fdo::MonitoringProxy::become_monitor
+    pub fn eavesdrop(mut self, eavesdrop: bool) -> Self {
+        self.0.eavesdrop = eavesdrop;
+
+        self
+    }
+
     /// Create a builder for `MatchRule`.
     pub(crate) fn new() -> Self {
         Self(MatchRule {
@@ -272,6 +296,7 @@ impl<'m> Builder<'m> {
             args: Vec::with_capacity(MAX_ARGS as usize),
             arg_paths: Vec::with_capacity(MAX_ARGS as usize),
             arg0ns: None,
+            eavesdrop: false,
         })
     }
 }
@@ -100,6 +100,7 @@ pub struct MatchRule<'m> {
     pub(crate) args: Vec<(u8, Str<'m>)>,
     pub(crate) arg_paths: Vec<(u8, ObjectPath<'m>)>,
     pub(crate) arg0ns: Option<Str<'m>>,
+    pub(crate) eavesdrop: bool,
 }
 
 assert_impl_all!(MatchRule<'_>: Send, Sync, Unpin);
@@ -155,6 +156,11 @@ impl<'m> MatchRule<'m> {
         self.arg0ns.as_ref()
     }
 
+    /// Whether to eavesdrop on messages not meant for the subscriber.
+    pub fn eavesdrop(&self) -> bool {
+        self.eavesdrop
+    }
+
     /// Creates an owned clone of `self`.
     pub fn to_owned(&self) -> MatchRule<'static> {
         MatchRule {
@@ -171,6 +177,7 @@ impl<'m> MatchRule<'m> {
                 .map(|(i, p)| (*i, p.to_owned()))
                 .collect(),
             arg0ns: self.arg0ns.as_ref().map(|a| a.to_owned()),
+            eavesdrop: self.eavesdrop,
         }
     }
 
@@ -194,6 +201,7 @@ impl<'m> MatchRule<'m> {
                 .map(|(i, p)| (i, p.into_owned()))
                 .collect(),
             arg0ns: self.arg0ns.map(|a| a.into_owned()),
+            eavesdrop: self.eavesdrop,
         }
     }
 
@@ -369,6 +377,9 @@ impl Display for MatchRule<'_> {
             write_comma(f, &mut first_component)?;
             write!(f, "arg0namespace='{arg0namespace}'")?;
         }
+        if self.eavesdrop() {
+            write_match_rule_string_component(f, "eavesdrop", "true", &mut first_component)?;
+        }
 
         Ok(())
     }
@@ -436,6 +447,11 @@ impl<'m> TryFrom<&'m str> for MatchRule<'m> {
                 "path_namespace" => builder.path_namespace(value)?,
                 "destination" => builder.destination(value)?,
                 "arg0namespace" => builder.arg0ns(value)?,
+                "eavesdrop" => match value {
+                    "true" => builder.eavesdrop(true),
+                    "false" => builder.eavesdrop(false),
+                    _ => return Err(Error::InvalidMatchRule),
+                },
                 key if key.starts_with("arg") => {
                     if let Some(trailing_idx) = key.find("path") {
                         let idx = key[3..trailing_idx]
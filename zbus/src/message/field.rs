@@ -97,9 +97,8 @@ pub(super) enum Field<'f> {
 assert_impl_all!(Field<'_>: Send, Sync, Unpin);
 
 impl<'f> Type for Field<'f> {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("(yv)")
-    }
+    const SIGNATURE: &'static zvariant::StaticSignature =
+        &zvariant::StaticSignature::from_str_unchecked("(yv)");
 }
 
 impl<'f> Serialize for Field<'f> {
@@ -0,0 +1,401 @@
+use std::fmt::{self, Write};
+
+use zvariant::{
+    Array, Dict, ParsedSignature, Signature, SignatureEntry, Structure, StructureBuilder, Value,
+};
+
+use super::{Message, Type};
+
+/// The number of spaces each nesting level is indented with.
+const INDENT: usize = 3;
+
+/// Human-readable rendering of a [`Message`], headers and body included.
+///
+/// Use [`Message::format`] to get one. The default rendering follows the `dbus-monitor` output:
+/// a first line with the message type and header fields, followed by one line per argument of the
+/// body, decoded according to the body signature:
+///
+/// ```text
+/// method call sender=:1.42 -> destination=org.freedesktop.DBus serial=3 path=/org/freedesktop/DBus; interface=org.freedesktop.DBus; member=RequestName; signature=su
+///    string "org.zbus.Example"
+///    uint32 4
+/// ```
+///
+/// In [compact](Formatter::compact) mode, everything is rendered on a single line, with the body
+/// arguments in the GVariant text format, which is more suited for logs and tracing spans:
+///
+/// ```text
+/// method call sender=:1.42 -> destination=org.freedesktop.DBus serial=3 path=/org/freedesktop/DBus; interface=org.freedesktop.DBus; member=RequestName; signature=su ("org.zbus.Example", uint32 4)
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use zbus::message::Message;
+///
+/// let msg = Message::method("/org/freedesktop/DBus", "RequestName")?
+///     .destination("org.freedesktop.DBus")?
+///     .interface("org.freedesktop.DBus")?
+///     .build(&("org.zbus.Example", 4u32))?;
+///
+/// assert_eq!(
+///     msg.format().compact(true).to_string(),
+///     format!(
+///         "method call sender=(null sender) -> destination=org.freedesktop.DBus serial={} \
+///          path=/org/freedesktop/DBus; interface=org.freedesktop.DBus; member=RequestName; \
+///          signature=su (\"org.zbus.Example\", uint32 4)",
+///         msg.primary_header().serial_num(),
+///     ),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Formatter<'m> {
+    msg: &'m Message,
+    compact: bool,
+}
+
+impl<'m> Formatter<'m> {
+    pub(super) fn new(msg: &'m Message) -> Self {
+        Self {
+            msg,
+            compact: false,
+        }
+    }
+
+    /// Render the message on a single line.
+    ///
+    /// Disabled by default.
+    #[must_use]
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+
+        self
+    }
+
+    fn fmt_header(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = self.msg.header();
+        let ty = header.message_type();
+        f.write_str(match ty {
+            Type::MethodCall => "method call",
+            Type::MethodReturn => "method return",
+            Type::Error => "error",
+            Type::Signal => "signal",
+        })?;
+
+        match header.sender() {
+            Some(sender) => write!(f, " sender={sender}")?,
+            None => f.write_str(" sender=(null sender)")?,
+        }
+        match header.destination() {
+            Some(destination) => write!(f, " -> destination={destination}")?,
+            None => f.write_str(" -> destination=(null destination)")?,
+        }
+        write!(f, " serial={}", header.primary().serial_num())?;
+
+        let mut fields = Vec::with_capacity(4);
+        match ty {
+            Type::MethodCall | Type::Signal => {
+                if let Some(path) = header.path() {
+                    fields.push(format!("path={path}"));
+                }
+                if let Some(interface) = header.interface() {
+                    fields.push(format!("interface={interface}"));
+                }
+                if let Some(member) = header.member() {
+                    fields.push(format!("member={member}"));
+                }
+            }
+            Type::MethodReturn | Type::Error => {
+                if let Some(name) = header.error_name() {
+                    fields.push(format!("error_name={name}"));
+                }
+                if let Some(serial) = header.reply_serial() {
+                    fields.push(format!("reply_serial={serial}"));
+                }
+            }
+        }
+        if let Some(signature) = header.signature().filter(|s| !s.is_empty()) {
+            fields.push(format!("signature={signature}"));
+        }
+
+        if !fields.is_empty() {
+            write!(f, " {}", fields.join("; "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Formatter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_header(f)?;
+
+        let body = self.msg.body();
+        let signature = match body.signature() {
+            Some(signature) if !signature.is_empty() => ParsedSignature::new(&signature),
+            _ => return Ok(()),
+        };
+        let args = match body.deserialize::<Structure<'_>>().and_then(|args| {
+            args.into_fields()
+                .into_iter()
+                .zip(&signature)
+                .map(|(arg, signature)| rewrap(arg, signature))
+                .collect::<zvariant::Result<Vec<_>>>()
+                .map_err(Into::into)
+        }) {
+            Ok(args) => args,
+            Err(e) => {
+                let sep = if self.compact { " " } else { "\n" };
+                return write!(f, "{sep}<failed to decode body: {e}>");
+            }
+        };
+
+        if self.compact {
+            f.write_str(" (")?;
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{arg}")?;
+            }
+            f.write_char(')')
+        } else {
+            for arg in &args {
+                f.write_char('\n')?;
+                indent(f, 1)?;
+                fmt_value(f, arg, 1)?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Wrap back the variants of `value`, of type `signature`.
+///
+/// Variants are unwrapped when deserializing to a [`Value`], so without this we couldn't tell a
+/// variant holding a string from a string, for example.
+fn rewrap<'a>(value: Value<'a>, signature: &SignatureEntry) -> zvariant::Result<Value<'a>> {
+    match (value, signature) {
+        (value @ Value::Value(_), SignatureEntry::Variant) => Ok(value),
+        (value, SignatureEntry::Variant) => {
+            let signature = ParsedSignature::new(&value.value_signature());
+            let value = match signature.peek() {
+                Some(signature) => rewrap(value, signature)?,
+                None => value,
+            };
+
+            Ok(Value::Value(Box::new(value)))
+        }
+        (Value::Array(mut array), SignatureEntry::Array(element)) => {
+            let mut rewrapped = Array::new(Signature::from(&**element));
+            while let Some(v) = array.remove() {
+                rewrapped.append(rewrap(v, element)?)?;
+            }
+
+            Ok(Value::Array(rewrapped))
+        }
+        (Value::Dict(dict), signature) => {
+            let (key, value) = match signature.as_dict() {
+                Some(types) => types,
+                None => return Ok(Value::Dict(dict)),
+            };
+            let mut rewrapped = Dict::new(Signature::from(key), Signature::from(value));
+            for (k, v) in dict {
+                rewrapped.append(k, rewrap(v, value)?)?;
+            }
+
+            Ok(Value::Dict(rewrapped))
+        }
+        (Value::Structure(structure), SignatureEntry::Struct(fields)) => structure
+            .into_fields()
+            .into_iter()
+            .zip(fields)
+            .try_fold(StructureBuilder::new(), |builder, (field, signature)| {
+                rewrap(field, signature).map(|field| builder.append_field(field))
+            })
+            .map(|builder| Value::Structure(builder.build())),
+        (value, _) => Ok(value),
+    }
+}
+
+fn indent(f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+    write!(f, "{:1$}", "", depth * INDENT)
+}
+
+/// Write `value` at nesting level `depth`, in the `dbus-monitor` format.
+///
+/// The first line is written as is, while the following ones (if any) are indented.
+fn fmt_value(f: &mut fmt::Formatter<'_>, value: &Value<'_>, depth: usize) -> fmt::Result {
+    match value {
+        Value::U8(v) => write!(f, "byte {v}"),
+        Value::Bool(v) => write!(f, "boolean {v}"),
+        Value::I16(v) => write!(f, "int16 {v}"),
+        Value::U16(v) => write!(f, "uint16 {v}"),
+        Value::I32(v) => write!(f, "int32 {v}"),
+        Value::U32(v) => write!(f, "uint32 {v}"),
+        Value::I64(v) => write!(f, "int64 {v}"),
+        Value::U64(v) => write!(f, "uint64 {v}"),
+        Value::F64(v) => write!(f, "double {v}"),
+        Value::Str(v) => write!(f, "string {:?}", v.as_str()),
+        Value::Signature(v) => write!(f, "signature {:?}", v.as_str()),
+        Value::ObjectPath(v) => write!(f, "object path {:?}", v.as_str()),
+        Value::Value(v) => {
+            f.write_str("variant ")?;
+            fmt_value(f, v, depth)
+        }
+        Value::Array(array) => {
+            f.write_str("array [")?;
+            fmt_children(f, array.inner().iter(), depth)?;
+            f.write_char(']')
+        }
+        Value::Dict(dict) => {
+            f.write_str("array [")?;
+            for (key, value) in dict.iter() {
+                f.write_char('\n')?;
+                indent(f, depth + 1)?;
+                f.write_str("dict entry(")?;
+                fmt_children(f, [key, value].into_iter(), depth + 1)?;
+                f.write_char(')')?;
+            }
+            if dict.iter().next().is_some() {
+                f.write_char('\n')?;
+                indent(f, depth)?;
+            }
+            f.write_char(']')
+        }
+        Value::Structure(structure) => {
+            f.write_str("struct {")?;
+            fmt_children(f, structure.fields().iter(), depth)?;
+            f.write_char('}')
+        }
+        #[cfg(unix)]
+        Value::Fd(fd) => write!(f, "file descriptor {fd}"),
+        // Types that are not part of the D-Bus format, when enabled in zvariant.
+        #[allow(unreachable_patterns)]
+        value => write!(f, "{value}"),
+    }
+}
+
+/// Write `children` one per line, one level deeper than `depth`, and indent the closing line.
+fn fmt_children<'v, I>(f: &mut fmt::Formatter<'_>, children: I, depth: usize) -> fmt::Result
+where
+    I: Iterator<Item = &'v Value<'v>>,
+{
+    let mut empty = true;
+    for child in children {
+        empty = false;
+        f.write_char('\n')?;
+        indent(f, depth + 1)?;
+        fmt_value(f, child, depth + 1)?;
+    }
+    if !empty {
+        f.write_char('\n')?;
+        indent(f, depth)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use test_log::test;
+    use zvariant::{ObjectPath, Value};
+
+    use crate::message::Message;
+
+    #[test]
+    fn method_call() {
+        let mut props = HashMap::new();
+        props.insert("Count", Value::from(7u8));
+        let msg = Message::method("/org/zbus/Example", "Frob")
+            .unwrap()
+            .sender(":1.42")
+            .unwrap()
+            .destination("org.zbus.Example")
+            .unwrap()
+            .interface("org.zbus.Example")
+            .unwrap()
+            .build(&(
+                "hi",
+                vec![1u32, 2],
+                props,
+                (ObjectPath::from_static_str_unchecked("/"), true),
+                Value::from(-1i64),
+                Vec::<i16>::new(),
+            ))
+            .unwrap();
+        let serial = msg.primary_header().serial_num();
+
+        assert_eq!(
+            msg.format().to_string(),
+            format!(
+                "method call sender=:1.42 -> destination=org.zbus.Example serial={serial} \
+                 path=/org/zbus/Example; interface=org.zbus.Example; member=Frob; \
+                 signature=saua{{sv}}(ob)van
+   string \"hi\"
+   array [
+      uint32 1
+      uint32 2
+   ]
+   array [
+      dict entry(
+         string \"Count\"
+         variant byte 7
+      )
+   ]
+   struct {{
+      object path \"/\"
+      boolean true
+   }}
+   variant int64 -1
+   array []"
+            ),
+        );
+        assert_eq!(
+            msg.format().compact(true).to_string(),
+            format!(
+                "method call sender=:1.42 -> destination=org.zbus.Example serial={serial} \
+                 path=/org/zbus/Example; interface=org.zbus.Example; member=Frob; \
+                 signature=saua{{sv}}(ob)van \
+                 (\"hi\", [uint32 1, 2], {{\"Count\": <byte 0x07>}}, (objectpath \"/\", true), \
+                 <int64 -1>, @an [])"
+            ),
+        );
+    }
+
+    #[test]
+    fn replies() {
+        let call = Message::method("/", "Ping").unwrap().build(&()).unwrap();
+        let serial = call.primary_header().serial_num();
+
+        let reply = Message::method_reply(&call).unwrap().build(&()).unwrap();
+        assert_eq!(
+            reply.format().to_string(),
+            format!(
+                "method return sender=(null sender) -> destination=(null destination) \
+                 serial={} reply_serial={serial}",
+                reply.primary_header().serial_num(),
+            ),
+        );
+
+        let error = Message::method_error(&call, "org.zbus.Error.Oops")
+            .unwrap()
+            .build(&("it broke",))
+            .unwrap();
+        assert_eq!(
+            error.format().to_string(),
+            format!(
+                "error sender=(null sender) -> destination=(null destination) serial={} \
+                 error_name=org.zbus.Error.Oops; reply_serial={serial}; signature=s
+   string \"it broke\"",
+                error.primary_header().serial_num(),
+            ),
+        );
+    }
+}
@@ -1,4 +1,6 @@
 //! D-Bus Message.
+#[cfg(unix)]
+use std::os::fd::AsFd;
 use std::{fmt, num::NonZeroU32, sync::Arc};
 
 use static_assertions::assert_impl_all;
@@ -19,6 +21,9 @@ use fields::{Fields, QuickFields};
 mod body;
 pub use body::Body;
 
+mod format;
+pub use format::Formatter;
+
 pub(crate) mod header;
 use header::MIN_MESSAGE_SIZE;
 pub use header::{EndianSig, Flags, Header, PrimaryHeader, Type, NATIVE_ENDIAN_SIG};
@@ -156,6 +161,23 @@ impl Message {
         })
     }
 
+    /// The same message, with the given receive sequence.
+    pub(crate) fn with_recv_seq(self, recv_seq: u64) -> Self {
+        if self.inner.recv_seq.recv_seq == recv_seq {
+            return self;
+        }
+
+        Self {
+            inner: Arc::new(Inner {
+                primary_header: self.inner.primary_header.clone(),
+                quick_fields: self.inner.quick_fields,
+                bytes: self.inner.bytes.clone(),
+                body_offset: self.inner.body_offset,
+                recv_seq: Sequence { recv_seq },
+            }),
+        }
+    }
+
     pub fn primary_header(&self) -> &PrimaryHeader {
         &self.inner.primary_header
     }
@@ -278,6 +300,45 @@ impl Message {
         &self.inner.bytes
     }
 
+    /// The same message, in the given byte order.
+    ///
+    /// The header and the body are converted in place using [`serialized::swap_endian`], so
+    /// nothing gets decoded and re-encoded. The file descriptors (if any) are duplicated. If the
+    /// message is already in the given byte order, this is just a cheap clone.
+    pub fn to_endian(&self, endian: Endian) -> Result<Self> {
+        let data = self.data();
+        let ctxt = data.context();
+        if ctxt.endian() == endian {
+            return Ok(self.clone());
+        }
+
+        let body_offset = self.inner.body_offset;
+        let mut bytes = data.bytes().to_vec();
+        let (header, body) = bytes.split_at_mut(body_offset);
+        let swapped_ctxt =
+            serialized::swap_endian(header, ctxt, <Header<'_> as zvariant::Type>::signature())?;
+        if let Some(signature) = self.inner.quick_fields.signature(self) {
+            let body_ctxt = serialized::Context::new_dbus(ctxt.endian(), body_offset);
+            serialized::swap_endian(body, body_ctxt, signature)?;
+        }
+        bytes[0] = EndianSig::from(endian) as u8;
+
+        #[cfg(unix)]
+        let data = {
+            let fds = data
+                .fds()
+                .iter()
+                .map(|fd| fd.as_fd().try_clone_to_owned())
+                .collect::<std::io::Result<Vec<_>>>()?;
+
+            serialized::Data::new_fds(bytes, swapped_ctxt, fds)
+        };
+        #[cfg(not(unix))]
+        let data = serialized::Data::new(bytes, swapped_ctxt);
+
+        Self::from_raw_parts(data, self.inner.recv_seq.recv_seq)
+    }
+
     /// Get the receive ordering of a message.
     ///
     /// This may be used to identify how two events were ordered on the bus.  It only produces a
@@ -288,6 +349,15 @@ impl Message {
     pub fn recv_position(&self) -> Sequence {
         self.inner.recv_seq
     }
+
+    /// A human-readable rendering of the message, including its decoded body.
+    ///
+    /// Unlike the [`Display`](fmt::Display) implementation, which only gives a short summary, this
+    /// renders all the header fields and body arguments, like `dbus-monitor` does. See
+    /// [`Formatter`] for details.
+    pub fn format(&self) -> Formatter<'_> {
+        Formatter::new(self)
+    }
 }
 
 impl fmt::Debug for Message {
@@ -425,4 +495,76 @@ mod tests {
             .unwrap();
         assert_eq!(e.to_string(), "Error org.freedesktop.zbus.Error: kaboom!");
     }
+
+    #[test]
+    fn to_endian() {
+        use std::collections::HashMap;
+
+        use rand::{thread_rng, Rng};
+        use zvariant::{OwnedValue, BE, LE};
+
+        type Body = (
+            String,
+            Vec<u32>,
+            HashMap<String, OwnedValue>,
+            (u8, i64, bool),
+        );
+
+        let mut rng = thread_rng();
+        for _ in 0..50 {
+            let body: Body = (
+                format!("{}", rng.gen::<u64>()),
+                (0..rng.gen_range(0..8)).map(|_| rng.gen()).collect(),
+                (0..rng.gen_range(0..4))
+                    .map(|i| (format!("prop{i}"), OwnedValue::from(rng.gen::<u16>())))
+                    .collect(),
+                (rng.gen(), rng.gen(), rng.gen()),
+            );
+            let build = |endian| {
+                Message::signal("/org/zbus/Object", "org.zbus.Test", "Changed")
+                    .unwrap()
+                    .endian(endian)
+                    .sender(":1.42")
+                    .unwrap()
+                    .build(&body)
+                    .unwrap()
+            };
+            let le = build(LE);
+            let be = build(BE);
+
+            for (from, to) in [(&le, &be), (&be, &le)] {
+                let endian = to.data().context().endian();
+                let converted = from.to_endian(endian).unwrap();
+                assert_eq!(converted.data().context(), to.data().context());
+                assert_eq!(
+                    converted.primary_header().serial_num(),
+                    from.primary_header().serial_num()
+                );
+                // Only the serial numbers differ.
+                let mut bytes = converted.data().bytes().to_vec();
+                bytes[8..12].copy_from_slice(&to.data().bytes()[8..12]);
+                assert_eq!(bytes, to.data().bytes());
+                assert_eq!(converted.body().deserialize::<Body>().unwrap(), body);
+            }
+        }
+
+        #[cfg(unix)]
+        {
+            let stdout = std::io::stdout();
+            let m = Message::method("/", "do")
+                .unwrap()
+                .endian(LE)
+                .build(&(Fd::from(&stdout), "foo"))
+                .unwrap();
+            let converted = m.to_endian(BE).unwrap();
+            assert_eq!(converted.data().fds().len(), 1);
+            let body = converted.body();
+            let (_, s): (Fd<'_>, &str) = body.deserialize().unwrap();
+            assert_eq!(s, "foo");
+            assert_eq!(
+                converted.to_endian(LE).unwrap().data().bytes(),
+                m.data().bytes()
+            );
+        }
+    }
 }
@@ -0,0 +1,373 @@
+//! Bus monitoring.
+//!
+//! See [`Monitor`] for details.
+
+use std::{
+    num::NonZeroU32,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::stream;
+use futures_util::stream::FusedStream;
+use static_assertions::assert_impl_all;
+use tracing::{debug, trace};
+
+use crate::{
+    connection,
+    fdo::{self, DBusProxy, MonitoringProxy},
+    message::{Message, Sequence, Type},
+    names::{BusName, OwnedBusName, OwnedUniqueName, UniqueName},
+    proxy::CacheProperties,
+    Connection, MatchRule, MessageStream, Result,
+};
+
+/// A stream of all the messages going through a bus.
+///
+/// A `Monitor` opens a dedicated connection to the bus and turns it into a monitor connection,
+/// through [`fdo::MonitoringProxy::become_monitor`]. It then yields a copy of all the messages
+/// matching the given match rules, regardless of their sender and destination. An empty list of
+/// rules means all messages.
+///
+/// If the bus doesn't support the `org.freedesktop.DBus.Monitoring` interface, the monitor falls
+/// back to subscribing to the rules with [`MatchRule::eavesdrop`] set. Most buses deny
+/// eavesdropping though, in which case only the broadcast signals are received.
+///
+/// Messages addressed to the monitor connection itself (e.g the `NameLost` signal the bus sends
+/// when it becomes a monitor) are filtered out.
+///
+/// **NOTE**: Just like [`MessageStream`], a `Monitor` must be continuously polled or the messages
+/// will pile up and the connection will stop reading from the socket.
+///
+/// # Example
+///
+/// ```
+/// use futures_util::stream::TryStreamExt;
+/// use zbus::{message::Type, Connection, MatchRule, Monitor};
+///
+/// # zbus::block_on(async {
+/// let rule = MatchRule::builder()
+///     .msg_type(Type::Signal)
+///     .interface("org.zbus.MonitorExample")?
+///     .build();
+/// let mut monitor = Monitor::session(&[rule]).await?;
+///
+/// let conn = Connection::session().await?;
+/// conn.emit_signal(
+///     None::<()>,
+///     "/org/zbus/MonitorExample",
+///     "org.zbus.MonitorExample",
+///     "Hello",
+///     &(),
+/// )
+/// .await?;
+///
+/// let msg = monitor.try_next().await?.unwrap();
+/// assert_eq!(msg.sender(), conn.unique_name().map(|n| n.inner()));
+/// assert!(msg.is_broadcast());
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Monitor {
+    stream: MessageStream,
+    conn: Connection,
+    eavesdropping: bool,
+}
+
+assert_impl_all!(Monitor: Send, Sync, Unpin);
+
+impl Monitor {
+    /// Monitor the session bus.
+    pub async fn session(rules: &[MatchRule<'_>]) -> Result<Self> {
+        Self::new(connection::Builder::session()?, rules).await
+    }
+
+    /// Monitor the system bus.
+    ///
+    /// Note that monitoring the system bus usually requires root privileges.
+    pub async fn system(rules: &[MatchRule<'_>]) -> Result<Self> {
+        Self::new(connection::Builder::system()?, rules).await
+    }
+
+    /// Monitor the bus `builder` connects to.
+    ///
+    /// `builder` must be for a bus connection and the resulting connection is dedicated to the
+    /// monitor, since a monitor connection can't be used for anything else.
+    pub async fn new(builder: connection::Builder<'_>, rules: &[MatchRule<'_>]) -> Result<Self> {
+        let conn = builder.build().await?;
+        // Subscribe before becoming a monitor so we don't miss any message.
+        let stream = MessageStream::from(&conn);
+
+        let monitoring = MonitoringProxy::builder(&conn)
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        let eavesdropping = match monitoring.become_monitor(rules, 0).await {
+            Ok(()) => false,
+            Err(fdo::Error::UnknownMethod(_) | fdo::Error::UnknownInterface(_)) => {
+                debug!("Bus doesn't support monitoring, falling back to eavesdropping");
+                let dbus = DBusProxy::builder(&conn)
+                    .cache_properties(CacheProperties::No)
+                    .build()
+                    .await?;
+                if rules.is_empty() {
+                    dbus.add_match_rule(MatchRule::builder().eavesdrop(true).build())
+                        .await?;
+                }
+                for rule in rules {
+                    let mut rule = rule.clone();
+                    rule.eavesdrop = true;
+                    dbus.add_match_rule(rule).await?;
+                }
+
+                true
+            }
+            Err(e) => return Err(e.into()),
+        };
+        trace!("Monitor set up on connection {:?}", conn.unique_name());
+
+        Ok(Self {
+            stream,
+            conn,
+            eavesdropping,
+        })
+    }
+
+    /// The connection dedicated to the monitor.
+    ///
+    /// Once turned into a monitor, the bus doesn't allow the connection to send any message.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Whether the monitor fell back to eavesdropping.
+    pub fn is_eavesdropping(&self) -> bool {
+        self.eavesdropping
+    }
+
+    /// The capacity of the underlying message queue.
+    ///
+    /// See [`MessageStream::max_queued`] for details.
+    pub fn max_queued(&self) -> usize {
+        self.stream.max_queued()
+    }
+
+    /// Set the capacity of the underlying message queue.
+    ///
+    /// See [`MessageStream::set_max_queued`] for details.
+    pub fn set_max_queued(&mut self, max_queued: usize) {
+        self.stream.set_max_queued(max_queued)
+    }
+
+    /// Whether `msg` is addressed to the monitor connection itself.
+    fn is_own(&self, msg: &Message) -> bool {
+        let header = msg.header();
+        match (header.destination(), self.conn.unique_name()) {
+            (Some(BusName::Unique(dest)), Some(name)) => dest == name.inner(),
+            _ => false,
+        }
+    }
+}
+
+impl stream::Stream for Monitor {
+    type Item = Result<MonitoredMessage>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(msg))) if this.is_own(&msg) => {
+                    trace!("Ignoring message addressed to the monitor: {}", msg);
+                }
+                Poll::Ready(Some(msg)) => {
+                    return Poll::Ready(Some(msg.map(MonitoredMessage::new)));
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl FusedStream for Monitor {
+    fn is_terminated(&self) -> bool {
+        self.stream.is_terminated()
+    }
+}
+
+/// A message seen by a [`Monitor`].
+///
+/// Since a monitor is neither the sender nor the receiver of the messages it sees, this gives both
+/// ends of the message, rather than whether it's incoming or outgoing.
+#[derive(Debug, Clone)]
+pub struct MonitoredMessage {
+    msg: Message,
+    sender: Option<OwnedUniqueName>,
+    destination: Option<OwnedBusName>,
+}
+
+assert_impl_all!(MonitoredMessage: Send, Sync, Unpin);
+
+impl MonitoredMessage {
+    fn new(msg: Message) -> Self {
+        let header = msg.header();
+        let sender = header.sender().map(|s| s.to_owned().into());
+        let destination = header.destination().map(|d| d.to_owned().into());
+
+        Self {
+            msg,
+            sender,
+            destination,
+        }
+    }
+
+    /// The message.
+    pub fn message(&self) -> &Message {
+        &self.msg
+    }
+
+    /// The message, consuming `self`.
+    pub fn into_message(self) -> Message {
+        self.msg
+    }
+
+    /// The message type.
+    pub fn message_type(&self) -> Type {
+        self.msg.message_type()
+    }
+
+    /// The connection that sent the message.
+    ///
+    /// This is set by the bus, so it's always present on messages routed by a conforming bus.
+    pub fn sender(&self) -> Option<&UniqueName<'static>> {
+        self.sender.as_deref()
+    }
+
+    /// The connection the message is sent to, or `None` for broadcast signals.
+    pub fn destination(&self) -> Option<&BusName<'static>> {
+        self.destination.as_deref()
+    }
+
+    /// Whether the message is a signal broadcast to all interested connections.
+    pub fn is_broadcast(&self) -> bool {
+        self.destination.is_none() && self.message_type() == Type::Signal
+    }
+
+    /// The serial number of the message, unique per sender.
+    pub fn serial(&self) -> NonZeroU32 {
+        self.msg.primary_header().serial_num()
+    }
+
+    /// The serial number of the method call this message is a reply to, if it's a reply.
+    ///
+    /// Together with the [`destination`](Self::destination), this identifies the call.
+    pub fn reply_serial(&self) -> Option<NonZeroU32> {
+        self.msg.header().reply_serial()
+    }
+
+    /// The position of the message in the stream of the monitor.
+    pub fn recv_position(&self) -> Sequence {
+        self.msg.recv_position()
+    }
+}
+
+impl From<MonitoredMessage> for Message {
+    fn from(msg: MonitoredMessage) -> Self {
+        msg.msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream::TryStreamExt;
+    use ntest::timeout;
+    use test_log::test;
+
+    use crate::{message::Type, utils::block_on, Connection, MatchRule};
+
+    use super::Monitor;
+
+    async fn ping(conn: &Connection) {
+        conn.call_method(
+            Some("org.freedesktop.DBus"),
+            "/org/freedesktop/DBus",
+            Some("org.freedesktop.DBus.Peer"),
+            "Ping",
+            &(),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn method_call() {
+        block_on(async {
+            let rule = MatchRule::builder()
+                .msg_type(Type::MethodCall)
+                .interface("org.freedesktop.DBus.Peer")
+                .unwrap()
+                .build();
+            let mut monitor = Monitor::session(&[rule]).await.unwrap();
+            assert!(!monitor.is_eavesdropping());
+
+            let conn = Connection::session().await.unwrap();
+            ping(&conn).await;
+
+            let call = monitor.try_next().await.unwrap().unwrap();
+            assert_eq!(call.message_type(), Type::MethodCall);
+            assert_eq!(call.sender(), conn.unique_name().map(|n| n.inner()));
+            assert_eq!(
+                call.destination().map(|d| d.as_str()),
+                Some("org.freedesktop.DBus"),
+            );
+            assert!(!call.is_broadcast());
+            assert_eq!(call.reply_serial(), None);
+
+            // The reply doesn't match the rule, so the next message is the next call.
+            ping(&conn).await;
+            let next = monitor.try_next().await.unwrap().unwrap();
+            assert_eq!(next.message_type(), Type::MethodCall);
+            assert!(next.serial() > call.serial());
+        })
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn all_messages() {
+        block_on(async {
+            let mut monitor = Monitor::session(&[]).await.unwrap();
+            let monitor_name = monitor.connection().unique_name().unwrap().to_owned();
+
+            let conn = Connection::session().await.unwrap();
+            ping(&conn).await;
+
+            // Skip the traffic of the `Hello` call, until the ping and its reply.
+            let call = loop {
+                let msg = monitor.try_next().await.unwrap().unwrap();
+                assert_ne!(
+                    msg.destination().map(|d| d.as_str()),
+                    Some(monitor_name.as_str()),
+                );
+
+                if msg.message().header().member().map(|m| m.as_str()) == Some("Ping") {
+                    break msg;
+                }
+            };
+            let reply = monitor.try_next().await.unwrap().unwrap();
+            assert_eq!(reply.message_type(), Type::MethodReturn);
+            assert_eq!(reply.reply_serial(), Some(call.serial()));
+            assert_eq!(
+                reply.sender().map(|s| s.as_str()),
+                Some("org.freedesktop.DBus")
+            );
+            assert_eq!(
+                reply.destination().map(|d| d.as_str()),
+                conn.unique_name().map(|n| n.as_str()),
+            );
+        })
+    }
+}
@@ -0,0 +1,726 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Write},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use tracing::trace;
+use zbus_names::{InterfaceName, MemberName};
+use zvariant::{OwnedValue, ParsedSignature, Signature, Structure, StructureBuilder, Value};
+
+use crate::{
+    fdo,
+    message::{Body, Flags, Message},
+    object_server::{ArcInterface, DispatchResult, Interface, SignalContext},
+    Connection, Error, ObjectServer, Result,
+};
+
+type BoxedFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type MethodHandler = Box<dyn Fn(Body) -> BoxedFuture<fdo::Result<Vec<OwnedValue>>> + Send + Sync>;
+type PropertyGetter = Box<dyn Fn() -> BoxedFuture<fdo::Result<OwnedValue>> + Send + Sync>;
+type PropertySetter = Box<dyn Fn(OwnedValue) -> BoxedFuture<fdo::Result<()>> + Send + Sync>;
+
+/// A D-Bus interface whose name and members are declared at run time.
+///
+/// The [`interface`] macro is the preferred way to implement D-Bus interfaces but it requires the
+/// interface to be known at compile time. When the members come from somewhere else (e.g a
+/// configuration file, a plugin or a scripting engine), use [`DynamicInterface::builder`] to
+/// declare methods, properties and signals along with their signatures, and register the result
+/// with [`ObjectServer::at_dynamic`].
+///
+/// Method and property handlers work with [`OwnedValue`](zvariant::OwnedValue)s that must match the
+/// declared signatures. Mismatches are reported to the caller as D-Bus errors rather than sent on
+/// the wire.
+///
+/// `DynamicInterface` is cheap to clone and all clones share the same handlers. Since the name of
+/// the interface is only known at run time, it doesn't implement [`Interface`] and the object
+/// server looks it up by name instead: through [`ObjectServer::dynamic_interface`] and
+/// [`ObjectServer::remove_interface`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # use async_io::block_on;
+/// use std::sync::{
+///     atomic::{AtomicU32, Ordering},
+///     Arc,
+/// };
+/// use zbus::{
+///     object_server::{DynamicInterface, DynamicProperty},
+///     zvariant::{OwnedValue, Structure},
+///     Connection,
+/// };
+///
+/// # block_on(async {
+/// let brightness = Arc::new(AtomicU32::new(80));
+/// let (get_brightness, set_brightness) = (brightness.clone(), brightness.clone());
+///
+/// let iface = DynamicInterface::builder("org.zbus.Lamp")?
+///     .method("Add", "uu", "u", |body| async move {
+///         let args: Structure<'_> = body.deserialize()?;
+///         let sum = args
+///             .fields()
+///             .iter()
+///             .map(|f| u32::try_from(f).unwrap_or_default())
+///             .sum::<u32>();
+///
+///         Ok(vec![OwnedValue::from(sum)])
+///     })?
+///     .property(
+///         "Brightness",
+///         DynamicProperty::new("u")?
+///             .getter(move || {
+///                 let brightness = get_brightness.clone();
+///                 async move { Ok(OwnedValue::from(brightness.load(Ordering::SeqCst))) }
+///             })
+///             .setter(move |value| {
+///                 let brightness = set_brightness.clone();
+///                 async move {
+///                     let value = u32::try_from(value).map_err(zbus::Error::from)?;
+///                     brightness.store(value, Ordering::SeqCst);
+///                     Ok(())
+///                 }
+///             }),
+///     )?
+///     .signal("Flashed", "u")?
+///     .build();
+///
+/// let connection = Connection::session().await?;
+/// connection
+///     .object_server()
+///     .at_dynamic("/org/zbus/Lamp", iface.clone())
+///     .await?;
+///
+/// let ctxt = zbus::SignalContext::new(&connection, "/org/zbus/Lamp")?;
+/// iface.emit_signal(&ctxt, "Flashed", &[3u32.into()]).await?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// # })?;
+/// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+/// ```
+///
+/// [`interface`]: crate::interface
+/// [`ObjectServer::at_dynamic`]: crate::ObjectServer::at_dynamic
+/// [`ObjectServer::dynamic_interface`]: crate::ObjectServer::dynamic_interface
+/// [`ObjectServer::remove_interface`]: crate::ObjectServer::remove_interface
+#[derive(Clone)]
+pub struct DynamicInterface {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    name: InterfaceName<'static>,
+    spawn_tasks_for_methods: bool,
+    methods: BTreeMap<String, DynamicMethod>,
+    properties: BTreeMap<String, DynamicProperty>,
+    signals: BTreeMap<String, Signature<'static>>,
+}
+
+struct DynamicMethod {
+    in_signature: Signature<'static>,
+    out_signature: Signature<'static>,
+    handler: MethodHandler,
+}
+
+impl DynamicInterface {
+    /// Create a builder for an interface with the given name.
+    pub fn builder<'n, N>(name: N) -> Result<Builder>
+    where
+        N: TryInto<InterfaceName<'n>>,
+        N::Error: Into<Error>,
+    {
+        let name = name.try_into().map_err(Into::into)?;
+
+        Ok(Builder {
+            inner: Inner {
+                name: name.into_owned(),
+                spawn_tasks_for_methods: true,
+                methods: BTreeMap::new(),
+                properties: BTreeMap::new(),
+                signals: BTreeMap::new(),
+            },
+        })
+    }
+
+    /// The name of the interface.
+    pub fn interface_name(&self) -> &InterfaceName<'static> {
+        &self.inner.name
+    }
+
+    /// Emit the signal `signal_name` declared through [`Builder::signal`].
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Failure`] is returned if the signal wasn't declared or if the signature of `args`
+    /// doesn't match the declared signature.
+    pub async fn emit_signal(
+        &self,
+        ctxt: &SignalContext<'_>,
+        signal_name: &str,
+        args: &[Value<'_>],
+    ) -> Result<()> {
+        let signature = self.inner.signals.get(signal_name).ok_or_else(|| {
+            Error::Failure(format!(
+                "Signal '{signal_name}' not declared on interface '{}'",
+                self.inner.name
+            ))
+        })?;
+        let body = structure_from_values(args.iter().map(|arg| arg.try_to_owned()))?;
+        check_signature(signature, body.as_ref()).map_err(|got| {
+            Error::Failure(format!(
+                "Signal '{signal_name}' expects signature '{signature}', got '{got}'"
+            ))
+        })?;
+
+        let conn = ctxt.connection();
+        match body {
+            Some(body) => {
+                conn.emit_signal(
+                    ctxt.destination(),
+                    ctxt.path(),
+                    self.inner.name.as_ref(),
+                    signal_name,
+                    &body,
+                )
+                .await
+            }
+            None => {
+                conn.emit_signal(
+                    ctxt.destination(),
+                    ctxt.path(),
+                    self.inner.name.as_ref(),
+                    signal_name,
+                    &(),
+                )
+                .await
+            }
+        }
+    }
+
+    /// Emit `org.freedesktop.DBus.Properties.PropertiesChanged` for `property_name`.
+    ///
+    /// The current value is queried through the property getter. Use this when the value of a
+    /// property changes outside of a `Set` call.
+    pub async fn property_changed(
+        &self,
+        ctxt: &SignalContext<'_>,
+        property_name: &str,
+    ) -> Result<()> {
+        let value = self
+            .get(property_name)
+            .await
+            .ok_or_else(|| {
+                Error::Failure(format!(
+                    "Property '{property_name}' not declared on interface '{}'",
+                    self.inner.name
+                ))
+            })?
+            .map_err(|e| Error::FDO(Box::new(e)))?;
+        let value = Value::from(value);
+        let mut changed = HashMap::new();
+        changed.insert(property_name, &value);
+
+        fdo::Properties::properties_changed(ctxt, self.inner.name.as_ref(), &changed, &[]).await
+    }
+
+    // The name to register the interface under and the interface to register.
+    pub(crate) fn into_arc_interface(self) -> (InterfaceName<'static>, ArcInterface) {
+        (self.inner.name.clone(), ArcInterface::new(Dispatcher(self)))
+    }
+
+    // Get the `DynamicInterface` out of a registered interface, if it is one.
+    pub(crate) fn from_interface(iface: &dyn Interface) -> Option<Self> {
+        iface
+            .downcast_ref::<Dispatcher>()
+            .map(|dispatcher| dispatcher.0.clone())
+    }
+
+    // The value of `property_name`, checked against its declared signature.
+    async fn get(&self, property_name: &str) -> Option<fdo::Result<OwnedValue>> {
+        let property = self.inner.properties.get(property_name)?;
+        let getter = match &property.getter {
+            Some(getter) => getter,
+            None => {
+                return Some(Err(fdo::Error::AccessDenied(format!(
+                    "Property '{property_name}' is write-only"
+                ))))
+            }
+        };
+
+        Some(getter().await.and_then(|value| {
+            if value.value_signature() == property.signature {
+                Ok(value)
+            } else {
+                Err(fdo::Error::Failed(format!(
+                    "Property '{property_name}' has signature '{}', got a value of signature '{}'",
+                    property.signature,
+                    value.value_signature(),
+                )))
+            }
+        }))
+    }
+}
+
+impl fmt::Debug for DynamicInterface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicInterface")
+            .field("name", &self.inner.name)
+            .field("methods", &self.inner.methods.keys().collect::<Vec<_>>())
+            .field(
+                "properties",
+                &self.inner.properties.keys().collect::<Vec<_>>(),
+            )
+            .field("signals", &self.inner.signals.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Builder for [`DynamicInterface`].
+///
+/// All member names must be valid D-Bus member names and all signatures valid D-Bus signatures.
+/// Declaring a member with the same name twice replaces the previous declaration.
+pub struct Builder {
+    inner: Inner,
+}
+
+impl Builder {
+    /// Declare a method.
+    ///
+    /// `in_signature` and `out_signature` are the signatures of all the input and output arguments
+    /// respectively, concatenated (e.g `"su"` for a string and an unsigned integer). Calls whose
+    /// body doesn't match `in_signature` are rejected with `InvalidArgs` before `handler` is
+    /// invoked. The values returned by `handler` are sent back as the reply.
+    pub fn method<'n, 's, N, I, O, F, Fut>(
+        mut self,
+        name: N,
+        in_signature: I,
+        out_signature: O,
+        handler: F,
+    ) -> Result<Self>
+    where
+        N: TryInto<MemberName<'n>>,
+        N::Error: Into<Error>,
+        I: TryInto<Signature<'s>>,
+        I::Error: Into<Error>,
+        O: TryInto<Signature<'s>>,
+        O::Error: Into<Error>,
+        F: Fn(Body) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<Vec<OwnedValue>>> + Send + 'static,
+    {
+        let name = name.try_into().map_err(Into::into)?;
+        let in_signature = in_signature.try_into().map_err(Into::into)?;
+        let out_signature = out_signature.try_into().map_err(Into::into)?;
+        self.inner.methods.insert(
+            name.to_string(),
+            DynamicMethod {
+                in_signature: in_signature.into_owned(),
+                out_signature: out_signature.into_owned(),
+                handler: Box::new(move |body| Box::pin(handler(body))),
+            },
+        );
+
+        Ok(self)
+    }
+
+    /// Declare a property.
+    pub fn property<'n, N>(mut self, name: N, property: DynamicProperty) -> Result<Self>
+    where
+        N: TryInto<MemberName<'n>>,
+        N::Error: Into<Error>,
+    {
+        let name = name.try_into().map_err(Into::into)?;
+        self.inner.properties.insert(name.to_string(), property);
+
+        Ok(self)
+    }
+
+    /// Declare a signal.
+    ///
+    /// `signature` is the signature of all the signal arguments, concatenated. Use
+    /// [`DynamicInterface::emit_signal`] to emit it.
+    pub fn signal<'n, 's, N, S>(mut self, name: N, signature: S) -> Result<Self>
+    where
+        N: TryInto<MemberName<'n>>,
+        N::Error: Into<Error>,
+        S: TryInto<Signature<'s>>,
+        S::Error: Into<Error>,
+    {
+        let name = name.try_into().map_err(Into::into)?;
+        let signature = signature.try_into().map_err(Into::into)?;
+        self.inner
+            .signals
+            .insert(name.to_string(), signature.into_owned());
+
+        Ok(self)
+    }
+
+    /// Whether each method call will be handled from a different spawned task.
+    ///
+    /// Enabled by default. See [`Interface::spawn_tasks_for_methods`] for details.
+    pub fn spawn_tasks_for_methods(mut self, spawn: bool) -> Self {
+        self.inner.spawn_tasks_for_methods = spawn;
+
+        self
+    }
+
+    /// Build the [`DynamicInterface`].
+    pub fn build(self) -> DynamicInterface {
+        DynamicInterface {
+            inner: Arc::new(self.inner),
+        }
+    }
+}
+
+impl fmt::Debug for Builder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("name", &self.inner.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A property of a [`DynamicInterface`].
+///
+/// The access mode of the property follows from the handlers set: a property with only a getter is
+/// read-only, one with only a setter is write-only and one with both is read-write.
+pub struct DynamicProperty {
+    signature: Signature<'static>,
+    getter: Option<PropertyGetter>,
+    setter: Option<PropertySetter>,
+}
+
+impl DynamicProperty {
+    /// Create a property of the given type, without any handlers.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Failure`] is returned if `signature` isn't a single complete type.
+    pub fn new<'s, S>(signature: S) -> Result<Self>
+    where
+        S: TryInto<Signature<'s>>,
+        S::Error: Into<Error>,
+    {
+        let signature = signature.try_into().map_err(Into::into)?;
+        if ParsedSignature::new(&signature).len() != 1 {
+            return Err(Error::Failure(format!(
+                "Property signature '{signature}' is not a single complete type"
+            )));
+        }
+
+        Ok(Self {
+            signature: signature.into_owned(),
+            getter: None,
+            setter: None,
+        })
+    }
+
+    /// Set the handler for reading the property.
+    pub fn getter<F, Fut>(mut self, getter: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<OwnedValue>> + Send + 'static,
+    {
+        self.getter = Some(Box::new(move || Box::pin(getter())));
+
+        self
+    }
+
+    /// Set the handler for writing the property.
+    ///
+    /// The handler is only called with values matching the property signature. Once it succeeds,
+    /// `org.freedesktop.DBus.Properties.PropertiesChanged` is emitted with the new value.
+    pub fn setter<F, Fut>(mut self, setter: F) -> Self
+    where
+        F: Fn(OwnedValue) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<()>> + Send + 'static,
+    {
+        self.setter = Some(Box::new(move |value| Box::pin(setter(value))));
+
+        self
+    }
+
+    fn access(&self) -> &'static str {
+        match (self.getter.is_some(), self.setter.is_some()) {
+            (true, true) => "readwrite",
+            (false, true) => "write",
+            // A property with no handlers is pointless but harmless.
+            (_, false) => "read",
+        }
+    }
+}
+
+impl fmt::Debug for DynamicProperty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicProperty")
+            .field("signature", &self.signature)
+            .field("access", &self.access())
+            .finish()
+    }
+}
+
+/// The [`Interface`] through which a [`DynamicInterface`] is served.
+///
+/// `DynamicInterface` doesn't implement `Interface` itself since it has no static name. Instead,
+/// the dispatcher is always registered under the name of the interface it serves, and its static
+/// name is a placeholder. This type is private, so nothing can look it up by that name either.
+struct Dispatcher(DynamicInterface);
+
+#[async_trait]
+impl Interface for Dispatcher {
+    fn name() -> InterfaceName<'static> {
+        InterfaceName::from_static_str_unchecked("org.freedesktop.zbus.DynamicInterface")
+    }
+
+    fn spawn_tasks_for_methods(&self) -> bool {
+        self.0.inner.spawn_tasks_for_methods
+    }
+
+    async fn get(&self, property_name: &str) -> Option<fdo::Result<OwnedValue>> {
+        self.0.get(property_name).await
+    }
+
+    async fn get_all(&self) -> fdo::Result<HashMap<String, OwnedValue>> {
+        let mut props = HashMap::new();
+        for (name, property) in &self.0.inner.properties {
+            if property.getter.is_none() {
+                continue;
+            }
+            if let Some(value) = self.0.get(name).await {
+                props.insert(name.clone(), value?);
+            }
+        }
+
+        Ok(props)
+    }
+
+    fn set<'call>(
+        &'call self,
+        property_name: &'call str,
+        value: &'call Value<'_>,
+        ctxt: &'call SignalContext<'_>,
+    ) -> DispatchResult<'call> {
+        let property = match self.0.inner.properties.get(property_name) {
+            Some(property) => property,
+            None => return DispatchResult::NotFound,
+        };
+        // Convert before creating the future since it can't hold on to the borrowed value.
+        let value = value.try_to_owned();
+
+        DispatchResult::Async(Box::pin(async move {
+            let setter = property.setter.as_ref().ok_or_else(|| {
+                fdo::Error::PropertyReadOnly(format!("Property '{property_name}' is read-only"))
+            })?;
+            let value = value?;
+            if value.value_signature() != property.signature {
+                return Err(fdo::Error::InvalidArgs(format!(
+                    "Property '{property_name}' has signature '{}', got a value of signature '{}'",
+                    property.signature,
+                    value.value_signature(),
+                ))
+                .into());
+            }
+            let new_value = value.try_clone()?;
+            setter(value).await?;
+
+            let new_value = Value::from(new_value);
+            let mut changed = HashMap::new();
+            changed.insert(property_name, &new_value);
+            fdo::Properties::properties_changed(ctxt, self.0.inner.name.as_ref(), &changed, &[])
+                .await
+        }))
+    }
+
+    async fn set_mut(
+        &mut self,
+        _property_name: &str,
+        _value: &Value<'_>,
+        _ctxt: &SignalContext<'_>,
+    ) -> Option<fdo::Result<()>> {
+        // `set` never returns `RequiresMut`.
+        None
+    }
+
+    fn call<'call>(
+        &'call self,
+        _server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        let method = match self.0.inner.methods.get(name.as_str()) {
+            Some(method) => method,
+            None => return DispatchResult::NotFound,
+        };
+
+        DispatchResult::Async(Box::pin(async move {
+            let hdr = msg.header();
+            let ret = method.call(msg.body()).await;
+            if hdr.primary().flags().contains(Flags::NoReplyExpected) {
+                trace!("No reply expected for {:?} by the caller.", msg);
+                return Ok(());
+            }
+
+            match ret {
+                Ok(Some(body)) => connection.reply(msg, &body).await,
+                Ok(None) => connection.reply(msg, &()).await,
+                Err(e) => connection.reply_dbus_error(&hdr, e).await,
+            }
+            .map(|_seq| ())
+        }))
+    }
+
+    fn call_mut<'call>(
+        &'call mut self,
+        _server: &'call ObjectServer,
+        _connection: &'call Connection,
+        _msg: &'call Message,
+        _name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        DispatchResult::NotFound
+    }
+
+    fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize) {
+        writeln!(
+            writer,
+            r#"{:indent$}<interface name="{}">"#,
+            "",
+            self.0.inner.name,
+            indent = level
+        )
+        .unwrap();
+        let level = level + 2;
+
+        for (name, method) in &self.0.inner.methods {
+            writeln!(
+                writer,
+                "{:indent$}<method name=\"{}\">",
+                "",
+                name,
+                indent = level
+            )
+            .unwrap();
+            for ty in ParsedSignature::new(&method.in_signature).iter() {
+                writeln!(
+                    writer,
+                    "{:indent$}<arg type=\"{}\" direction=\"in\"/>",
+                    "",
+                    ty,
+                    indent = level + 2
+                )
+                .unwrap();
+            }
+            for ty in ParsedSignature::new(&method.out_signature).iter() {
+                writeln!(
+                    writer,
+                    "{:indent$}<arg type=\"{}\" direction=\"out\"/>",
+                    "",
+                    ty,
+                    indent = level + 2
+                )
+                .unwrap();
+            }
+            writeln!(writer, "{:indent$}</method>", "", indent = level).unwrap();
+        }
+
+        for (name, signature) in &self.0.inner.signals {
+            writeln!(
+                writer,
+                "{:indent$}<signal name=\"{}\">",
+                "",
+                name,
+                indent = level
+            )
+            .unwrap();
+            for ty in ParsedSignature::new(signature).iter() {
+                writeln!(
+                    writer,
+                    "{:indent$}<arg type=\"{}\"/>",
+                    "",
+                    ty,
+                    indent = level + 2
+                )
+                .unwrap();
+            }
+            writeln!(writer, "{:indent$}</signal>", "", indent = level).unwrap();
+        }
+
+        for (name, property) in &self.0.inner.properties {
+            writeln!(
+                writer,
+                "{:indent$}<property name=\"{}\" type=\"{}\" access=\"{}\"/>",
+                "",
+                name,
+                property.signature,
+                property.access(),
+                indent = level,
+            )
+            .unwrap();
+        }
+
+        writeln!(writer, r#"{:indent$}</interface>"#, "", indent = level - 2).unwrap();
+    }
+}
+
+impl DynamicMethod {
+    // Returns the reply body, or `None` if the method has no output arguments.
+    async fn call(&self, body: Body) -> fdo::Result<Option<Structure<'static>>> {
+        let in_signature = body
+            .signature()
+            .map(|s| s.to_owned())
+            .unwrap_or_else(|| Signature::from_static_str_unchecked(""));
+        if in_signature != self.in_signature {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "Expected arguments of signature '{}', got '{in_signature}'",
+                self.in_signature,
+            )));
+        }
+
+        let outputs = (self.handler)(body).await?;
+        let reply = structure_from_values(outputs.into_iter().map(Ok))?;
+        check_signature(&self.out_signature, reply.as_ref()).map_err(|got| {
+            fdo::Error::Failed(format!(
+                "Method returned values of signature '{got}' instead of '{}'",
+                self.out_signature,
+            ))
+        })?;
+
+        Ok(reply)
+    }
+}
+
+// Build a structure of the given values, if there are any.
+fn structure_from_values(
+    values: impl Iterator<Item = zvariant::Result<OwnedValue>>,
+) -> Result<Option<Structure<'static>>> {
+    let mut builder = StructureBuilder::new();
+    let mut empty = true;
+    for value in values {
+        builder.push_value(value?.into());
+        empty = false;
+    }
+
+    Ok((!empty).then(|| builder.build()))
+}
+
+// Check that the fields of `body` match `expected`, returning the actual signature otherwise.
+fn check_signature(
+    expected: &Signature<'_>,
+    body: Option<&Structure<'_>>,
+) -> std::result::Result<(), String> {
+    let got = match body {
+        Some(body) => {
+            let signature = body.signature();
+            signature.slice(1..signature.len() - 1).to_string()
+        }
+        None => String::new(),
+    };
+
+    if got == expected.as_str() {
+        Ok(())
+    } else {
+        Err(got)
+    }
+}
@@ -12,7 +12,7 @@ use std::{
 use tracing::{debug, instrument, trace, trace_span, Instrument};
 
 use static_assertions::assert_impl_all;
-use zbus_names::InterfaceName;
+use zbus_names::{InterfaceName, OwnedInterfaceName};
 use zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Signature, Type, Value};
 
 use crate::{
@@ -28,6 +28,9 @@ mod interface;
 pub(crate) use interface::ArcInterface;
 pub use interface::{DispatchResult, Interface};
 
+mod dynamic;
+pub use dynamic::{Builder as DynamicInterfaceBuilder, DynamicInterface, DynamicProperty};
+
 mod signal_context;
 pub use signal_context::SignalContext;
 
@@ -181,6 +184,50 @@ impl<I> Clone for InterfaceRef<I> {
     }
 }
 
+/// A builder for registering several interfaces at the same path at once.
+///
+/// Created by [`ObjectServer::object`]. See its documentation for details.
+#[derive(Debug)]
+#[must_use = "the interfaces are only registered once `ObjectBuilder::register` is called"]
+pub struct ObjectBuilder<'s> {
+    server: &'s ObjectServer,
+    path: ObjectPath<'static>,
+    interfaces: Vec<(InterfaceName<'static>, ArcInterface)>,
+}
+
+impl ObjectBuilder<'_> {
+    /// Add an [`Interface`] to the object.
+    pub fn interface<I>(mut self, iface: I) -> Self
+    where
+        I: Interface,
+    {
+        self.interfaces.push((I::name(), ArcInterface::new(iface)));
+
+        self
+    }
+
+    /// Add a [`DynamicInterface`] to the object.
+    pub fn dynamic_interface(mut self, iface: DynamicInterface) -> Self {
+        self.interfaces.push(iface.into_arc_interface());
+
+        self
+    }
+
+    /// Register all the added interfaces.
+    ///
+    /// # Errors
+    ///
+    /// If any of the interfaces already exists at the path, or has been added twice,
+    /// `Error::InterfaceExists` is returned and none of the interfaces are registered.
+    ///
+    /// If no interfaces were added, this does nothing. In particular, no object is created.
+    pub async fn register(self) -> Result<()> {
+        self.server
+            .add_arc_interfaces(self.path, self.interfaces)
+            .await
+    }
+}
+
 #[derive(Default, Debug)]
 pub(crate) struct Node {
     path: OwnedObjectPath,
@@ -277,6 +324,59 @@ impl Node {
         self.children.remove(node).is_some()
     }
 
+    // Detach the descendant Node at path, if any. `path` must not be the root path.
+    fn take_child(&mut self, path: &ObjectPath<'_>) -> Option<Node> {
+        let (parent, name) = path.rsplit_once('/')?;
+        let parent = if parent.is_empty() { "/" } else { parent };
+        let parent = ObjectPath::from_str_unchecked(parent);
+
+        self.get_child_mut(&parent, false).0?.children.remove(name)
+    }
+
+    // Remove the ancestors of `path` left with no interfaces and no children, bottom-up.
+    fn prune_ancestors(&mut self, path: &ObjectPath<'_>) {
+        let mut path = path.as_str();
+        while let Some((parent, _)) = path.rsplit_once('/') {
+            if parent.is_empty() {
+                // Never remove the root node.
+                break;
+            }
+            let parent_path = ObjectPath::from_str_unchecked(parent);
+            match self.get_child(&parent_path) {
+                Some(node)
+                    if node.children.is_empty() && node.user_interfaces().next().is_none() =>
+                {
+                    self.take_child(&parent_path);
+                }
+                _ => break,
+            }
+            path = parent;
+        }
+    }
+
+    // The interfaces registered by the user, i.e excluding the ones we add to every node.
+    fn user_interfaces(&self) -> impl Iterator<Item = &InterfaceName<'static>> {
+        self.interfaces.keys().filter(|k| {
+            **k != Peer::name() && **k != Introspectable::name() && **k != Properties::name()
+        })
+    }
+
+    fn info(&self) -> NodeInfo {
+        let mut interfaces: Vec<_> = self
+            .user_interfaces()
+            .map(|i| OwnedInterfaceName::from(i.clone()))
+            .collect();
+        interfaces.sort();
+        let mut children: Vec<_> = self.children.values().map(|n| n.path.clone()).collect();
+        children.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        NodeInfo {
+            path: self.path.clone(),
+            interfaces,
+            children,
+        }
+    }
+
     fn add_arc_interface(&mut self, name: InterfaceName<'static>, arc_iface: ArcInterface) -> bool {
         match self.interfaces.entry(name) {
             Entry::Vacant(e) => {
@@ -410,6 +510,38 @@ impl Node {
     }
 }
 
+/// Information about a node of the [`ObjectServer`] tree.
+///
+/// Returned by [`ObjectServer::nodes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeInfo {
+    path: OwnedObjectPath,
+    interfaces: Vec<OwnedInterfaceName>,
+    children: Vec<OwnedObjectPath>,
+}
+
+impl NodeInfo {
+    /// The object path of the node.
+    pub fn path(&self) -> &OwnedObjectPath {
+        &self.path
+    }
+
+    /// The names of the interfaces registered at this node, sorted.
+    ///
+    /// The standard interfaces that are implemented on every node on your behalf
+    /// (`org.freedesktop.DBus.Peer`, `org.freedesktop.DBus.Introspectable` and
+    /// `org.freedesktop.DBus.Properties`) are not included. Nodes that only exist because they are
+    /// in the path of other nodes have no interfaces.
+    pub fn interfaces(&self) -> &[OwnedInterfaceName] {
+        &self.interfaces
+    }
+
+    /// The object paths of the direct children of this node, sorted.
+    pub fn children(&self) -> &[OwnedObjectPath] {
+        &self.children
+    }
+}
+
 /// An object server, holding server-side D-Bus objects & interfaces.
 ///
 /// Object servers hold interfaces on various object paths, and expose them over D-Bus.
@@ -506,54 +638,174 @@ impl ObjectServer {
             .await
     }
 
+    /// Register a [`DynamicInterface`] at a given path.
+    ///
+    /// Same as [`ObjectServer::at`], for interfaces whose name is only known at run time.
+    ///
+    /// If an interface with the same name already exists at this path, returns false.
+    pub async fn at_dynamic<'p, P>(&self, path: P, iface: DynamicInterface) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let (name, arc_iface) = iface.into_arc_interface();
+
+        self.add_arc_interface(path, name, arc_iface).await
+    }
+
+    /// Register several D-Bus [`Interface`]s at a given path at once.
+    ///
+    /// Unlike multiple calls to [`ObjectServer::at`], the interfaces are added atomically: either
+    /// all of them or none of them are registered, and a single
+    /// `org.freedesktop.DBus.ObjectManager.InterfacesAdded` signal listing all the interfaces and
+    /// their properties is emitted through the closest [`ObjectManager`] (if any). Consumers of
+    /// the object manager, therefore, never see a partially registered object.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use zbus::{Connection, interface};
+    /// # use async_io::block_on;
+    /// #
+    /// struct Device;
+    ///
+    /// #[interface(name = "org.myiface.Device")]
+    /// impl Device {}
+    ///
+    /// struct Battery(u8);
+    ///
+    /// #[interface(name = "org.myiface.Battery")]
+    /// impl Battery {
+    ///     #[zbus(property)]
+    ///     fn percentage(&self) -> u8 {
+    ///         self.0
+    ///     }
+    /// }
+    ///
+    /// # block_on(async {
+    /// let connection = Connection::session().await?;
+    /// connection
+    ///     .object_server()
+    ///     .object("/org/myiface/devices/0")?
+    ///     .interface(Device)
+    ///     .interface(Battery(42))
+    ///     .register()
+    ///     .await?;
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// # })?;
+    /// #
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    pub fn object<'p, P>(&self, path: P) -> Result<ObjectBuilder<'_>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+
+        Ok(ObjectBuilder {
+            server: self,
+            path: path.into_owned(),
+            interfaces: vec![],
+        })
+    }
+
     pub(crate) async fn add_arc_interface<'p, P>(
         &self,
         path: P,
         name: InterfaceName<'static>,
         arc_iface: ArcInterface,
     ) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        match self.add_arc_interfaces(path, vec![(name, arc_iface)]).await {
+            Ok(()) => Ok(true),
+            Err(Error::InterfaceExists(_, _)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Add all the given interfaces at `path`, or none of them if any already exists there.
+    pub(crate) async fn add_arc_interfaces<'p, P>(
+        &self,
+        path: P,
+        interfaces: Vec<(InterfaceName<'static>, ArcInterface)>,
+    ) -> Result<()>
     where
         P: TryInto<ObjectPath<'p>>,
         P::Error: Into<Error>,
     {
         let path = path.try_into().map_err(Into::into)?;
+        if interfaces.is_empty() {
+            return Ok(());
+        }
         let mut root = self.root().write().await;
+        // Check before creating the node, so nothing is left behind on error.
+        let existing = root.get_child(&path);
+        for (i, (name, _)) in interfaces.iter().enumerate() {
+            if existing.is_some_and(|node| node.interfaces.contains_key(name))
+                || interfaces[..i].iter().any(|(n, _)| n == name)
+            {
+                return Err(Error::InterfaceExists(name.clone(), path.into_owned()));
+            }
+        }
         let (node, manager_path) = root.get_child_mut(&path, true);
         let node = node.unwrap();
-        let added = node.add_arc_interface(name.clone(), arc_iface);
-        if added {
+
+        let mut added_manager = false;
+        let mut names = Vec::with_capacity(interfaces.len());
+        for (name, arc_iface) in interfaces {
             if name == ObjectManager::name() {
-                // Just added an object manager. Need to signal all managed objects under it.
-                let ctxt = SignalContext::new(&self.connection(), path)?;
-                let objects = node.get_managed_objects().await?;
-                for (path, owned_interfaces) in objects {
-                    let interfaces = owned_interfaces
-                        .iter()
-                        .map(|(i, props)| {
-                            let props = props
-                                .iter()
-                                .map(|(k, v)| Ok((k.as_str(), Value::try_from(v)?)))
-                                .collect::<Result<_>>();
-                            Ok((i.into(), props?))
-                        })
-                        .collect::<Result<_>>()?;
-                    ObjectManager::interfaces_added(&ctxt, &path, &interfaces).await?;
-                }
-            } else if let Some(manager_path) = manager_path {
-                let ctxt = SignalContext::new(&self.connection(), manager_path.clone())?;
-                let mut interfaces = HashMap::new();
-                let owned_props = node.get_properties(name.clone()).await?;
-                let props = owned_props
+                added_manager = true;
+            } else {
+                names.push(name.clone());
+            }
+            node.add_arc_interface(name, arc_iface);
+        }
+
+        if added_manager {
+            // Just added an object manager. Need to signal all managed objects under it.
+            let ctxt = SignalContext::new(&self.connection(), path.clone())?;
+            let objects = node.get_managed_objects().await?;
+            for (path, owned_interfaces) in objects {
+                let interfaces = owned_interfaces
                     .iter()
-                    .map(|(k, v)| Ok((k.as_str(), Value::try_from(v)?)))
+                    .map(|(i, props)| {
+                        let props = props
+                            .iter()
+                            .map(|(k, v)| Ok((k.as_str(), Value::try_from(v)?)))
+                            .collect::<Result<_>>();
+                        Ok((i.into(), props?))
+                    })
                     .collect::<Result<_>>()?;
-                interfaces.insert(name, props);
-
                 ObjectManager::interfaces_added(&ctxt, &path, &interfaces).await?;
             }
         }
+        if let (Some(manager_path), false) = (manager_path, names.is_empty()) {
+            let ctxt = SignalContext::new(&self.connection(), manager_path.clone())?;
+            let mut owned_props = Vec::with_capacity(names.len());
+            for name in names {
+                let props = node.get_properties(name.clone()).await?;
+                owned_props.push((name, props));
+            }
+            let interfaces = owned_props
+                .iter()
+                .map(|(name, props)| {
+                    let props = props
+                        .iter()
+                        .map(|(k, v)| Ok((k.as_str(), Value::try_from(v)?)))
+                        .collect::<Result<_>>();
+                    Ok((name.clone(), props?))
+                })
+                .collect::<Result<_>>()?;
+
+            ObjectManager::interfaces_added(&ctxt, &path, &interfaces).await?;
+        }
 
-        Ok(added)
+        Ok(())
     }
 
     /// Unregister a D-Bus [`Interface`] at a given path.
@@ -565,17 +817,33 @@ impl ObjectServer {
         I: Interface,
         P: TryInto<ObjectPath<'p>>,
         P::Error: Into<Error>,
+    {
+        self.remove_interface(path, I::name()).await
+    }
+
+    /// Unregister the interface named `name` at a given path.
+    ///
+    /// Same as [`ObjectServer::remove`], except that the interface is identified by its name. This
+    /// is needed for interfaces whose name is only known at run time, such as
+    /// [`DynamicInterface`].
+    pub async fn remove_interface<'p, 'i, P, N>(&self, path: P, name: N) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+        N: TryInto<InterfaceName<'i>>,
+        N::Error: Into<Error>,
     {
         let path = path.try_into().map_err(Into::into)?;
+        let name = name.try_into().map_err(Into::into)?.into_owned();
         let mut root = self.root.write().await;
         let (node, manager_path) = root.get_child_mut(&path, false);
         let node = node.ok_or(Error::InterfaceNotFound)?;
-        if !node.remove_interface(I::name()) {
+        if !node.remove_interface(name.clone()) {
             return Err(Error::InterfaceNotFound);
         }
         if let Some(manager_path) = manager_path {
             let ctxt = SignalContext::new(&self.connection(), manager_path.clone())?;
-            ObjectManager::interfaces_removed(&ctxt, &path, &[I::name()]).await?;
+            ObjectManager::interfaces_removed(&ctxt, &path, &[name]).await?;
         }
         if node.is_empty() {
             let mut path_parts = path.rsplit('/').filter(|i| !i.is_empty());
@@ -592,6 +860,141 @@ impl ObjectServer {
         Ok(false)
     }
 
+    /// Unregister all the interfaces at a given path and all its descendants.
+    ///
+    /// Ancestors left with no interfaces and no children are removed as well. For each removed
+    /// object, a single `org.freedesktop.DBus.ObjectManager.InterfacesRemoved`
+    /// signal listing all its interfaces is emitted through the closest [`ObjectManager`] (if any).
+    ///
+    /// Returns `false` if there was no object at the given path.
+    pub async fn remove_subtree<'p, P>(&self, path: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let mut root = self.root.write().await;
+        let (subtree, manager_path) = if path.as_str() == "/" {
+            let root_path = root.path.clone();
+            (std::mem::replace(&mut *root, Node::new(root_path)), None)
+        } else {
+            let manager_path = match root.get_child_mut(&path, false) {
+                (Some(_), manager_path) => {
+                    manager_path.map(|p| OwnedObjectPath::from(p.to_owned()))
+                }
+                (None, _) => return Ok(false),
+            };
+            let subtree = match root.take_child(&path) {
+                Some(subtree) => subtree,
+                None => return Ok(false),
+            };
+            root.prune_ancestors(&path);
+
+            (subtree, manager_path)
+        };
+
+        let conn = self.connection();
+        let mut nodes = vec![(&subtree, manager_path)];
+        while let Some((node, manager_path)) = nodes.pop() {
+            let child_manager_path = if node.interfaces.contains_key(&ObjectManager::name()) {
+                Some(node.path.clone())
+            } else {
+                manager_path.clone()
+            };
+            nodes.extend(
+                node.children
+                    .values()
+                    .map(|child| (child, child_manager_path.clone())),
+            );
+
+            let manager_path = match manager_path {
+                Some(manager_path) => manager_path,
+                None => continue,
+            };
+            let interfaces: Vec<_> = node
+                .user_interfaces()
+                .filter(|i| **i != ObjectManager::name())
+                .cloned()
+                .collect();
+            if interfaces.is_empty() {
+                continue;
+            }
+            let ctxt = SignalContext::new(&conn, manager_path)?;
+            ObjectManager::interfaces_removed(&ctxt, &node.path, &interfaces).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Whether there is an object at the given path.
+    ///
+    /// Intermediate nodes, i.e nodes that only exist because they are in the path of other
+    /// objects, count as objects too.
+    pub async fn has_object<'p, P>(&self, path: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+
+        Ok(self.root.read().await.get_child(&path).is_some())
+    }
+
+    /// Walk the node at the given path and all its descendants.
+    ///
+    /// Nodes are returned in depth-first pre-order, with children of a node sorted by path. If
+    /// there is no node at the given path, an empty list is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use zbus::{Connection, interface};
+    /// # use async_io::block_on;
+    /// #
+    /// struct Device;
+    ///
+    /// #[interface(name = "org.myiface.Device")]
+    /// impl Device {}
+    ///
+    /// # block_on(async {
+    /// let connection = Connection::session().await?;
+    /// let object_server = connection.object_server();
+    /// object_server.at("/org/myiface/devices/0", Device).await?;
+    /// object_server.at("/org/myiface/devices/1", Device).await?;
+    ///
+    /// for node in object_server.nodes("/org/myiface/devices").await? {
+    ///     println!("{}: {:?}", node.path(), node.interfaces());
+    /// }
+    ///
+    /// // All devices are gone.
+    /// object_server.remove_subtree("/org/myiface/devices").await?;
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// # })?;
+    /// #
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    pub async fn nodes<'p, P>(&self, path: P) -> Result<Vec<NodeInfo>>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let root = self.root.read().await;
+        let mut infos = vec![];
+        let mut nodes: Vec<_> = root.get_child(&path).into_iter().collect();
+        while let Some(node) = nodes.pop() {
+            let info = node.info();
+            // Push in reverse so that children are visited in order.
+            for child in info.children.iter().rev() {
+                nodes.push(&node.children[child.rsplit('/').next().unwrap()]);
+            }
+            infos.push(info);
+        }
+
+        Ok(infos)
+    }
+
     /// Get the interface at the given path.
     ///
     /// # Errors
@@ -667,6 +1070,41 @@ impl ObjectServer {
         })
     }
 
+    /// Get the [`DynamicInterface`] named `name` at the given path.
+    ///
+    /// Same as [`ObjectServer::interface`], for interfaces whose name is only known at run time.
+    /// Since all clones of a `DynamicInterface` share the same handlers, this returns a clone of
+    /// the registered interface.
+    ///
+    /// # Errors
+    ///
+    /// If there is no `DynamicInterface` named `name` at the given path, `Error::InterfaceNotFound`
+    /// error is returned.
+    pub async fn dynamic_interface<'p, 'i, P, N>(
+        &self,
+        path: P,
+        name: N,
+    ) -> Result<DynamicInterface>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+        N: TryInto<InterfaceName<'i>>,
+        N::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let name = name.try_into().map_err(Into::into)?.into_owned();
+        let root = self.root().read().await;
+        let node = root.get_child(&path).ok_or(Error::InterfaceNotFound)?;
+        let lock = node
+            .interface_lock(name)
+            .ok_or(Error::InterfaceNotFound)?
+            .instance
+            .clone();
+        let iface = lock.read().await;
+
+        DynamicInterface::from_interface(&*iface).ok_or(Error::InterfaceNotFound)
+    }
+
     async fn dispatch_call_to_iface(
         &self,
         iface: Arc<RwLock<dyn Interface>>,
@@ -874,6 +1312,8 @@ impl<R> Type for ResponseDispatchNotifier<R>
 where
     R: Type,
 {
+    const SIGNATURE: &'static zvariant::StaticSignature = R::SIGNATURE;
+
     fn signature() -> Signature<'static> {
         R::signature()
     }
@@ -0,0 +1,563 @@
+//! Capture of D-Bus traffic in the pcap format.
+//!
+//! The [pcap] file format with the `DLT_DBUS` link type is what `dbus-monitor --pcap` writes, and
+//! what Wireshark (among others) understands. Each record of such a file is a complete D-Bus
+//! message, as sent on the wire.
+//!
+//! * [`Writer`] writes [`Message`]s to a pcap file.
+//! * [`Capture`] is an [`Interceptor`] that writes all the messages sent and received by a
+//!   [`Connection`]. To capture the messages of a [`MessageStream`] only, use a [`Writer`]
+//!   directly.
+//! * [`Reader`] reads [`Message`]s back from a pcap file, for example to replay the captured
+//!   traffic into a service in tests.
+//!
+//! Note that the file descriptors passed along the messages can't be captured. Messages read from
+//! a capture still reference them but their bodies can't be deserialized.
+//!
+//! # Example
+//!
+//! ```
+//! use zbus::{pcap, Message};
+//!
+//! let mut writer = pcap::Writer::new(vec![])?;
+//! let msg = Message::signal("/org/zbus/Example", "org.zbus.Example", "Ping")?.build(&"hello")?;
+//! writer.write_message(&msg)?;
+//!
+//! let bytes = writer.into_inner();
+//! let mut reader = pcap::Reader::new(&bytes[..])?;
+//! let read = reader.read_message()?.unwrap();
+//! assert_eq!(read.header().member().unwrap(), "Ping");
+//! assert_eq!(read.body().deserialize::<&str>()?, "hello");
+//! assert!(reader.read_message()?.is_none());
+//! # Ok::<(), zbus::Error>(())
+//! ```
+//!
+//! [pcap]: https://www.tcpdump.org/manpages/pcap-savefile.5.html
+//! [`Interceptor`]: crate::connection::Interceptor
+//! [`Connection`]: crate::Connection
+//! [`MessageStream`]: crate::MessageStream
+
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tracing::warn;
+use zvariant::{serialized, Endian, Type};
+
+use crate::{
+    connection::interceptor::{Action, Interceptor},
+    message::{header::MAX_MESSAGE_SIZE, EndianSig, Header},
+    utils::padding_for_8_bytes,
+    Error, Message, Result,
+};
+
+/// The link type of D-Bus captures.
+const DLT_DBUS: u32 = 231;
+const MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const VERSION_MAJOR: u16 = 2;
+const VERSION_MINOR: u16 = 4;
+const GLOBAL_HEADER_SIZE: usize = 24;
+const RECORD_HEADER_SIZE: usize = 16;
+
+/// Writes [`Message`]s in the pcap format.
+///
+/// The pcap file header is written on creation and a record is written for each message, in
+/// native byte order, with a microsecond resolution timestamp.
+#[derive(Debug)]
+pub struct Writer<W> {
+    inner: W,
+}
+
+impl<W: Write> Writer<W> {
+    /// Create a new `Writer`, writing the pcap file header to `inner`.
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        let mut header = Vec::with_capacity(GLOBAL_HEADER_SIZE);
+        header.extend(MAGIC_MICROS.to_ne_bytes());
+        header.extend(VERSION_MAJOR.to_ne_bytes());
+        header.extend(VERSION_MINOR.to_ne_bytes());
+        // Time zone offset and timestamp accuracy, which are always 0 in practice.
+        header.extend(0i32.to_ne_bytes());
+        header.extend(0u32.to_ne_bytes());
+        header.extend((MAX_MESSAGE_SIZE as u32).to_ne_bytes());
+        header.extend(DLT_DBUS.to_ne_bytes());
+        inner.write_all(&header)?;
+
+        Ok(Self { inner })
+    }
+
+    /// Write `msg`, timestamped with the current time.
+    pub fn write_message(&mut self, msg: &Message) -> io::Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        self.write_message_at(msg, timestamp)
+    }
+
+    /// Write `msg`, timestamped with `timestamp` since the Unix epoch.
+    pub fn write_message_at(&mut self, msg: &Message, timestamp: Duration) -> io::Result<()> {
+        let data = msg.data();
+        let len = u32::try_from(data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "message too large"))?;
+        // The format is limited to 32-bit timestamps.
+        let secs = u32::try_from(timestamp.as_secs()).unwrap_or(u32::MAX);
+
+        let mut header = Vec::with_capacity(RECORD_HEADER_SIZE);
+        header.extend(secs.to_ne_bytes());
+        header.extend(timestamp.subsec_micros().to_ne_bytes());
+        // The captured and original lengths, always the same since we capture complete messages.
+        header.extend(len.to_ne_bytes());
+        header.extend(len.to_ne_bytes());
+        self.inner.write_all(&header)?;
+        self.inner.write_all(data)
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// Get a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Consume the `Writer`, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Reads [`Message`]s from a pcap file.
+///
+/// Files in either byte order and with either microsecond or nanosecond resolution timestamps are
+/// supported. Only files with the `DLT_DBUS` link type are accepted.
+///
+/// `Reader` is also an iterator over the messages of the file.
+#[derive(Debug)]
+pub struct Reader<R> {
+    inner: R,
+    endian: Endian,
+    nanos: bool,
+}
+
+impl<R: Read> Reader<R> {
+    /// Create a new `Reader`, reading and validating the pcap file header from `inner`.
+    pub fn new(mut inner: R) -> Result<Self> {
+        let mut header = [0; GLOBAL_HEADER_SIZE];
+        inner.read_exact(&mut header)?;
+
+        let magic = [header[0], header[1], header[2], header[3]];
+        let (endian, nanos) = [Endian::Little, Endian::Big]
+            .into_iter()
+            .find_map(|endian| match read_u32(endian, magic) {
+                MAGIC_MICROS => Some((endian, false)),
+                MAGIC_NANOS => Some((endian, true)),
+                _ => None,
+            })
+            .ok_or_else(|| Error::Failure("not a pcap file".to_string()))?;
+        let link_type = read_u32(endian, [header[20], header[21], header[22], header[23]]);
+        if link_type != DLT_DBUS {
+            return Err(Error::Failure(format!(
+                "not a D-Bus capture (link type {link_type})"
+            )));
+        }
+
+        Ok(Self {
+            inner,
+            endian,
+            nanos,
+        })
+    }
+
+    /// Read the next message.
+    ///
+    /// Returns `None` at the end of the file.
+    pub fn read_message(&mut self) -> Result<Option<Message>> {
+        Ok(self.read_record()?.map(|(_, msg)| msg))
+    }
+
+    /// Read the next message, along with its timestamp since the Unix epoch.
+    ///
+    /// Returns `None` at the end of the file. A record cut short by the end of the file, or
+    /// containing an invalid message, is an error.
+    pub fn read_record(&mut self) -> Result<Option<(Duration, Message)>> {
+        let mut header = [0; RECORD_HEADER_SIZE];
+        if !self.read_record_header(&mut header)? {
+            return Ok(None);
+        }
+        let field = |i: usize| {
+            read_u32(
+                self.endian,
+                [header[i], header[i + 1], header[i + 2], header[i + 3]],
+            )
+        };
+        let secs = u64::from(field(0));
+        let subsecs = field(4);
+        let timestamp = if self.nanos {
+            Duration::new(secs, subsecs)
+        } else {
+            Duration::new(secs, 0) + Duration::from_micros(subsecs.into())
+        };
+        let captured_len = field(8) as usize;
+        let original_len = field(12) as usize;
+        if captured_len != original_len {
+            return Err(Error::Failure(format!(
+                "truncated message in capture ({captured_len} of {original_len} bytes)"
+            )));
+        }
+        if captured_len > MAX_MESSAGE_SIZE {
+            return Err(Error::ExcessData);
+        }
+
+        let mut bytes = vec![0; captured_len];
+        self.inner.read_exact(&mut bytes)?;
+        let endian_sig = EndianSig::try_from(*bytes.first().ok_or(Error::InvalidField)?)?;
+        let ctxt = serialized::Context::new_dbus(Endian::from(endian_sig), 0);
+        let data = serialized::Data::new(bytes, ctxt);
+        let header_len = data.validate(<Header<'_> as Type>::signature())?;
+        // SAFETY: The header was just validated and the body is validated right after.
+        let msg = unsafe { Message::from_bytes(data) }?;
+
+        let body_offset = header_len + padding_for_8_bytes(header_len);
+        if body_offset + msg.primary_header().body_len() as usize != captured_len {
+            return Err(Error::Failure(format!(
+                "message length in capture doesn't match its header ({captured_len} bytes)"
+            )));
+        }
+        // File descriptors aren't part of the capture, so the bodies of the messages that carried
+        // some can't be validated. Their deserialization still fails for invalid encodings.
+        if msg.header().unix_fds().unwrap_or(0) == 0 {
+            let body = msg.body();
+            let body_len = match body.signature() {
+                Some(signature) => body.data().validate(signature)?,
+                None => 0,
+            };
+            if body_len != body.len() {
+                return Err(Error::ExcessData);
+            }
+        }
+
+        Ok(Some((timestamp, msg)))
+    }
+
+    // Fill `header`, returning `false` if the end of the file was reached before reading anything.
+    // Reaching the end in the middle of the header means the capture is truncated.
+    fn read_record_header(&mut self, header: &mut [u8; RECORD_HEADER_SIZE]) -> Result<bool> {
+        let mut filled = 0;
+        while filled < header.len() {
+            match self.inner.read(&mut header[filled..]) {
+                Ok(0) if filled == 0 => return Ok(false),
+                Ok(0) => {
+                    return Err(Error::Failure(format!(
+                    "truncated record header in capture ({filled} of {RECORD_HEADER_SIZE} bytes)"
+                )))
+                }
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl<R: Read> Iterator for Reader<R> {
+    type Item = Result<Message>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_message().transpose()
+    }
+}
+
+/// An [`Interceptor`] writing all the messages sent and received by a connection in the pcap
+/// format.
+///
+/// Interceptors see messages in the order they were added in (see [`Interceptor`]), so add the
+/// `Capture` first to capture the received messages exactly as they were read from the socket, or
+/// last to capture the sent messages exactly as they are written to it.
+///
+/// Failures to write the capture are logged and otherwise ignored. Since writing is blocking,
+/// writing to anything slower than a buffered file will slow down the connection.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::{fs::File, io::BufWriter};
+/// use zbus::{connection::Builder, pcap::Capture};
+///
+/// # zbus::block_on(async {
+/// let file = BufWriter::new(File::create("session.pcap")?);
+/// let conn = Builder::session()?
+///     .interceptor(Capture::new(file)?)
+///     .build()
+///     .await?;
+/// # drop(conn);
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`Interceptor`]: crate::connection::Interceptor
+pub struct Capture {
+    writer: Mutex<Writer<Box<dyn Write + Send>>>,
+}
+
+impl Capture {
+    /// Create a new `Capture`, writing the pcap file header to `writer`.
+    pub fn new<W>(writer: W) -> io::Result<Self>
+    where
+        W: Write + Send + 'static,
+    {
+        let writer: Box<dyn Write + Send> = Box::new(writer);
+
+        Ok(Self {
+            writer: Mutex::new(Writer::new(writer)?),
+        })
+    }
+
+    fn capture(&self, msg: &Message) {
+        let mut writer = self.writer.lock().expect("lock poisoned");
+        if let Err(e) = writer.write_message(msg) {
+            warn!("Failed to capture message: {}", e);
+        }
+    }
+}
+
+impl fmt::Debug for Capture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Capture").finish_non_exhaustive()
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        if let Ok(writer) = self.writer.get_mut() {
+            let _ = writer.flush();
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Interceptor for Capture {
+    async fn outgoing(&self, msg: Message) -> Action {
+        self.capture(&msg);
+
+        Action::Pass(msg)
+    }
+
+    async fn incoming(&self, msg: Message) -> Action {
+        self.capture(&msg);
+
+        Action::Pass(msg)
+    }
+}
+
+fn read_u32(endian: Endian, bytes: [u8; 4]) -> u32 {
+    match endian {
+        Endian::Little => u32::from_le_bytes(bytes),
+        Endian::Big => u32::from_be_bytes(bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "p2p")]
+    use std::sync::{Arc, Mutex};
+
+    #[cfg(feature = "p2p")]
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::*;
+    use crate::message::Type;
+
+    #[test]
+    fn round_trip() {
+        let mut writer = Writer::new(vec![]).unwrap();
+        let call = Message::method("/org/zbus/Test", "Test")
+            .unwrap()
+            .interface("org.zbus.Test")
+            .unwrap()
+            .build(&(42u32, "hello"))
+            .unwrap();
+        let signal = Message::signal("/org/zbus/Test", "org.zbus.Test", "Tested")
+            .unwrap()
+            .endian(Endian::Big)
+            .build(&())
+            .unwrap();
+        let timestamp = Duration::new(1_700_000_000, 123_456_000);
+        writer.write_message_at(&call, timestamp).unwrap();
+        writer.write_message(&signal).unwrap();
+        let bytes = writer.into_inner();
+
+        let mut reader = Reader::new(&bytes[..]).unwrap();
+        let (read_timestamp, read_call) = reader.read_record().unwrap().unwrap();
+        assert_eq!(read_timestamp, timestamp);
+        assert_eq!(read_call.data().bytes(), call.data().bytes());
+        assert_eq!(
+            read_call.body().deserialize::<(u32, &str)>().unwrap(),
+            (42, "hello")
+        );
+        let read_signal = reader.next().unwrap().unwrap();
+        assert_eq!(read_signal.message_type(), Type::Signal);
+        assert_eq!(read_signal.primary_header().endian_sig(), EndianSig::Big);
+        assert_eq!(read_signal.data().bytes(), signal.data().bytes());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn foreign_byte_order() {
+        // A big-endian file header, with nanosecond resolution timestamps.
+        let mut bytes = vec![];
+        bytes.extend(MAGIC_NANOS.to_be_bytes());
+        bytes.extend(VERSION_MAJOR.to_be_bytes());
+        bytes.extend(VERSION_MINOR.to_be_bytes());
+        bytes.extend([0; 8]);
+        bytes.extend(65535u32.to_be_bytes());
+        bytes.extend(DLT_DBUS.to_be_bytes());
+        let msg = Message::signal("/", "org.zbus.Test", "Tested")
+            .unwrap()
+            .build(&())
+            .unwrap();
+        let len = msg.data().len() as u32;
+        bytes.extend(1u32.to_be_bytes());
+        bytes.extend(5u32.to_be_bytes());
+        bytes.extend(len.to_be_bytes());
+        bytes.extend(len.to_be_bytes());
+        bytes.extend(msg.data().bytes());
+
+        let mut reader = Reader::new(&bytes[..]).unwrap();
+        let (timestamp, read) = reader.read_record().unwrap().unwrap();
+        assert_eq!(timestamp, Duration::new(1, 5));
+        assert_eq!(read.data().bytes(), msg.data().bytes());
+
+        // Only the end of the file at a record boundary ends the capture.
+        let mut reader = Reader::new(&bytes[..bytes.len() - 1]).unwrap();
+        assert!(reader.read_record().is_err());
+        let mut reader = Reader::new(&bytes[..GLOBAL_HEADER_SIZE + 10]).unwrap();
+        assert!(reader.read_record().is_err());
+        let mut reader = Reader::new(&bytes[..GLOBAL_HEADER_SIZE]).unwrap();
+        assert!(reader.read_record().unwrap().is_none());
+
+        // Other link types are rejected.
+        let len = bytes.len();
+        bytes[20..24].copy_from_slice(&1u32.to_be_bytes());
+        assert!(Reader::new(&bytes[..len]).is_err());
+    }
+
+    #[test]
+    fn invalid_message() {
+        let msg = Message::method("/org/zbus/Test", "Test")
+            .unwrap()
+            .build(&(42u32, "hello"))
+            .unwrap();
+        let record = |msg_bytes: &[u8]| {
+            let mut writer = Writer::new(vec![]).unwrap();
+            let len = msg_bytes.len() as u32;
+            writer.inner.extend(0u32.to_ne_bytes());
+            writer.inner.extend(0u32.to_ne_bytes());
+            writer.inner.extend(len.to_ne_bytes());
+            writer.inner.extend(len.to_ne_bytes());
+            writer.inner.extend(msg_bytes);
+            writer.into_inner()
+        };
+        let bytes = record(msg.data().bytes());
+        assert!(Reader::new(&bytes[..]).unwrap().read_message().is_ok());
+
+        // Make the string in the body longer than the message.
+        let mut msg_bytes = msg.data().bytes().to_vec();
+        let len = msg_bytes.len();
+        msg_bytes[len - 10..len - 6].copy_from_slice(&100u32.to_le_bytes());
+        let bytes = record(&msg_bytes);
+        assert!(Reader::new(&bytes[..]).unwrap().read_message().is_err());
+
+        // Break the path in the header.
+        let mut msg_bytes = msg.data().bytes().to_vec();
+        msg_bytes[24] = b'x';
+        let bytes = record(&msg_bytes);
+        assert!(Reader::new(&bytes[..]).unwrap().read_message().is_err());
+
+        // Trailing bytes after the body.
+        let mut msg_bytes = msg.data().bytes().to_vec();
+        msg_bytes.extend([0; 8]);
+        let bytes = record(&msg_bytes);
+        assert!(Reader::new(&bytes[..]).unwrap().read_message().is_err());
+    }
+
+    // A `Write` implementation that can be inspected after being handed over.
+    #[cfg(feature = "p2p")]
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    #[cfg(feature = "p2p")]
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "p2p")]
+    #[test]
+    #[timeout(15000)]
+    fn capture() {
+        crate::utils::block_on(test_capture()).unwrap();
+    }
+
+    #[cfg(feature = "p2p")]
+    async fn test_capture() -> Result<()> {
+        use crate::{
+            connection::{socket::Channel, Builder},
+            Guid,
+        };
+
+        struct Echo;
+
+        #[crate::interface(name = "org.zbus.Echo")]
+        impl Echo {
+            fn echo(&self, s: &str) -> String {
+                s.to_string()
+            }
+        }
+
+        let buffer = SharedBuffer::default();
+        let (a, b) = Channel::pair();
+        let guid = Guid::generate();
+        let _server = Builder::authenticated_socket(a, guid.clone())?
+            .p2p()
+            .serve_at("/org/zbus/Echo", Echo)?
+            .build()
+            .await?;
+        let client = Builder::authenticated_socket(b, guid)?
+            .p2p()
+            .interceptor(Capture::new(buffer.clone())?)
+            .build()
+            .await?;
+        let reply = client
+            .call_method(
+                None::<()>,
+                "/org/zbus/Echo",
+                Some("org.zbus.Echo"),
+                "Echo",
+                &"hi",
+            )
+            .await?;
+
+        let bytes = buffer.0.lock().unwrap().clone();
+        let messages = Reader::new(&bytes[..])?.collect::<Result<Vec<_>>>()?;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].message_type(), Type::MethodCall);
+        assert_eq!(messages[0].body().deserialize::<&str>()?, "hi");
+        assert_eq!(messages[1].data().bytes(), reply.data().bytes());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use std::sync::Mutex;
+
+use async_broadcast::{broadcast, InactiveReceiver, Sender, TrySendError};
+use futures_core::stream::Stream;
+use serde::Serialize;
+use static_assertions::assert_impl_all;
+use zbus_names::InterfaceName;
+use zvariant::{DynamicType, ObjectPath, SerializeValue, Type};
+
+use crate::{message::Message, Error, Result};
+
+const MAX_QUEUED_SIGNALS: usize = 64;
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+
+/// The state shared by the in-memory mocks generated by the [`proxy`] macro.
+///
+/// Every call made through a mock is recorded as the [`Message`] a real proxy would have sent, so
+/// that tests can assert on the member name and deserialize the arguments from the body. Property
+/// reads and writes are recorded as the corresponding `org.freedesktop.DBus.Properties` calls.
+///
+/// Signals emitted through the mock are delivered to all the signal streams created from it.
+///
+/// [`proxy`]: attr.proxy.html
+#[derive(Debug)]
+pub struct Mock {
+    interface: InterfaceName<'static>,
+    path: ObjectPath<'static>,
+    calls: Mutex<Vec<Message>>,
+    signal_sender: Sender<Message>,
+    signal_receiver: InactiveReceiver<Message>,
+}
+
+assert_impl_all!(Mock: Send, Sync, Unpin);
+
+impl Mock {
+    /// Create a new `Mock` for the object at `path`, implementing `interface`.
+    pub fn new<I, P>(interface: I, path: P) -> Result<Self>
+    where
+        I: TryInto<InterfaceName<'static>>,
+        I::Error: Into<Error>,
+        P: TryInto<ObjectPath<'static>>,
+        P::Error: Into<Error>,
+    {
+        let (mut signal_sender, signal_receiver) = broadcast(MAX_QUEUED_SIGNALS);
+        // Like a real bus, slow listeners miss the oldest signals rather than blocking the sender.
+        signal_sender.set_overflow(true);
+
+        Ok(Self {
+            interface: interface.try_into().map_err(Into::into)?,
+            path: path.try_into().map_err(Into::into)?,
+            calls: Mutex::new(vec![]),
+            signal_sender,
+            signal_receiver: signal_receiver.deactivate(),
+        })
+    }
+
+    /// The interface of the mocked object.
+    pub fn interface(&self) -> &InterfaceName<'static> {
+        &self.interface
+    }
+
+    /// The path of the mocked object.
+    pub fn path(&self) -> &ObjectPath<'static> {
+        &self.path
+    }
+
+    /// The calls made through the mock so far, oldest first.
+    pub fn calls(&self) -> Vec<Message> {
+        self.calls.lock().expect("lock poisoned").clone()
+    }
+
+    /// Remove and return the calls made through the mock so far, oldest first.
+    pub fn take_calls(&self) -> Vec<Message> {
+        std::mem::take(&mut *self.calls.lock().expect("lock poisoned"))
+    }
+
+    /// Record a call to the method `method_name` with the given arguments.
+    ///
+    /// Returns the recorded call.
+    pub fn record_call<B>(&self, method_name: &str, body: &B) -> Result<Message>
+    where
+        B: Serialize + DynamicType,
+    {
+        let msg = Message::method(&self.path, method_name)?
+            .interface(&self.interface)?
+            .build(body)?;
+
+        Ok(self.record(msg))
+    }
+
+    /// Record a read of the property `property_name`.
+    ///
+    /// Returns the recorded call.
+    pub fn record_get_property(&self, property_name: &str) -> Result<Message> {
+        let msg = Message::method(&self.path, "Get")?
+            .interface(PROPERTIES_INTERFACE)?
+            .build(&(self.interface.as_str(), property_name))?;
+
+        Ok(self.record(msg))
+    }
+
+    /// Record a write of `value` to the property `property_name`.
+    ///
+    /// Returns the recorded call.
+    pub fn record_set_property<T>(&self, property_name: &str, value: &T) -> Result<Message>
+    where
+        T: Serialize + Type,
+    {
+        let msg = Message::method(&self.path, "Set")?
+            .interface(PROPERTIES_INTERFACE)?
+            .build(&(
+                self.interface.as_str(),
+                property_name,
+                SerializeValue(value),
+            ))?;
+
+        Ok(self.record(msg))
+    }
+
+    /// Emit the signal `signal_name` with the given arguments.
+    ///
+    /// If no signal stream was created from this mock, the signal is dropped.
+    pub fn emit_signal<B>(&self, signal_name: &str, body: &B) -> Result<()>
+    where
+        B: Serialize + DynamicType,
+    {
+        let msg = Message::signal(&self.path, &self.interface, signal_name)?.build(body)?;
+        match self.signal_sender.try_broadcast(msg) {
+            Ok(_) | Err(TrySendError::Inactive(_)) => Ok(()),
+            Err(e) => Err(Error::Failure(e.to_string())),
+        }
+    }
+
+    /// Create a stream of all the signals emitted through this mock from now on.
+    pub fn receive_signals(&self) -> impl Stream<Item = Message> + Send + Unpin + 'static {
+        self.signal_receiver.activate_cloned()
+    }
+
+    /// The error to reply to `call` with, when no response was programmed for it.
+    ///
+    /// This is an `org.freedesktop.DBus.Error.UnknownMethod` error, or an
+    /// `org.freedesktop.DBus.Error.UnknownProperty` error for property accesses. Like any error
+    /// reply, it's converted into the error type of the method by the caller.
+    pub fn no_response(&self, call: &Message) -> Error {
+        let header = call.header();
+        let (error_name, detail) = match header.interface() {
+            Some(i) if i == PROPERTIES_INTERFACE => {
+                let property_name = call
+                    .body()
+                    .deserialize_unchecked::<(&str, &str)>()
+                    .map(|(_, name)| name.to_string())
+                    .unwrap_or_default();
+
+                ("org.freedesktop.DBus.Error.UnknownProperty", property_name)
+            }
+            _ => (
+                "org.freedesktop.DBus.Error.UnknownMethod",
+                header.member().map(ToString::to_string).unwrap_or_default(),
+            ),
+        };
+        let detail = format!("No response programmed for `{}.{detail}`", self.interface);
+
+        match Message::method_error(call, error_name).and_then(|b| b.build(&detail)) {
+            Ok(reply) => reply.into(),
+            Err(e) => e,
+        }
+    }
+
+    fn record(&self, msg: Message) -> Message {
+        self.calls.lock().expect("lock poisoned").push(msg.clone());
+
+        msg
+    }
+}
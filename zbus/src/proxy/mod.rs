@@ -29,6 +29,9 @@ use crate::{
 mod builder;
 pub use builder::{Builder, CacheProperties, ProxyDefault};
 
+mod mock;
+pub use mock::Mock;
+
 /// A client-side interface proxy.
 ///
 /// A `Proxy` is a helper to interact with an interface on a remote object.
@@ -1535,4 +1538,101 @@ mod tests {
 
         Ok(())
     }
+
+    #[proxy(
+        default_path = "/org/zbus/Test",
+        default_service = "org.zbus.Test.AllSignals",
+        interface = "org.zbus.Test.AllSignals"
+    )]
+    trait AllSignals {
+        #[zbus(signal)]
+        fn first(&self, n: u32) -> Result<()>;
+
+        #[zbus(signal)]
+        fn second(&self, msg: &str) -> Result<()>;
+    }
+
+    struct AllSignalsIface;
+
+    #[interface(name = "org.zbus.Test.AllSignals")]
+    impl AllSignalsIface {
+        #[zbus(signal)]
+        async fn first(context: &SignalContext<'_>, n: u32) -> Result<()>;
+
+        #[zbus(signal)]
+        async fn second(context: &SignalContext<'_>, msg: &str) -> Result<()>;
+
+        // Not declared in the proxy so it's expected to be skipped.
+        #[zbus(signal)]
+        async fn third(context: &SignalContext<'_>) -> Result<()>;
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn receive_all_signals_enum() {
+        block_on(test_receive_all_signals_enum()).unwrap();
+    }
+
+    async fn test_receive_all_signals_enum() -> Result<()> {
+        let server_conn = connection::Builder::session()?
+            .name("org.zbus.Test.AllSignals")?
+            .serve_at("/org/zbus/Test", AllSignalsIface)?
+            .build()
+            .await?;
+        let client_conn = Connection::session().await?;
+
+        let proxy = AllSignalsProxy::new(&client_conn).await?;
+        let mut stream = proxy.receive_all_signals().await?;
+
+        let ctxt = SignalContext::new(&server_conn, "/org/zbus/Test")?;
+        AllSignalsIface::first(&ctxt, 1).await?;
+        AllSignalsIface::third(&ctxt).await?;
+        AllSignalsIface::second(&ctxt, "two").await?;
+        AllSignalsIface::first(&ctxt, 3).await?;
+
+        match stream.next().await.unwrap() {
+            AllSignalsSignal::First(signal) => assert_eq!(signal.args()?.n, 1),
+            s => panic!("unexpected signal: {s:?}"),
+        }
+        match stream.next().await.unwrap() {
+            AllSignalsSignal::Second(signal) => assert_eq!(signal.args()?.msg, "two"),
+            s => panic!("unexpected signal: {s:?}"),
+        }
+        match stream.next().await.unwrap() {
+            AllSignalsSignal::First(signal) => assert_eq!(signal.args()?.n, 3),
+            s => panic!("unexpected signal: {s:?}"),
+        }
+
+        Ok(())
+    }
+
+    // The blocking API can't be used from within an async context (that panics under tokio), so
+    // this is tested separately.
+    #[test]
+    #[timeout(15000)]
+    fn receive_all_signals_enum_blocking() -> Result<()> {
+        let service = "org.zbus.Test.AllSignals.Blocking";
+        let server_conn = crate::blocking::connection::Builder::session()?
+            .name(service)?
+            .serve_at("/org/zbus/Test", AllSignalsIface)?
+            .build()?;
+        let client_conn = crate::blocking::Connection::session()?;
+
+        let proxy = AllSignalsProxyBlocking::builder(&client_conn)
+            .destination(service)?
+            .build()?;
+        let mut iter = proxy.receive_all_signals()?;
+
+        let ctxt = SignalContext::new(server_conn.inner(), "/org/zbus/Test")?;
+        block_on(async {
+            AllSignalsIface::third(&ctxt).await?;
+            AllSignalsIface::second(&ctxt, "four").await
+        })?;
+        match iter.next().unwrap() {
+            AllSignalsSignal::Second(signal) => assert_eq!(signal.args()?.msg, "four"),
+            s => panic!("unexpected signal: {s:?}"),
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,87 @@
+use std::{
+    io::{self, BufRead, BufReader},
+    process::{Child, Command, Stdio},
+};
+
+use crate::{connection::Builder, Address, Connection, Error, Result, Task};
+
+/// A private message bus for tests.
+///
+/// This launches a new `dbus-daemon` instance, using the standard session bus configuration. The
+/// bus is isolated from the session bus of the user, so tests can own names and emit signals
+/// without interfering with each other or with the rest of the system.
+///
+/// The daemon is killed when the `TestBus` is dropped, disconnecting all the connections to it.
+///
+/// `dbus-daemon` must be in `PATH`.
+#[derive(Debug)]
+pub struct TestBus {
+    daemon: Child,
+    address: Address,
+}
+
+impl TestBus {
+    /// Launch a new private bus.
+    pub async fn new() -> Result<Self> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdout = daemon
+            .stdout
+            .take()
+            .expect("stdout of dbus-daemon is piped");
+
+        // The daemon prints its address on the first line, once it's ready for connections.
+        let line = Task::spawn_blocking(
+            move || {
+                let mut line = String::new();
+                BufReader::new(stdout).read_line(&mut line).map(|_| line)
+            },
+            "dbus-daemon address reader",
+        )
+        .await;
+        let address = line
+            .map_err(Error::from)
+            .and_then(|line| match line.trim() {
+                "" => Err(Error::InputOutput(
+                    io::Error::new(io::ErrorKind::UnexpectedEof, "dbus-daemon exited early").into(),
+                )),
+                address => address.parse(),
+            });
+
+        match address {
+            Ok(address) => Ok(Self { daemon, address }),
+            Err(e) => {
+                let _ = daemon.kill();
+                let _ = daemon.wait();
+
+                Err(e)
+            }
+        }
+    }
+
+    /// The address of the bus.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Create a connection builder for this bus.
+    pub fn connection_builder(&self) -> Result<Builder<'static>> {
+        Builder::address(self.address.clone())
+    }
+
+    /// Create a new connection to this bus.
+    pub async fn connection(&self) -> Result<Connection> {
+        self.connection_builder()?.build().await
+    }
+}
+
+impl Drop for TestBus {
+    fn drop(&mut self) {
+        // Errors mean the daemon already exited, in which case there is nothing left to clean up.
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
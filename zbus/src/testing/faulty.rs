@@ -0,0 +1,586 @@
+use std::{collections::BTreeMap, io, time::Duration};
+
+#[cfg(unix)]
+use std::os::fd::{AsFd, BorrowedFd, OwnedFd};
+
+use crate::{
+    connection::socket::{BoxedSplit, ReadHalf, Socket, Split, WriteHalf},
+    fdo::ConnectionCredentials,
+    Message,
+};
+
+#[cfg(unix)]
+type RecvmsgResult = io::Result<(usize, Vec<OwnedFd>)>;
+
+#[cfg(not(unix))]
+type RecvmsgResult = io::Result<usize>;
+
+/// A fault to inject while transferring a message through a [`FaultySocket`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Fault {
+    /// Delay the message by the given duration, on top of the [`Faults::latency`].
+    Delay(Duration),
+    /// XOR the byte at `offset` in the encoded message with `mask`.
+    ///
+    /// Offsets past the end of the message are ignored.
+    Corrupt {
+        /// The offset of the byte to corrupt.
+        offset: usize,
+        /// The bits to flip.
+        mask: u8,
+    },
+    /// Fail to pass the file descriptors of the message.
+    ///
+    /// When sending, the message is rejected with an [`io::ErrorKind::InvalidInput`] error, like
+    /// transports that can't pass file descriptors do. When receiving, the message is delivered
+    /// without its file descriptors.
+    FailFds,
+    /// Disconnect instead of transferring the message.
+    ///
+    /// All the following transfers in the same direction fail as well.
+    Disconnect,
+}
+
+/// The faults to inject in one direction of a [`FaultySocket`].
+///
+/// Messages are counted from 0, separately for each direction. Since faults are tied to message
+/// indices rather than timing, the same script always leads to the same failures.
+#[derive(Debug, Clone, Default)]
+pub struct Faults {
+    latency: Duration,
+    max_chunk: Option<usize>,
+    scripted: BTreeMap<usize, Vec<Fault>>,
+}
+
+impl Faults {
+    /// Create a new `Faults` that doesn't inject any fault.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delay every message by `latency`.
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+
+        self
+    }
+
+    /// Transfer messages in chunks of at most `max_chunk` bytes.
+    ///
+    /// This exercises the handling of partial reads and writes. For writes, the underlying socket
+    /// must be byte-oriented (i.e implement [`WriteHalf::sendmsg`]), so it can't be used with
+    /// a [`Channel`] for example.
+    ///
+    /// # Panics
+    ///
+    /// If `max_chunk` is 0.
+    ///
+    /// [`Channel`]: crate::connection::socket::Channel
+    pub fn max_chunk(mut self, max_chunk: usize) -> Self {
+        assert!(max_chunk > 0, "`max_chunk` must be at least 1");
+        self.max_chunk = Some(max_chunk);
+
+        self
+    }
+
+    /// Inject `fault` when transferring the message at `index`.
+    ///
+    /// Several faults can be injected for the same message.
+    ///
+    /// Note that [`Fault::Corrupt`] on the write side requires a byte-oriented underlying socket,
+    /// just like [`Faults::max_chunk`].
+    pub fn at(mut self, index: usize, fault: Fault) -> Self {
+        self.scripted.entry(index).or_default().push(fault);
+
+        self
+    }
+
+    /// Disconnect after transferring `count` messages.
+    pub fn disconnect_after(self, count: usize) -> Self {
+        self.at(count, Fault::Disconnect)
+    }
+
+    fn take(&mut self, index: usize) -> Vec<Fault> {
+        self.scripted.remove(&index).unwrap_or_default()
+    }
+}
+
+/// A socket wrapper that injects faults, for testing how code behaves with broken peers.
+///
+/// The faults to inject in each direction are scripted through [`Faults`]. Any socket can be
+/// wrapped, including an in-process [`Channel`].
+///
+/// Since messages are intercepted whole, `FaultySocket` can't be used for the authentication
+/// handshake. Use it with [`Builder::authenticated_socket`].
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use zbus::{
+///     connection::{socket::Channel, Builder},
+///     testing::{Fault, Faults, FaultySocket},
+///     Guid,
+/// };
+///
+/// # zbus::block_on(async {
+/// let (server, client) = Channel::pair();
+/// // The client receives every message late, and never receives the second one.
+/// let client = FaultySocket::new(client).read_faults(
+///     Faults::new()
+///         .latency(Duration::from_millis(1))
+///         .at(1, Fault::Disconnect),
+/// );
+///
+/// let guid = Guid::generate();
+/// let _server = Builder::authenticated_socket(server, guid.clone())?
+///     .p2p()
+///     .build()
+///     .await?;
+/// let client = Builder::authenticated_socket(client, guid)?
+///     .p2p()
+///     .build()
+///     .await?;
+/// # let _ = client;
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`Channel`]: crate::connection::socket::Channel
+/// [`Builder::authenticated_socket`]: crate::connection::Builder::authenticated_socket
+#[derive(Debug)]
+pub struct FaultySocket {
+    inner: BoxedSplit,
+    read_faults: Faults,
+    write_faults: Faults,
+    can_pass_unix_fd: Option<bool>,
+}
+
+impl FaultySocket {
+    /// Wrap `socket`, without injecting any fault yet.
+    pub fn new<S>(socket: S) -> Self
+    where
+        S: Into<BoxedSplit>,
+    {
+        Self {
+            inner: socket.into(),
+            read_faults: Faults::new(),
+            write_faults: Faults::new(),
+            can_pass_unix_fd: None,
+        }
+    }
+
+    /// The faults to inject when receiving messages.
+    pub fn read_faults(mut self, faults: Faults) -> Self {
+        self.read_faults = faults;
+
+        self
+    }
+
+    /// The faults to inject when sending messages.
+    pub fn write_faults(mut self, faults: Faults) -> Self {
+        self.write_faults = faults;
+
+        self
+    }
+
+    /// Override whether the socket claims to support passing file descriptors.
+    ///
+    /// By default, this is the same as the wrapped socket. Since a [`Channel`] passes messages
+    /// as-is, enabling this allows to exchange file descriptors over it.
+    ///
+    /// [`Channel`]: crate::connection::socket::Channel
+    pub fn can_pass_unix_fd(mut self, can_pass_unix_fd: bool) -> Self {
+        self.can_pass_unix_fd = Some(can_pass_unix_fd);
+
+        self
+    }
+}
+
+impl Socket for FaultySocket {
+    type ReadHalf = FaultyReader;
+    type WriteHalf = FaultyWriter;
+
+    fn split(self) -> Split<Self::ReadHalf, Self::WriteHalf> {
+        let (read, write) = self.inner.take();
+
+        Split::new(
+            FaultyReader {
+                inner: read,
+                faults: self.read_faults,
+                can_pass_unix_fd: self.can_pass_unix_fd,
+                index: 0,
+                disconnected: false,
+                pending: vec![],
+                pos: 0,
+                #[cfg(unix)]
+                pending_fds: vec![],
+            },
+            FaultyWriter {
+                inner: write,
+                faults: self.write_faults,
+                can_pass_unix_fd: self.can_pass_unix_fd,
+                index: 0,
+                disconnected: false,
+            },
+        )
+    }
+}
+
+/// The read half of a [`FaultySocket`].
+#[derive(Debug)]
+pub struct FaultyReader {
+    inner: Box<dyn ReadHalf>,
+    faults: Faults,
+    can_pass_unix_fd: Option<bool>,
+    index: usize,
+    disconnected: bool,
+    // The encoded message being received.
+    pending: Vec<u8>,
+    pos: usize,
+    #[cfg(unix)]
+    pending_fds: Vec<OwnedFd>,
+}
+
+impl FaultyReader {
+    // Receive the next message from the wrapped socket and apply the faults scripted for it.
+    //
+    // Returns `false` on disconnection.
+    async fn receive_next(&mut self) -> io::Result<bool> {
+        let faults = self.faults.take(self.index);
+        self.index += 1;
+        delay(self.faults.latency, &faults).await;
+        if self.disconnected || faults.contains(&Fault::Disconnect) {
+            self.disconnected = true;
+
+            return Ok(false);
+        }
+
+        let msg = self
+            .inner
+            .receive_message(0, &mut vec![])
+            .await
+            .map_err(to_io_error)?;
+        let data = msg.data();
+        self.pending = data.to_vec();
+        self.pos = 0;
+        corrupt(&mut self.pending, &faults);
+        #[cfg(unix)]
+        if !faults.contains(&Fault::FailFds) {
+            self.pending_fds = data
+                .fds()
+                .iter()
+                .map(|fd| fd.as_fd().try_clone_to_owned())
+                .collect::<io::Result<_>>()?;
+        }
+
+        Ok(true)
+    }
+}
+
+#[async_trait::async_trait]
+impl ReadHalf for FaultyReader {
+    async fn recvmsg(&mut self, buf: &mut [u8]) -> RecvmsgResult {
+        if self.pos == self.pending.len() && !self.receive_next().await? {
+            #[cfg(unix)]
+            return Ok((0, vec![]));
+            #[cfg(not(unix))]
+            return Ok(0);
+        }
+
+        let remaining = &self.pending[self.pos..];
+        let len = remaining
+            .len()
+            .min(buf.len())
+            .min(self.faults.max_chunk.unwrap_or(usize::MAX));
+        buf[..len].copy_from_slice(&remaining[..len]);
+        self.pos += len;
+
+        #[cfg(unix)]
+        return Ok((len, std::mem::take(&mut self.pending_fds)));
+        #[cfg(not(unix))]
+        return Ok(len);
+    }
+
+    fn can_pass_unix_fd(&self) -> bool {
+        self.can_pass_unix_fd
+            .unwrap_or_else(|| self.inner.can_pass_unix_fd())
+    }
+
+    async fn peer_credentials(&mut self) -> io::Result<ConnectionCredentials> {
+        self.inner.peer_credentials().await
+    }
+}
+
+/// The write half of a [`FaultySocket`].
+#[derive(Debug)]
+pub struct FaultyWriter {
+    inner: Box<dyn WriteHalf>,
+    faults: Faults,
+    can_pass_unix_fd: Option<bool>,
+    index: usize,
+    disconnected: bool,
+}
+
+#[async_trait::async_trait]
+impl WriteHalf for FaultyWriter {
+    async fn send_message(&mut self, msg: &Message) -> crate::Result<()> {
+        let faults = self.faults.take(self.index);
+        self.index += 1;
+        delay(self.faults.latency, &faults).await;
+        if faults.contains(&Fault::Disconnect) && !self.disconnected {
+            self.disconnected = true;
+            // Let the peer notice the disconnection too.
+            self.inner.close().await?;
+        }
+        if self.disconnected {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "disconnected").into());
+        }
+
+        let data = msg.data();
+        #[cfg(unix)]
+        if faults.contains(&Fault::FailFds) && !data.fds().is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "failed to pass file descriptors",
+            )
+            .into());
+        }
+
+        let corrupted = faults.iter().any(|f| matches!(f, Fault::Corrupt { .. }));
+        if self.faults.max_chunk.is_none() && !corrupted {
+            return self.inner.send_message(msg).await;
+        }
+
+        let mut bytes = data.to_vec();
+        corrupt(&mut bytes, &faults);
+        let max_chunk = self.faults.max_chunk.unwrap_or(usize::MAX);
+        let mut pos = 0;
+        while pos < bytes.len() {
+            #[cfg(unix)]
+            let fds: Vec<BorrowedFd<'_>> = if pos == 0 {
+                data.fds().iter().map(|f| f.as_fd()).collect()
+            } else {
+                vec![]
+            };
+            let end = bytes.len().min(pos + max_chunk);
+            pos += self
+                .inner
+                .sendmsg(
+                    &bytes[pos..end],
+                    #[cfg(unix)]
+                    &fds,
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+    async fn send_zero_byte(&mut self) -> io::Result<Option<usize>> {
+        self.inner.send_zero_byte().await
+    }
+
+    async fn close(&mut self) -> io::Result<()> {
+        self.inner.close().await
+    }
+
+    fn can_pass_unix_fd(&self) -> bool {
+        self.can_pass_unix_fd
+            .unwrap_or_else(|| self.inner.can_pass_unix_fd())
+    }
+
+    async fn peer_credentials(&mut self) -> io::Result<ConnectionCredentials> {
+        self.inner.peer_credentials().await
+    }
+}
+
+async fn delay(latency: Duration, faults: &[Fault]) {
+    let delay = faults.iter().fold(latency, |delay, fault| match fault {
+        Fault::Delay(d) => delay + *d,
+        _ => delay,
+    });
+    if !delay.is_zero() {
+        super::sleep(delay).await;
+    }
+}
+
+fn corrupt(bytes: &mut [u8], faults: &[Fault]) {
+    for fault in faults {
+        if let Fault::Corrupt { offset, mask } = fault {
+            if let Some(byte) = bytes.get_mut(*offset) {
+                *byte ^= mask;
+            }
+        }
+    }
+}
+
+fn to_io_error(e: crate::Error) -> io::Error {
+    match e {
+        crate::Error::InputOutput(e) => io::Error::new(e.kind(), e),
+        e => io::Error::other(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::*;
+    use crate::{
+        connection::{socket::Channel, Builder},
+        interface, proxy,
+        utils::block_on,
+        Connection, Guid, Result,
+    };
+
+    struct Echo;
+
+    #[interface(name = "org.zbus.Testing.Echo")]
+    impl Echo {
+        fn echo(&self, s: &str) -> String {
+            s.to_string()
+        }
+    }
+
+    #[proxy(
+        interface = "org.zbus.Testing.Echo",
+        default_service = "org.zbus.Testing.Echo",
+        default_path = "/org/zbus/Testing/Echo"
+    )]
+    trait Echo {
+        fn echo(&self, s: &str) -> Result<String>;
+    }
+
+    async fn connect<F>(faulty: F) -> Result<(Connection, Connection)>
+    where
+        F: FnOnce(FaultySocket) -> FaultySocket,
+    {
+        let (server, client) = Channel::pair();
+        let guid = Guid::generate();
+        let server = Builder::authenticated_socket(server, guid.clone())?
+            .p2p()
+            .serve_at("/org/zbus/Testing/Echo", Echo)?
+            .build()
+            .await?;
+        let client = Builder::authenticated_socket(faulty(FaultySocket::new(client)), guid)?
+            .p2p()
+            .build()
+            .await?;
+
+        Ok((server, client))
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn partial_reads() {
+        block_on(async {
+            let faults = Faults::new()
+                .max_chunk(3)
+                .latency(Duration::from_millis(1))
+                .at(1, Fault::Delay(Duration::from_millis(10)));
+            let (_server, client) = connect(|s| s.read_faults(faults)).await?;
+            let proxy = EchoProxy::new(&client).await?;
+
+            for s in ["hello", "world"] {
+                assert_eq!(proxy.echo(s).await?, s);
+            }
+
+            Ok::<_, crate::Error>(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn read_disconnect() {
+        block_on(async {
+            let faults = Faults::new().disconnect_after(1);
+            let (_server, client) = connect(|s| s.read_faults(faults)).await?;
+            let proxy = EchoProxy::new(&client).await?;
+
+            assert_eq!(proxy.echo("hello").await?, "hello");
+            // The reply never arrives but the call must not hang.
+            assert!(proxy.echo("world").await.is_err());
+
+            Ok::<_, crate::Error>(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn write_disconnect() {
+        block_on(async {
+            let faults = Faults::new().disconnect_after(1);
+            let (_server, client) = connect(|s| s.write_faults(faults)).await?;
+            let proxy = EchoProxy::new(&client).await?;
+
+            assert_eq!(proxy.echo("hello").await?, "hello");
+            let err = proxy.echo("world").await.unwrap_err();
+            assert!(
+                matches!(err, crate::Error::InputOutput(e) if e.kind() == io::ErrorKind::BrokenPipe)
+            );
+
+            Ok::<_, crate::Error>(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn corrupted_read() {
+        block_on(async {
+            // An invalid endianness signature.
+            let faults = Faults::new().at(
+                0,
+                Fault::Corrupt {
+                    offset: 0,
+                    mask: 0xff,
+                },
+            );
+            let (_server, client) = connect(|s| s.read_faults(faults)).await?;
+            let proxy = EchoProxy::new(&client).await?;
+
+            assert!(proxy.echo("hello").await.is_err());
+
+            Ok::<_, crate::Error>(())
+        })
+        .unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn fd_passing_failure() {
+        block_on(async {
+            let faults = Faults::new().at(0, Fault::FailFds);
+            let (_server, client) =
+                connect(|s| s.write_faults(faults).can_pass_unix_fd(true)).await?;
+            let stdin = std::io::stdin();
+            let fd = zvariant::Fd::from(stdin.as_fd());
+
+            let err = client
+                .call_method(
+                    Some("org.zbus.Testing.Echo"),
+                    "/org/zbus/Testing/Echo",
+                    Some("org.zbus.Testing.Echo"),
+                    "Echo",
+                    &(fd,),
+                )
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err, crate::Error::InputOutput(e) if e.kind() == io::ErrorKind::InvalidInput)
+            );
+
+            Ok::<_, crate::Error>(())
+        })
+        .unwrap();
+    }
+}
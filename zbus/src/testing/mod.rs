@@ -0,0 +1,327 @@
+//! Helpers for testing D-Bus services and clients.
+//!
+//! Tests need connections to talk to each other, without depending on a session bus being
+//! available (or polluting it). This module provides two ways to get them:
+//!
+//! * [`p2p_pair`] (and [`p2p_builders`]) to create a pair of peer-to-peer connections, connected
+//!   through an in-process [`Channel`]. This is the fastest option and doesn't need anything from
+//!   the system, but there is no bus, so no name ownership or bus-routed signals.
+//! * [`TestBus`] to launch a private `dbus-daemon` instance and create connections to it.
+//!
+//! The [`expect_signal`], [`expect_no_signal`] and [`expect_property`] functions help to assert
+//! emitted signals and property changes, failing with a timeout error rather than hanging the test
+//! when the expected event never arrives.
+//!
+//! To test how code behaves with broken peers, wrap a socket in a [`FaultySocket`] to inject
+//! latency, partial reads and writes, disconnections, corrupted messages and file descriptor
+//! passing failures.
+//!
+//! All the helpers work with both the `async-io` (default) and `tokio` runtimes.
+//!
+//! This module is only available when the `testing` feature is enabled.
+//!
+//! # Example
+//!
+//! ```
+//! use std::time::Duration;
+//! use zbus::{interface, object_server::SignalContext, proxy, testing};
+//!
+//! struct Greeter;
+//!
+//! #[interface(name = "org.zbus.Greeter")]
+//! impl Greeter {
+//!     async fn greet(
+//!         &self,
+//!         name: &str,
+//!         #[zbus(signal_context)] ctxt: SignalContext<'_>,
+//!     ) -> zbus::fdo::Result<String> {
+//!         Self::greeted(&ctxt, name).await?;
+//!
+//!         Ok(format!("Hello {name}!"))
+//!     }
+//!
+//!     #[zbus(signal)]
+//!     async fn greeted(ctxt: &SignalContext<'_>, name: &str) -> zbus::Result<()>;
+//! }
+//!
+//! #[proxy(
+//!     interface = "org.zbus.Greeter",
+//!     default_service = "org.zbus.Greeter",
+//!     default_path = "/org/zbus/Greeter"
+//! )]
+//! trait Greeter {
+//!     fn greet(&self, name: &str) -> zbus::Result<String>;
+//!
+//!     #[zbus(signal)]
+//!     fn greeted(&self, name: &str) -> zbus::Result<()>;
+//! }
+//!
+//! # zbus::block_on(async {
+//! let (server, client) = testing::p2p_builders()?;
+//! let _server = server.serve_at("/org/zbus/Greeter", Greeter)?.build().await?;
+//! let client = client.build().await?;
+//!
+//! let proxy = GreeterProxy::new(&client).await?;
+//! let mut greeted = proxy.receive_greeted().await?;
+//! assert_eq!(proxy.greet("Maria").await?, "Hello Maria!");
+//!
+//! let signal = testing::expect_signal(&mut greeted, Duration::from_secs(5)).await?;
+//! assert_eq!(signal.args()?.name(), &"Maria");
+//! testing::expect_no_signal(&mut greeted, Duration::from_millis(10)).await?;
+//! # Ok::<(), zbus::Error>(())
+//! # }).unwrap();
+//! ```
+//!
+//! [`Channel`]: crate::connection::socket::Channel
+
+use futures_core::stream::Stream;
+use futures_util::StreamExt;
+use std::{future::Future, io, time::Duration};
+use zvariant::OwnedValue;
+
+use crate::{
+    connection::{socket::Channel, Builder},
+    proxy::PropertyStream,
+    Connection, Error, Guid, Result,
+};
+
+mod bus;
+pub use bus::TestBus;
+mod faulty;
+pub use faulty::{Fault, Faults, FaultyReader, FaultySocket, FaultyWriter};
+
+/// Create builders for a pair of peer-to-peer connections, connected through an in-process
+/// [`Channel`].
+///
+/// The first builder is for the server side and the second one for the client side. Since no
+/// authentication is involved, the builders can be built in any order, on the same task. Use this
+/// over [`p2p_pair`] to configure the connections before building them, e.g to serve interfaces
+/// on the server side from the start.
+///
+/// [`Channel`]: crate::connection::socket::Channel
+pub fn p2p_builders() -> Result<(Builder<'static>, Builder<'static>)> {
+    let (server, client) = Channel::pair();
+    let guid = Guid::generate();
+
+    Ok((
+        Builder::authenticated_socket(server, guid.clone())?.p2p(),
+        Builder::authenticated_socket(client, guid)?.p2p(),
+    ))
+}
+
+/// Create a pair of connected peer-to-peer connections.
+///
+/// Returns the server and client connections, in that order. See [`p2p_builders`] for details.
+pub async fn p2p_pair() -> Result<(Connection, Connection)> {
+    let (server, client) = p2p_builders()?;
+
+    Ok((server.build().await?, client.build().await?))
+}
+
+/// Await `future`, failing if it doesn't complete within `duration`.
+///
+/// On timeout, an [`Error::InputOutput`] error of [`io::ErrorKind::TimedOut`] kind is returned.
+pub async fn timeout<F>(duration: Duration, future: F) -> Result<F::Output>
+where
+    F: Future,
+{
+    #[cfg(not(feature = "tokio"))]
+    {
+        use futures_util::future::{select, Either};
+
+        let future = std::pin::pin!(future);
+        match select(future, async_io::Timer::after(duration)).await {
+            Either::Left((output, _)) => Ok(output),
+            Either::Right(_) => Err(timed_out(duration)),
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    {
+        tokio::time::timeout(duration, future)
+            .await
+            .map_err(|_| timed_out(duration))
+    }
+}
+
+/// Wait for the next item of `stream`, typically a stream of signals.
+///
+/// Fails if the stream ends, or no item is received within `duration`.
+pub async fn expect_signal<S>(stream: &mut S, duration: Duration) -> Result<S::Item>
+where
+    S: Stream + Unpin,
+{
+    timeout(duration, stream.next())
+        .await?
+        .ok_or_else(|| Error::Failure("stream ended while expecting a signal".to_string()))
+}
+
+/// Ensure that no item of `stream`, typically a stream of signals, is received for `duration`.
+///
+/// Fails if an item is received in the meantime. The end of the stream is not an error.
+pub async fn expect_no_signal<S>(stream: &mut S, duration: Duration) -> Result<()>
+where
+    S: Stream + Unpin,
+{
+    match timeout(duration, stream.next()).await {
+        Ok(Some(_)) => Err(Error::Failure(format!(
+            "unexpected signal received within {duration:?}"
+        ))),
+        Ok(None) | Err(_) => Ok(()),
+    }
+}
+
+/// Wait for the property behind `stream` to change to `expected`.
+///
+/// Changes to other values are skipped. Fails if the value doesn't change to `expected` within
+/// `duration`.
+///
+/// Note that property changes are only reported by proxies with property caching enabled.
+pub async fn expect_property<T>(
+    stream: &mut PropertyStream<'_, T>,
+    expected: &T,
+    duration: Duration,
+) -> Result<()>
+where
+    T: TryFrom<OwnedValue> + PartialEq + Unpin,
+    T::Error: Into<Error>,
+{
+    timeout(duration, async {
+        while let Some(changed) = stream.next().await {
+            if changed.get().await? == *expected {
+                return Ok(());
+            }
+        }
+
+        Err(Error::Failure(
+            "stream ended while expecting a property change".to_string(),
+        ))
+    })
+    .await?
+}
+
+async fn sleep(duration: Duration) {
+    #[cfg(not(feature = "tokio"))]
+    async_io::Timer::after(duration).await;
+
+    #[cfg(feature = "tokio")]
+    tokio::time::sleep(duration).await;
+}
+
+fn timed_out(duration: Duration) -> Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("timed out after {duration:?}"),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::*;
+    use crate::{interface, object_server::SignalContext, proxy, utils::block_on};
+
+    struct Counter(u32);
+
+    #[interface(name = "org.zbus.Testing.Counter")]
+    impl Counter {
+        async fn increment(&mut self, #[zbus(signal_context)] ctxt: SignalContext<'_>) -> u32 {
+            self.0 += 1;
+            self.count_changed(&ctxt).await.unwrap();
+            Self::incremented(&ctxt, self.0).await.unwrap();
+
+            self.0
+        }
+
+        #[zbus(property)]
+        fn count(&self) -> u32 {
+            self.0
+        }
+
+        #[zbus(signal)]
+        async fn incremented(ctxt: &SignalContext<'_>, count: u32) -> Result<()>;
+    }
+
+    #[proxy(
+        interface = "org.zbus.Testing.Counter",
+        default_service = "org.zbus.Testing.Counter",
+        default_path = "/org/zbus/Testing/Counter"
+    )]
+    trait Counter {
+        fn increment(&self) -> Result<u32>;
+
+        #[zbus(property)]
+        fn count(&self) -> Result<u32>;
+
+        #[zbus(signal)]
+        fn incremented(&self, count: u32) -> Result<()>;
+    }
+
+    async fn check_counter(proxy: &CounterProxy<'_>) -> Result<()> {
+        let mut incremented = proxy.receive_incremented().await?;
+        let mut count_changed = proxy.receive_count_changed().await;
+        expect_no_signal(&mut incremented, Duration::from_millis(10)).await?;
+
+        assert_eq!(proxy.increment().await?, 1);
+        let signal = expect_signal(&mut incremented, Duration::from_secs(5)).await?;
+        assert_eq!(signal.args()?.count, 1);
+        expect_property(&mut count_changed, &1, Duration::from_secs(5)).await?;
+
+        // Nothing else should come.
+        let err = expect_signal(&mut incremented, Duration::from_millis(10))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InputOutput(e) if e.kind() == io::ErrorKind::TimedOut));
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn p2p() {
+        block_on(test_p2p()).unwrap();
+    }
+
+    async fn test_p2p() -> Result<()> {
+        let (server, client) = p2p_builders()?;
+        let _server = server
+            .serve_at("/org/zbus/Testing/Counter", Counter(0))?
+            .build()
+            .await?;
+        let client = client.build().await?;
+        let proxy = CounterProxy::new(&client).await?;
+
+        check_counter(&proxy).await
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn private_bus() {
+        block_on(test_private_bus()).unwrap();
+    }
+
+    async fn test_private_bus() -> Result<()> {
+        let bus = TestBus::new().await?;
+        let _server = bus
+            .connection_builder()?
+            .name("org.zbus.Testing.Counter")?
+            .serve_at("/org/zbus/Testing/Counter", Counter(0))?
+            .build()
+            .await?;
+        let client = bus.connection().await?;
+        let proxy = CounterProxy::new(&client).await?;
+
+        check_counter(&proxy).await?;
+
+        // The bus goes away with the `TestBus`.
+        drop(bus);
+        assert!(proxy.increment().await.is_err());
+
+        Ok(())
+    }
+}
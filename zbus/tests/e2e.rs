@@ -62,6 +62,8 @@ trait MyIface {
 
     fn test_error(&self) -> zbus::Result<()>;
 
+    fn test_structured_error(&self, kind: u8) -> Result<(), MyIfaceError>;
+
     fn test_single_struct_arg(&self, arg: ArgStructTest) -> zbus::Result<()>;
 
     fn test_single_struct_ret(&self) -> zbus::Result<ArgStructTest>;
@@ -193,6 +195,11 @@ impl MyIfaceImpl {
 #[zbus(prefix = "org.freedesktop.MyIface.Error")]
 enum MyIfaceError {
     SomethingWentWrong(String),
+    OutOfRange(String, u32, u32),
+    BadArg(ArgStructTest),
+    Busy {
+        retry_after: u64,
+    },
     #[zbus(error)]
     ZBus(zbus::Error),
 }
@@ -238,6 +245,19 @@ impl MyIfaceImpl {
         Err(MyIfaceError::SomethingWentWrong("oops".to_string()))
     }
 
+    #[instrument]
+    fn test_structured_error(&self, kind: u8) -> Result<(), MyIfaceError> {
+        debug!("`TestStructuredError` called.");
+        Err(match kind {
+            0 => MyIfaceError::OutOfRange("value too large".to_string(), 42, 10),
+            1 => MyIfaceError::BadArg(ArgStructTest {
+                foo: 7,
+                bar: "bad".to_string(),
+            }),
+            _ => MyIfaceError::Busy { retry_after: 5 },
+        })
+    }
+
     #[instrument]
     fn test_single_struct_arg(
         &self,
@@ -654,6 +674,24 @@ async fn my_iface_test(conn: Connection, event: Event) -> zbus::Result<u32> {
     assert_eq!(proxy.cached_count()?, None);
 
     proxy.test_header().await?;
+    match proxy.test_structured_error(0).await {
+        Err(MyIfaceError::OutOfRange(desc, value, max)) => {
+            assert_eq!(desc, "value too large");
+            assert_eq!((value, max), (42, 10));
+        }
+        e => panic!("unexpected result: {e:?}"),
+    }
+    match proxy.test_structured_error(1).await {
+        Err(MyIfaceError::BadArg(arg)) => {
+            assert_eq!(arg.foo, 7);
+            assert_eq!(arg.bar, "bad");
+        }
+        e => panic!("unexpected result: {e:?}"),
+    }
+    match proxy.test_structured_error(2).await {
+        Err(MyIfaceError::Busy { retry_after }) => assert_eq!(retry_after, 5),
+        e => panic!("unexpected result: {e:?}"),
+    }
     proxy
         .test_single_struct_arg(ArgStructTest {
             foo: 1,
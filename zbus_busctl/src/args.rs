@@ -0,0 +1,282 @@
+//! The text syntax of values, as used by `busctl`.
+//!
+//! Values are given as a sequence of tokens, guided by their signature:
+//!
+//! * Basic types are given as is, e.g `42` or `hello` (booleans also accept `yes`/`no`).
+//! * Arrays are given as the number of elements, followed by the elements.
+//! * Dicts are given as the number of entries, followed by the key and value of each entry.
+//! * Variants are given as the signature of the contained value, followed by the value.
+//! * Structures are given as their fields.
+//!
+//! E.g `a{sv} 2 Name s zbus Answer u 42`.
+
+use std::{error::Error, str::FromStr};
+
+use zvariant::{
+    Array, Dict, ObjectPath, ParsedSignature, Signature, SignatureEntry, StructureBuilder, Value,
+};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Parse the values of type `signature` from `tokens`.
+///
+/// All the tokens must be consumed.
+pub fn parse(signature: &str, tokens: &[String]) -> Result<Vec<Value<'static>>> {
+    let signature = ParsedSignature::parse_str(signature)
+        .map_err(|e| format!("invalid signature `{signature}`: {e}"))?;
+
+    let mut parser = Parser {
+        tokens: tokens.iter(),
+    };
+    let values = signature
+        .iter()
+        .map(|signature| parser.value(signature))
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(token) = parser.tokens.next() {
+        return Err(format!("unexpected argument `{token}`").into());
+    }
+
+    Ok(values)
+}
+
+/// Format `values` of type `signature`.
+///
+/// This is the opposite of [`parse`], except that strings are quoted.
+pub fn format(signature: &Signature<'_>, values: &[Value<'_>]) -> String {
+    let mut tokens = vec![];
+    for (value, signature) in values.iter().zip(&ParsedSignature::new(signature)) {
+        format_value(&mut tokens, value, signature);
+    }
+
+    tokens.join(" ")
+}
+
+struct Parser<'t, I: Iterator<Item = &'t String>> {
+    tokens: I,
+}
+
+impl<'t, I: Iterator<Item = &'t String>> Parser<'t, I> {
+    fn next(&mut self, what: &str) -> Result<&'t str> {
+        self.tokens
+            .next()
+            .map(String::as_str)
+            .ok_or_else(|| format!("missing {what}").into())
+    }
+
+    fn number<N>(&mut self, what: &str) -> Result<N>
+    where
+        N: FromStr,
+        N::Err: Error + 'static,
+    {
+        let token = self.next(what)?;
+
+        token
+            .parse()
+            .map_err(|e| format!("invalid {what} `{token}`: {e}").into())
+    }
+
+    fn value(&mut self, signature: &SignatureEntry) -> Result<Value<'static>> {
+        let value = match signature {
+            SignatureEntry::U8 => Value::U8(self.number("byte")?),
+            SignatureEntry::Bool => match self.next("boolean")? {
+                "true" | "yes" | "1" => Value::Bool(true),
+                "false" | "no" | "0" => Value::Bool(false),
+                token => return Err(format!("invalid boolean `{token}`").into()),
+            },
+            SignatureEntry::I16 => Value::I16(self.number("int16")?),
+            SignatureEntry::U16 => Value::U16(self.number("uint16")?),
+            SignatureEntry::I32 => Value::I32(self.number("int32")?),
+            SignatureEntry::U32 => Value::U32(self.number("uint32")?),
+            SignatureEntry::I64 => Value::I64(self.number("int64")?),
+            SignatureEntry::U64 => Value::U64(self.number("uint64")?),
+            SignatureEntry::F64 => Value::F64(self.number("double")?),
+            SignatureEntry::Str => Value::from(self.next("string")?.to_string()),
+            SignatureEntry::ObjectPath => {
+                let path = self.next("object path")?;
+                ObjectPath::try_from(path.to_string())
+                    .map_err(|e| format!("invalid object path `{path}`: {e}"))?
+                    .into()
+            }
+            SignatureEntry::Signature => Value::from(self.signature()?),
+            SignatureEntry::Variant => {
+                let signature = self.signature()?;
+                let parsed = ParsedSignature::new(&signature);
+                let mut entries = parsed.iter();
+                match (entries.next(), entries.next()) {
+                    (Some(entry), None) => Value::Value(Box::new(self.value(entry)?)),
+                    _ => {
+                        return Err(
+                            format!("variant signature `{signature}` isn't a single type").into(),
+                        )
+                    }
+                }
+            }
+            SignatureEntry::Array(element) => {
+                let len: usize = self.number("array length")?;
+                if let Some((key, value)) = signature.as_dict() {
+                    let mut dict = Dict::new(Signature::from(key), Signature::from(value));
+                    for _ in 0..len {
+                        dict.append(self.value(key)?, self.value(value)?)?;
+                    }
+
+                    Value::Dict(dict)
+                } else {
+                    let mut array = Array::new(Signature::from(&**element));
+                    for _ in 0..len {
+                        array.append(self.value(element)?)?;
+                    }
+
+                    Value::Array(array)
+                }
+            }
+            SignatureEntry::Struct(fields) => {
+                let mut builder = StructureBuilder::new();
+                for field in fields {
+                    builder = builder.append_field(self.value(field)?);
+                }
+
+                Value::Structure(builder.build())
+            }
+            #[cfg(unix)]
+            SignatureEntry::Fd => return Err("file descriptors are not supported".into()),
+            _ => return Err(format!("unsupported signature `{signature}`").into()),
+        };
+
+        Ok(value)
+    }
+
+    fn signature(&mut self) -> Result<Signature<'static>> {
+        let signature = self.next("signature")?;
+
+        Signature::try_from(signature.to_string())
+            .map_err(|e| format!("invalid signature `{signature}`: {e}").into())
+    }
+}
+
+fn format_value(tokens: &mut Vec<String>, value: &Value<'_>, signature: &SignatureEntry) {
+    if let SignatureEntry::Variant = signature {
+        // Variants are unwrapped when deserialized, but not when built.
+        let value = match value {
+            Value::Value(v) => &**v,
+            v => v,
+        };
+        let signature = value.value_signature();
+        tokens.push(signature.to_string());
+        for signature in &ParsedSignature::new(&signature) {
+            format_value(tokens, value, signature);
+        }
+
+        return;
+    }
+
+    match (value, signature) {
+        (Value::U8(v), _) => tokens.push(v.to_string()),
+        (Value::Bool(v), _) => tokens.push(v.to_string()),
+        (Value::I16(v), _) => tokens.push(v.to_string()),
+        (Value::U16(v), _) => tokens.push(v.to_string()),
+        (Value::I32(v), _) => tokens.push(v.to_string()),
+        (Value::U32(v), _) => tokens.push(v.to_string()),
+        (Value::I64(v), _) => tokens.push(v.to_string()),
+        (Value::U64(v), _) => tokens.push(v.to_string()),
+        (Value::F64(v), _) => tokens.push(v.to_string()),
+        (Value::Str(v), _) => tokens.push(format!("{:?}", v.as_str())),
+        (Value::Signature(v), _) => tokens.push(format!("{:?}", v.as_str())),
+        (Value::ObjectPath(v), _) => tokens.push(format!("{:?}", v.as_str())),
+        (Value::Value(v), _) => format_value(tokens, v, &SignatureEntry::Variant),
+        (Value::Array(array), SignatureEntry::Array(element)) => {
+            tokens.push(array.len().to_string());
+            for v in array.inner() {
+                format_value(tokens, v, element);
+            }
+        }
+        (Value::Dict(dict), signature) => match signature.as_dict() {
+            Some((key_signature, value_signature)) => {
+                tokens.push(dict.iter().count().to_string());
+                for (k, v) in dict.iter() {
+                    format_value(tokens, k, key_signature);
+                    format_value(tokens, v, value_signature);
+                }
+            }
+            None => tokens.push(value.to_string()),
+        },
+        (Value::Structure(structure), SignatureEntry::Struct(fields)) => {
+            for (v, signature) in structure.fields().iter().zip(fields) {
+                format_value(tokens, v, signature);
+            }
+        }
+        // File descriptors and types that are not part of the D-Bus format.
+        #[allow(unreachable_patterns)]
+        (value, _) => tokens.push(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use zvariant::{ObjectPath, Signature, Value};
+
+    use super::{format, parse};
+
+    fn tokens(s: &str) -> Vec<String> {
+        s.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn basic() {
+        let values = parse("sybuxdo", &tokens("hello 255 yes 42 -7 1.5 /org/zbus")).unwrap();
+        assert_eq!(
+            values,
+            [
+                Value::from("hello"),
+                Value::U8(255),
+                Value::Bool(true),
+                Value::U32(42),
+                Value::I64(-7),
+                Value::F64(1.5),
+                Value::from(ObjectPath::from_static_str_unchecked("/org/zbus")),
+            ],
+        );
+        assert_eq!(
+            format(&Signature::from_static_str_unchecked("sybuxdo"), &values),
+            r#""hello" 255 true 42 -7 1.5 "/org/zbus""#,
+        );
+    }
+
+    #[test]
+    fn containers() {
+        let args = "2 a b 2 Name s zbus Answer u 42 3 -1 as 0";
+        let values = parse("asa{sv}(xi)v", &tokens(args)).unwrap();
+
+        assert_eq!(values[0], Value::from(vec!["a", "b"]));
+        let mut dict = HashMap::new();
+        dict.insert("Name", Value::from("zbus"));
+        dict.insert("Answer", Value::from(42u32));
+        assert_eq!(values[1], Value::from(dict));
+        assert_eq!(
+            values[3],
+            Value::Value(Box::new(Value::from(Vec::<&str>::new())))
+        );
+
+        assert_eq!(
+            format(
+                &Signature::from_static_str_unchecked("asa{sv}(xi)v"),
+                &values
+            ),
+            r#"2 "a" "b" 2 "Answer" u 42 "Name" s "zbus" 3 -1 as 0"#,
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse("s", &[]).is_err());
+        assert!(parse("u", &tokens("-1")).is_err());
+        assert!(parse("b", &tokens("maybe")).is_err());
+        assert!(parse("s", &tokens("a b")).is_err());
+        assert!(parse("(s", &tokens("a")).is_err());
+        assert!(parse("v", &tokens("ss a b")).is_err());
+        assert!(parse("v", &tokens("")).is_err());
+        assert!(parse("()", &tokens("")).is_err());
+        assert!(parse("o", &tokens("not/a/path")).is_err());
+    }
+}
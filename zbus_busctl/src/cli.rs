@@ -0,0 +1,108 @@
+use clap::{Args as ClapArgs, Parser};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct Args {
+    #[clap(flatten)]
+    pub bus: Bus,
+
+    #[clap(subcommand)]
+    pub command: Command,
+}
+
+/// The bus to connect to. Defaults to the session bus.
+#[derive(ClapArgs, Debug, Clone)]
+#[group(multiple = false)]
+pub struct Bus {
+    /// Connect to the system bus.
+    #[clap(long, global = true)]
+    pub system: bool,
+
+    /// Connect to the session bus.
+    #[clap(long, global = true)]
+    pub session: bool,
+
+    /// Connect to the bus at the specified D-Bus address.
+    #[clap(long, global = true)]
+    pub address: Option<String>,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub enum Command {
+    /// List the names on the bus.
+    #[clap()]
+    List {
+        /// Also list the activatable names that are not currently owned.
+        #[clap(long)]
+        activatable: bool,
+    },
+
+    /// Show the object tree of a service.
+    #[clap()]
+    Tree {
+        service: String,
+        #[clap(default_value = "/")]
+        object_path: String,
+    },
+
+    /// Show the interfaces, methods, properties and signals of an object.
+    #[clap()]
+    Introspect {
+        service: String,
+        object_path: String,
+        /// Only show this interface.
+        interface: Option<String>,
+    },
+
+    /// Call a method and show its reply.
+    ///
+    /// The arguments are given as a signature, followed by the values. Arrays are given as the
+    /// number of elements followed by the elements, variants as the signature of the contained
+    /// value followed by the value, and dicts as the number of entries followed by the keys and
+    /// values. E.g `a{sv} 2 Name s "zbus" Answer u 42`.
+    #[clap()]
+    Call {
+        service: String,
+        object_path: String,
+        interface: String,
+        method: String,
+        signature: Option<String>,
+        #[clap(allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Get the value of properties.
+    #[clap()]
+    GetProperty {
+        service: String,
+        object_path: String,
+        interface: String,
+        #[clap(required = true)]
+        properties: Vec<String>,
+    },
+
+    /// Set the value of a property.
+    ///
+    /// The value is given as a signature, followed by the value, just like for `call`.
+    #[clap()]
+    SetProperty {
+        service: String,
+        object_path: String,
+        interface: String,
+        property: String,
+        signature: String,
+        #[clap(required = true, allow_hyphen_values = true)]
+        value: Vec<String>,
+    },
+
+    /// Show the messages going through the bus.
+    ///
+    /// Match rules can be given to only show specific messages, e.g `type='signal'`.
+    #[clap()]
+    Monitor {
+        /// Show each message on a single line.
+        #[clap(long)]
+        compact: bool,
+        match_rules: Vec<String>,
+    },
+}
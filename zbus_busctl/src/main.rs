@@ -0,0 +1,412 @@
+#![deny(rust_2018_idioms)]
+
+use std::{error::Error, process::ExitCode};
+
+use clap::Parser;
+use futures_util::TryStreamExt;
+use zbus::{
+    blocking::{
+        self,
+        fdo::{DBusProxy, IntrospectableProxy, PropertiesProxy},
+        Connection,
+    },
+    connection,
+    message::Message,
+    names::InterfaceName,
+    MatchRule, Monitor,
+};
+use zbus_xml::{Annotation, Arg, ArgDirection, Node};
+use zvariant::{Optional, Structure, StructureBuilder, Value};
+
+mod args;
+mod cli;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn main() -> ExitCode {
+    let args = cli::Args::parse();
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: cli::Args) -> Result<()> {
+    let bus = args.bus;
+
+    match args.command {
+        cli::Command::List { activatable } => list(&connect(&bus)?, activatable),
+        cli::Command::Tree {
+            service,
+            object_path,
+        } => tree(&connect(&bus)?, &service, &object_path),
+        cli::Command::Introspect {
+            service,
+            object_path,
+            interface,
+        } => introspect(
+            &connect(&bus)?,
+            &service,
+            &object_path,
+            interface.as_deref(),
+        ),
+        cli::Command::Call {
+            service,
+            object_path,
+            interface,
+            method,
+            signature,
+            args,
+        } => {
+            let values = match signature {
+                Some(signature) => args::parse(&signature, &args)?,
+                None => vec![],
+            };
+            let conn = connect(&bus)?;
+            let reply = if values.is_empty() {
+                conn.call_method(
+                    Some(&*service),
+                    &*object_path,
+                    Some(&*interface),
+                    &*method,
+                    &(),
+                )?
+            } else {
+                let body = values
+                    .into_iter()
+                    .fold(StructureBuilder::new(), |builder, value| {
+                        builder.append_field(value)
+                    })
+                    .build();
+                conn.call_method(
+                    Some(&*service),
+                    &*object_path,
+                    Some(&*interface),
+                    &*method,
+                    &body,
+                )?
+            };
+
+            print_body(&reply)
+        }
+        cli::Command::GetProperty {
+            service,
+            object_path,
+            interface,
+            properties,
+        } => {
+            let conn = connect(&bus)?;
+            let proxy = properties_proxy(&conn, &service, &object_path)?;
+            let interface = InterfaceName::try_from(interface)?;
+            for property in properties {
+                let value = proxy.get(interface.clone(), &property)?;
+                println!("{}", format_value(&value));
+            }
+
+            Ok(())
+        }
+        cli::Command::SetProperty {
+            service,
+            object_path,
+            interface,
+            property,
+            signature,
+            value,
+        } => {
+            let mut values = args::parse(&signature, &value)?;
+            if values.len() != 1 {
+                return Err(format!("`{signature}` is not a single complete type").into());
+            }
+            let conn = connect(&bus)?;
+            let proxy = properties_proxy(&conn, &service, &object_path)?;
+            let interface = InterfaceName::try_from(interface)?;
+
+            Ok(proxy.set(interface, &property, &values.remove(0))?)
+        }
+        cli::Command::Monitor {
+            compact,
+            match_rules,
+        } => monitor(&bus, compact, &match_rules),
+    }
+}
+
+fn connect(bus: &cli::Bus) -> Result<Connection> {
+    let conn = match &bus.address {
+        Some(address) => blocking::connection::Builder::address(&**address)?.build()?,
+        None if bus.system => Connection::system()?,
+        None => Connection::session()?,
+    };
+
+    Ok(conn)
+}
+
+fn list(conn: &Connection, activatable: bool) -> Result<()> {
+    let dbus = DBusProxy::new(conn)?;
+    let names = dbus.list_names()?;
+
+    let mut rows = vec![];
+    for name in &names {
+        let owner = dbus
+            .get_name_owner(name.inner().clone())
+            .map(|owner| owner.to_string())
+            .unwrap_or_else(|_| "-".to_string());
+        let pid = dbus
+            .get_connection_unix_process_id(name.inner().clone())
+            .map(|pid| pid.to_string())
+            .unwrap_or_else(|_| "-".to_string());
+        rows.push(vec![name.to_string(), owner, pid]);
+    }
+    if activatable {
+        for name in dbus.list_activatable_names()? {
+            if !names.contains(&name) {
+                rows.push(vec![
+                    name.to_string(),
+                    "(activatable)".to_string(),
+                    "-".to_string(),
+                ]);
+            }
+        }
+    }
+    rows.sort();
+    print_table(&["NAME", "OWNER", "PID"], &rows);
+
+    Ok(())
+}
+
+fn tree(conn: &Connection, service: &str, object_path: &str) -> Result<()> {
+    println!("{object_path}");
+
+    print_children(conn, service, object_path, "")
+}
+
+fn print_children(conn: &Connection, service: &str, object_path: &str, prefix: &str) -> Result<()> {
+    let node = introspect_node(conn, service, object_path)?;
+    let children: Vec<_> = node.nodes().iter().filter_map(|n| n.name()).collect();
+    for (i, name) in children.iter().enumerate() {
+        let path = match object_path {
+            "/" => format!("/{name}"),
+            _ => format!("{object_path}/{name}"),
+        };
+        let (branch, indent) = if i + 1 == children.len() {
+            ("└─ ", "   ")
+        } else {
+            ("├─ ", "│  ")
+        };
+        println!("{prefix}{branch}{path}");
+
+        if let Err(e) = print_children(conn, service, &path, &format!("{prefix}{indent}")) {
+            eprintln!("Failed to introspect `{path}`: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+fn introspect(
+    conn: &Connection,
+    service: &str,
+    object_path: &str,
+    interface: Option<&str>,
+) -> Result<()> {
+    let node = introspect_node(conn, service, object_path)?;
+    let proxy = properties_proxy(conn, service, object_path)?;
+
+    let mut rows = vec![];
+    for iface in node.interfaces() {
+        let name = iface.name();
+        if interface.is_some_and(|i| i != name.as_str()) {
+            continue;
+        }
+        rows.push(vec![
+            name.to_string(),
+            "interface".to_string(),
+            "-".to_string(),
+            "-".to_string(),
+            flags(iface.annotations(), &[]),
+        ]);
+
+        for method in iface.methods() {
+            let (outputs, inputs): (Vec<_>, Vec<_>) = method
+                .args()
+                .iter()
+                .partition(|arg| arg.direction() == Some(ArgDirection::Out));
+            rows.push(vec![
+                format!(".{}", method.name()),
+                "method".to_string(),
+                signature(inputs),
+                signature(outputs),
+                flags(method.annotations(), &[]),
+            ]);
+        }
+
+        // Not all the properties are necessarily readable, so this is best effort.
+        let values = proxy
+            .get_all(Optional::from(Some(name.clone())))
+            .unwrap_or_default();
+        for property in iface.properties() {
+            let value = values
+                .get(property.name().as_str())
+                .map(|v| args::format(&v.value_signature(), std::slice::from_ref(v)))
+                .unwrap_or_else(|| "-".to_string());
+            let writable = if property.access().write() {
+                &["writable"][..]
+            } else {
+                &[]
+            };
+            rows.push(vec![
+                format!(".{}", property.name()),
+                "property".to_string(),
+                property.ty().to_string(),
+                value,
+                flags(property.annotations(), writable),
+            ]);
+        }
+
+        for signal in iface.signals() {
+            rows.push(vec![
+                format!(".{}", signal.name()),
+                "signal".to_string(),
+                signature(signal.args()),
+                "-".to_string(),
+                flags(signal.annotations(), &[]),
+            ]);
+        }
+    }
+    print_table(
+        &["NAME", "TYPE", "SIGNATURE", "RESULT/VALUE", "FLAGS"],
+        &rows,
+    );
+
+    Ok(())
+}
+
+fn monitor(bus: &cli::Bus, compact: bool, match_rules: &[String]) -> Result<()> {
+    let rules = match_rules
+        .iter()
+        .map(|rule| MatchRule::try_from(rule.as_str()))
+        .collect::<zbus::Result<Vec<_>>>()?;
+    let builder = match &bus.address {
+        Some(address) => connection::Builder::address(&**address)?,
+        None if bus.system => connection::Builder::system()?,
+        None => connection::Builder::session()?,
+    };
+
+    zbus::block_on(async {
+        let mut monitor = Monitor::new(builder, &rules).await?;
+        if monitor.is_eavesdropping() {
+            eprintln!("The bus doesn't support monitoring, eavesdropping instead.");
+        }
+
+        while let Some(msg) = monitor.try_next().await? {
+            let msg = msg.message().format().compact(compact);
+            if compact {
+                println!("{msg}");
+            } else {
+                println!("{msg}\n");
+            }
+        }
+
+        Ok(())
+    })
+}
+
+fn introspect_node(conn: &Connection, service: &str, object_path: &str) -> Result<Node<'static>> {
+    let xml = IntrospectableProxy::builder(conn)
+        .destination(service)?
+        .path(object_path)?
+        .build()?
+        .introspect()?;
+
+    Ok(Node::from_reader(xml.as_bytes())?)
+}
+
+fn properties_proxy<'p>(
+    conn: &Connection,
+    service: &'p str,
+    object_path: &'p str,
+) -> Result<PropertiesProxy<'p>> {
+    Ok(PropertiesProxy::builder(conn)
+        .destination(service)?
+        .path(object_path)?
+        .build()?)
+}
+
+fn print_body(msg: &Message) -> Result<()> {
+    let body = msg.body();
+    if let Some(signature) = body.signature().filter(|s| !s.is_empty()) {
+        let values = body.deserialize::<Structure<'_>>()?.into_fields();
+        println!("{signature} {}", args::format(&signature, &values));
+    }
+
+    Ok(())
+}
+
+/// Format `value`, prefixed by its signature.
+fn format_value(value: &Value<'_>) -> String {
+    let signature = value.value_signature();
+
+    format!(
+        "{signature} {}",
+        args::format(&signature, std::slice::from_ref(value))
+    )
+}
+
+/// The signature of `args`, or `-` if there's none.
+fn signature<'a, I>(args: I) -> String
+where
+    I: IntoIterator<Item = &'a Arg<'a>>,
+{
+    let signature: String = args.into_iter().map(|arg| arg.ty().to_string()).collect();
+    if signature.is_empty() {
+        "-".to_string()
+    } else {
+        signature
+    }
+}
+
+/// The flags of a member, from its annotations and `extra` flags.
+fn flags(annotations: &[Annotation], extra: &[&str]) -> String {
+    let mut flags: Vec<_> = annotations
+        .iter()
+        .filter_map(|a| match (a.name(), a.value()) {
+            ("org.freedesktop.DBus.Deprecated", "true") => Some("deprecated"),
+            ("org.freedesktop.DBus.Method.NoReply", "true") => Some("no-reply"),
+            ("org.freedesktop.DBus.Property.EmitsChangedSignal", "const") => Some("const"),
+            _ => None,
+        })
+        .collect();
+    flags.extend_from_slice(extra);
+
+    if flags.is_empty() {
+        "-".to_string()
+    } else {
+        flags.join(" ")
+    }
+}
+
+fn print_table(header: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<_> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let print_row = |cells: &mut dyn Iterator<Item = &str>| {
+        let line = cells
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!("{}", line.trim_end());
+    };
+    print_row(&mut header.iter().copied());
+    for row in rows {
+        print_row(&mut row.iter().map(String::as_str));
+    }
+}
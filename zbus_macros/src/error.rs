@@ -1,6 +1,7 @@
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
-use syn::{spanned::Spanned, Data, DeriveInput, Error, Fields, Ident, Variant};
+use syn::{spanned::Spanned, Data, DeriveInput, Error, Fields, Ident, Type, Variant};
+use zbus_names::ErrorName;
 use zvariant_utils::def_attrs;
 
 def_attrs! {
@@ -8,7 +9,8 @@ def_attrs! {
 
     pub StructAttributes("struct") {
         prefix str,
-        impl_display bool
+        impl_display bool,
+        impl_error_conversions bool
     };
 
     pub VariantAttributes("enum variant") {
@@ -23,9 +25,12 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
     let StructAttributes {
         prefix,
         impl_display,
+        impl_error_conversions,
     } = StructAttributes::parse(&input.attrs)?;
     let prefix = prefix.unwrap_or_else(|| "org.freedesktop.DBus".to_string());
     let generate_display = impl_display.unwrap_or(true);
+    let generate_error_conversions = impl_error_conversions.unwrap_or(false);
+    let span = input.span();
 
     let (_vis, name, _generics, data) = match input.data {
         Data::Enum(data) => (input.vis, input.ident, input.generics, data),
@@ -39,6 +44,8 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
     let mut error_converts = quote! {};
 
     let mut zbus_error_variant = None;
+    let mut uses_desc = false;
+    let mut uses_msg = false;
 
     for variant in data.variants {
         let VariantAttributes { name, error } = VariantAttributes::parse(&variant.attrs)?;
@@ -52,6 +59,10 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
             String::from("org.freedesktop.zbus.Error")
         };
 
+        if !error {
+            validate_name::<ErrorName<'_>>(&fqn, ident.span())?;
+        }
+
         let error_name = quote! {
             #zbus::names::ErrorName::from_static_str_unchecked(#fqn)
         };
@@ -76,32 +87,41 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
             zbus_error_variant = Some(quote! { #ident });
         }
 
-        // FIXME: this will error if the first field is not a string as per the dbus spec, but we
-        // may support other cases?
+        // The description is taken from the first field, if it's a `String`.
         let e = match &variant.fields {
             Fields::Unit => quote! {
                 Self::#ident => None,
             },
-            Fields::Unnamed(_) => {
+            Fields::Unnamed(f) => {
                 if error {
                     quote! {
                         Self::#ident(#zbus::Error::MethodError(_, desc, _)) => desc.as_deref(),
                         Self::#ident(_) => None,
                     }
-                } else {
+                } else if f.unnamed.first().map(|f| is_string_type(&f.ty)) == Some(true) {
                     quote! {
                         Self::#ident(desc, ..) => Some(&desc),
                     }
+                } else {
+                    quote! {
+                        Self::#ident(..) => None,
+                    }
                 }
             }
             Fields::Named(n) => {
-                let f = &n
+                let f = n
                     .named
                     .first()
-                    .ok_or_else(|| Error::new(n.span(), "expected at least one field"))?
-                    .ident;
-                quote! {
-                    Self::#ident { #f, } => Some(#f),
+                    .ok_or_else(|| Error::new(n.span(), "expected at least one field"))?;
+                let f_ident = &f.ident;
+                if is_string_type(&f.ty) {
+                    quote! {
+                        Self::#ident { #f_ident, .. } => Some(#f_ident),
+                    }
+                } else {
+                    quote! {
+                        Self::#ident { .. } => None,
+                    }
                 }
             }
         };
@@ -109,25 +129,59 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
 
         // The conversion for #[zbus(error)] variant is handled separately/explicitly.
         if !error {
-            // FIXME: deserialize msg to error field instead, to support variable args
             let e = match &variant.fields {
                 Fields::Unit => quote! {
-                    #fqn => Self::#ident,
-                },
-                Fields::Unnamed(_) => quote! {
-                    #fqn => { Self::#ident(::std::clone::Clone::clone(desc).unwrap_or_default()) },
+                    #fqn => return Self::#ident,
                 },
-                Fields::Named(n) => {
-                    let f = &n
-                        .named
-                        .first()
-                        .ok_or_else(|| Error::new(n.span(), "expected at least one field"))?
-                        .ident;
+                // A lone `String` field is the description, so we don't need to decode the body.
+                Fields::Unnamed(f) if f.unnamed.len() == 1 && is_string_type(&f.unnamed[0].ty) => {
+                    uses_desc = true;
+
+                    quote! {
+                        #fqn => return Self::#ident(::std::clone::Clone::clone(desc).unwrap_or_default()),
+                    }
+                }
+                Fields::Named(n) if n.named.len() == 1 && is_string_type(&n.named[0].ty) => {
+                    uses_desc = true;
+                    let f = &n.named[0].ident;
+
                     quote! {
                         #fqn => {
                             let desc = ::std::clone::Clone::clone(desc).unwrap_or_default();
 
-                            Self::#ident { #f: desc }
+                            return Self::#ident { #f: desc };
+                        }
+                    }
+                }
+                Fields::Unnamed(f) => {
+                    uses_msg = true;
+                    let fields = (0..f.unnamed.len())
+                        .map(|n| Ident::new(&format!("f{n}"), ident.span()))
+                        .collect::<Vec<_>>();
+                    let types = f.unnamed.iter().map(|f| &f.ty);
+
+                    quote! {
+                        #fqn => {
+                            if let ::std::result::Result::Ok((#(#fields),*)) =
+                                msg.body().deserialize::<(#(#types),*)>()
+                            {
+                                return Self::#ident(#(#fields),*);
+                            }
+                        }
+                    }
+                }
+                Fields::Named(n) => {
+                    uses_msg = true;
+                    let fields = n.named.iter().map(|f| &f.ident).collect::<Vec<_>>();
+                    let types = n.named.iter().map(|f| &f.ty);
+
+                    quote! {
+                        #fqn => {
+                            if let ::std::result::Result::Ok((#(#fields),*)) =
+                                msg.body().deserialize::<(#(#types),*)>()
+                            {
+                                return Self::#ident { #(#fields),* };
+                            }
                         }
                     }
                 }
@@ -139,21 +193,59 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
         replies.extend(r);
     }
 
+    if generate_error_conversions && zbus_error_variant.is_none() {
+        return Err(Error::new(
+            span,
+            "`impl_error_conversions` requires a `#[zbus(error)]` variant",
+        ));
+    }
+
     let from_zbus_error_impl = zbus_error_variant
         .map(|ident| {
+            let desc = if uses_desc {
+                quote! { desc }
+            } else {
+                quote! { _ }
+            };
+            let msg = if uses_msg {
+                quote! { msg }
+            } else {
+                quote! { _ }
+            };
+
+            let error_conversions = if generate_error_conversions {
+                quote! {
+                    impl ::std::convert::From<#zbus::zvariant::Error> for #name {
+                        fn from(value: #zbus::zvariant::Error) -> #name {
+                            Self::from(#zbus::Error::from(value))
+                        }
+                    }
+
+                    impl ::std::convert::From<::std::io::Error> for #name {
+                        fn from(value: ::std::io::Error) -> #name {
+                            Self::from(#zbus::Error::from(value))
+                        }
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
             quote! {
                 impl ::std::convert::From<#zbus::Error> for #name {
                     fn from(value: #zbus::Error) -> #name {
-                        if let #zbus::Error::MethodError(name, desc, _) = &value {
+                        if let #zbus::Error::MethodError(name, #desc, #msg) = &value {
                             match name.as_str() {
                                 #error_converts
-                                _ => Self::#ident(value),
+                                _ => (),
                             }
-                        } else {
-                            Self::#ident(value)
                         }
+
+                        Self::#ident(value)
                     }
                 }
+
+                #error_conversions
             }
         })
         .unwrap_or_default();
@@ -251,3 +343,15 @@ fn gen_reply_for_variant(
         }
     }
 }
+
+fn is_string_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) => p
+            .path
+            .segments
+            .last()
+            .map(|s| s.ident == "String" && s.arguments.is_empty())
+            .unwrap_or(false),
+        _ => false,
+    }
+}
@@ -1,4 +1,4 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
 use std::collections::BTreeMap;
 use syn::{
@@ -7,6 +7,7 @@ use syn::{
     Lit::Str, Meta, Meta::NameValue, MetaList, MetaNameValue, NestedMeta, PatType, PathArguments,
     ReturnType, Signature, Token, Type, TypePath,
 };
+use zbus_names::{InterfaceName, MemberName, PropertyName};
 use zvariant_utils::{case, def_attrs, macros::AttrParse, old_new};
 
 use crate::utils::*;
@@ -233,6 +234,13 @@ impl MethodInfo {
             quote!(c.reply(m, &reply).await)
         };
 
+        if let Some(name) = &attrs_name {
+            if is_property {
+                validate_name::<PropertyName<'_>>(name, ident.span())?;
+            } else {
+                validate_name::<MemberName<'_>>(name, ident.span())?;
+            }
+        }
         let member_name = attrs_name.clone().unwrap_or_else(|| {
             let mut name = ident.to_string();
             if is_property && has_inputs {
@@ -317,6 +325,7 @@ pub fn expand<T: AttrParse + Into<TraitAttrs>, M: AttrParse + Into<MethodAttrs>>
                     "`name` and `interface` attributes should not be specified at the same time",
                 )),
             };
+        validate_name::<InterfaceName<'_>>(&name, Span::call_site())?;
 
         (name, !spawn.unwrap_or(true))
     };
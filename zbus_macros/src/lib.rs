@@ -46,6 +46,9 @@ mod utils;
 ///
 /// * `blocking_name` - Specify the exact name of the blocking proxy type.
 ///
+/// * `gen_mock` - Whether or not to generate a `TraitNameApi` trait and an in-memory mock
+///   implementing it (default: `false`). Read the [Mocking](#mocking) section below for details.
+///
 /// * `assume_defaults` - whether to auto-generate values for `default_path` and `default_service`
 ///   if none are specified (default: `false`). `proxy` generates a warning if neither this
 ///   attribute nor one of the default values are specified. Please make sure to explicitly set
@@ -105,6 +108,56 @@ mod utils;
 /// access to the signal arguments. It also implements `Deref<Target = Message>` to allow easy
 /// access to the underlying [`zbus::message::Message`].
 ///
+/// If the interface has any signals, an enum named `<TraitName>Signal` is also generated, with a
+/// variant (named after the signal and wrapping the `<SignalName>` type) for each signal. The
+/// `receive_all_signals` method creates a stream (iterator for the blocking proxy), named
+/// `<TraitName>SignalStream` (`<TraitName>SignalIterator` for the blocking proxy), that yields all
+/// the signals in the order they were received. Signals not declared in the trait are skipped.
+/// Since its receiver would clash with this method, a signal method can't be named `all_signals`.
+///
+/// # Mocking
+///
+/// With `gen_mock = true`, an object-safe asynchronous trait named `<TraitName>Api` is generated,
+/// with the methods, property getters and setters, and `receive_<method_name>` signal streams
+/// (as well as `receive_all_signals`) of the interface. It is implemented by the asynchronous proxy and by
+/// an in-memory mock, named `Mock<ProxyName>`. Code written against the trait can then be unit
+/// tested without a bus:
+///
+/// * The responses of the mock are programmed through its `on_<method_name>` methods. Calls
+///   without a programmed response fail with `org.freedesktop.DBus.Error.UnknownMethod` (or
+///   `UnknownProperty` for properties).
+///
+/// * Signals are emitted to the streams through its `emit_<method_name>` methods.
+///
+/// * All calls are recorded as the messages a proxy would have sent, and returned by its `calls`
+///   method. See [`zbus::proxy::Mock`] for details.
+///
+/// Generic methods and methods with the `object` attribute are not supported by `gen_mock`.
+///
+/// ```
+/// # use zbus::{proxy, Result};
+/// #[proxy(interface = "org.test.Counter", default_path = "/org/test/Counter", gen_mock = true)]
+/// trait Counter {
+///     fn add(&self, n: u32) -> Result<u32>;
+/// }
+///
+/// async fn add_twice(counter: &dyn CounterApi, n: u32) -> Result<u32> {
+///     counter.add(n).await?;
+///     counter.add(n).await
+/// }
+///
+/// # async_io::block_on(async {
+/// let mut mock = MockCounterProxy::new();
+/// mock.on_add(|n| Ok(n * 2));
+/// assert_eq!(add_twice(&mock, 2).await?, 4);
+///
+/// let calls = mock.calls();
+/// assert_eq!(calls.len(), 2);
+/// assert_eq!(calls[0].body().deserialize::<u32>()?, 2);
+/// # Ok::<_, zbus::Error>(())
+/// # }).unwrap();
+/// ```
+///
 /// # Example
 ///
 /// ```no_run
@@ -161,6 +214,13 @@ mod utils;
 /// let args = signal.args()?;
 /// println!("arg1: {}, arg2: {}", args.arg1(), args.arg2());
 ///
+/// // Handle all the signals of the interface through a single iterator.
+/// for signal in proxy.receive_all_signals()? {
+///     match signal {
+///         SomeIfaceSignal::SomeSignal(signal) => println!("{}", signal.args()?.arg1()),
+///     }
+/// }
+///
 /// // Now the same again, but asynchronous.
 /// block_on(async move {
 ///     let proxy = SomeIfaceProxy::builder(&connection.into())
@@ -189,6 +249,7 @@ mod utils;
 /// [`zbus::blocking::Proxy`]: https://docs.rs/zbus/latest/zbus/blocking/proxy/struct.Proxy.html
 /// [`zbus::SignalStream`]: https://docs.rs/zbus/latest/zbus/proxy/struct.SignalStream.html
 /// [`zbus::blocking::SignalIterator`]: https://docs.rs/zbus/latest/zbus/blocking/proxy/struct.SignalIterator.html
+/// [`zbus::proxy::Mock`]: https://docs.rs/zbus/latest/zbus/proxy/struct.Mock.html
 /// [`ObjectPath`]: https://docs.rs/zvariant/latest/zvariant/struct.ObjectPath.html
 /// [dbus_emits_changed_signal]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
 #[proc_macro_attribute]
@@ -388,16 +449,33 @@ pub fn dbus_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// If a special variant marked with the `zbus` attribute is present, `From<zbus::Error>` is
 /// also implemented for your type. This variant can only have a single unnamed field of type
 /// [`zbus::Error`]. This implementation makes it possible for you to declare proxy methods to
-/// directly return this type, rather than [`zbus::Error`].
+/// directly return this type, rather than [`zbus::Error`]. If you also add
+/// `#[zbus(impl_error_conversions = true)]` to your type, `From<zvariant::Error>` and
+/// `From<std::io::Error>` are implemented too, converting through [`zbus::Error`].
+///
+/// Each variant (except for the special `zbus` one) can have any number of (named or unnamed)
+/// fields. All fields are serialized, in order, as the body of the error message and must
+/// therefore implement [`serde::Serialize`] and [`zvariant::Type`]. If the first field is a
+/// `String`, it's used as the human-readable error description.
 ///
-/// Each variant (except for the special `zbus` one) can optionally have a (named or unnamed)
-/// `String` field (which is used as the human-readable error description).
+/// When converting a [`zbus::Error::MethodError`] back into your type, the error body is
+/// deserialized into the fields of the variant matching the error name (so the fields must also
+/// implement [`serde::Deserialize`]). If the body doesn't match the fields, the error is converted
+/// into the special `zbus` variant instead.
 ///
 /// # Example
 ///
 /// ```
+/// use serde::{Deserialize, Serialize};
+/// use zbus::zvariant::Type;
 /// use zbus_macros::DBusError;
 ///
+/// #[derive(Debug, Deserialize, Serialize, Type)]
+/// struct Quota {
+///     used: u64,
+///     limit: u64,
+/// }
+///
 /// #[derive(DBusError, Debug)]
 /// #[zbus(prefix = "org.myservice.App")]
 /// enum Error {
@@ -405,13 +483,20 @@ pub fn dbus_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///     ZBus(zbus::Error),
 ///     FileNotFound(String),
 ///     OutOfMemory,
+///     // Sent with a `stt` body.
+///     OutOfRange(String, u32, u32),
+///     // Sent with a `tt` body.
+///     QuotaExceeded(Quota),
+///     Busy { retry_after: u64 },
 /// }
 /// ```
 ///
 /// [`zbus::DBusError`]: https://docs.rs/zbus/latest/zbus/trait.DBusError.html
 /// [`zbus::Error`]: https://docs.rs/zbus/latest/zbus/enum.Error.html
+/// [`zbus::Error::MethodError`]: https://docs.rs/zbus/latest/zbus/enum.Error.html#variant.MethodError
 /// [`zvariant::Type`]: https://docs.rs/zvariant/latest/zvariant/trait.Type.html
 /// [`serde::Serialize`]: https://docs.rs/serde/1.0.132/serde/trait.Serialize.html
+/// [`serde::Deserialize`]: https://docs.rs/serde/1.0.132/serde/trait.Deserialize.html
 #[proc_macro_derive(DBusError, attributes(zbus))]
 pub fn derive_dbus_error(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
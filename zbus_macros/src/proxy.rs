@@ -1,10 +1,11 @@
-use crate::utils::{pat_ident, typed_arg, zbus_path, PropertyEmitsChangedSignal};
+use crate::utils::{pat_ident, typed_arg, validate_name, zbus_path, PropertyEmitsChangedSignal};
 use proc_macro2::{Literal, Span, TokenStream};
 use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{
-    fold::Fold, parse_quote, parse_str, spanned::Spanned, AttributeArgs, Error, FnArg, Ident,
-    ItemTrait, Path, ReturnType, TraitItemMethod,
+    fold::Fold, parse_quote, parse_str, spanned::Spanned, Attribute, AttributeArgs, Error, FnArg,
+    Ident, ItemTrait, Path, ReturnType, TraitItemMethod,
 };
+use zbus_names::{BusName, InterfaceName, MemberName, PropertyName};
 use zvariant_utils::{case, def_attrs, macros::AttrParse, old_new};
 
 pub mod old {
@@ -54,7 +55,8 @@ def_attrs! {
         async_name str,
         blocking_name str,
         gen_async bool,
-        gen_blocking bool
+        gen_blocking bool,
+        gen_mock bool
     };
 
     pub MethodAttributes("method") {
@@ -112,6 +114,7 @@ pub fn expand<I: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
         blocking_name,
         gen_async,
         gen_blocking,
+        gen_mock,
     ) = match I::parse_nested_metas(&args)?.into() {
         ImplAttrs::Old(old) => (
             old.interface,
@@ -123,6 +126,7 @@ pub fn expand<I: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
             old.blocking_name,
             old.gen_async,
             old.gen_blocking,
+            None,
         ),
         ImplAttrs::New(new) => (
             new.interface,
@@ -134,6 +138,7 @@ pub fn expand<I: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
             new.blocking_name,
             new.gen_async,
             new.gen_blocking,
+            new.gen_mock,
         ),
     };
 
@@ -145,6 +150,16 @@ pub fn expand<I: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
             "both `interface` and `name` attributes shouldn't be specified at the same time",
         )),
     }?;
+    if let Some(name) = &iface_name {
+        validate_name::<InterfaceName<'_>>(name, Span::call_site())?;
+    }
+    if let Some(path) = &default_path {
+        zvariant_utils::object_path::validate(path)
+            .map_err(|e| syn::Error::new(Span::call_site(), format!("`{path}`: {e}")))?;
+    }
+    if let Some(service) = &default_service {
+        validate_name::<BusName<'_>>(service, Span::call_site())?;
+    }
     let gen_async = gen_async.unwrap_or(true);
     let gen_blocking = gen_blocking.unwrap_or(true);
 
@@ -161,6 +176,13 @@ pub fn expand<I: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
         gen_async || async_name.is_none(),
         "Can't set asynchronous proxy's name if you disabled it. 😸",
     );
+    let gen_mock = gen_mock.unwrap_or(false);
+    if gen_mock && !gen_async {
+        return Err(Error::new(
+            Span::call_site(),
+            "`gen_mock` requires the asynchronous proxy to be generated",
+        ));
+    }
 
     let blocking_proxy = if gen_blocking {
         let proxy_name = blocking_name.unwrap_or_else(|| {
@@ -186,26 +208,38 @@ pub fn expand<I: AttrParse + Into<ImplAttrs>, M: AttrParse + Into<MethodAttrs>>(
     } else {
         quote! {}
     };
+    let async_proxy_name = async_name.unwrap_or_else(|| format!("{}Proxy", input.ident));
     let async_proxy = if gen_async {
-        let proxy_name = async_name.unwrap_or_else(|| format!("{}Proxy", input.ident));
         create_proxy::<M>(
             &input,
             iface_name.as_deref(),
             assume_defaults,
             default_path.as_deref(),
             default_service.as_deref(),
-            &proxy_name,
+            &async_proxy_name,
             false,
             true,
         )?
     } else {
         quote! {}
     };
+    let mock = if gen_mock {
+        create_mock::<M>(
+            &input,
+            iface_name.as_deref(),
+            default_path.as_deref(),
+            &async_proxy_name,
+        )?
+    } else {
+        quote! {}
+    };
 
     Ok(quote! {
         #blocking_proxy
 
         #async_proxy
+
+        #mock
     })
 }
 
@@ -250,6 +284,7 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
     let mut stream_types = TokenStream::new();
     let mut has_properties = false;
     let mut uncached_properties: Vec<String> = vec![];
+    let mut signals = vec![];
 
     let async_opts = AsyncOpts::new(blocking);
 
@@ -274,17 +309,16 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
             let is_property = property.is_some();
             let has_inputs = m.sig.inputs.len() > 1;
 
-            let member_name = name.take().unwrap_or_else(|| {
-                case::pascal_or_camel_case(
-                    if is_property && has_inputs {
-                        assert!(method_name.starts_with("set_"));
-                        &method_name[4..]
-                    } else {
-                        &method_name
-                    },
-                    true,
-                )
-            });
+            if let Some(name) = &name {
+                if is_property {
+                    validate_name::<PropertyName<'_>>(name, m.span())?;
+                } else {
+                    validate_name::<MemberName<'_>>(name, m.span())?;
+                }
+            }
+            let member_name = name
+                .take()
+                .unwrap_or_else(|| default_member_name(&method_name, is_property, has_inputs));
 
             let m = if let Some(prop_attrs) = &property {
                 has_properties = true;
@@ -307,6 +341,13 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
                     emits_changed_signal,
                 )
             } else if is_signal {
+                if method_name == "all_signals" {
+                    return Err(Error::new(
+                        m.sig.ident.span(),
+                        "a signal method can't be named `all_signals`, since its receiver would \
+                         clash with the generated `receive_all_signals` method",
+                    ));
+                }
                 let (method, types) = gen_proxy_signal(
                     &proxy_name,
                     &iface_name,
@@ -317,6 +358,13 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
                     gen_sig_args,
                 );
                 stream_types.extend(types);
+                let cfg_attrs: Vec<_> = m
+                    .attrs
+                    .iter()
+                    .filter(|a| a.path.is_ident("cfg"))
+                    .cloned()
+                    .collect();
+                signals.push((member_name.clone(), cfg_attrs));
 
                 method
             } else {
@@ -332,6 +380,19 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
         }
     }
 
+    if !signals.is_empty() {
+        let (method, types) = gen_proxy_all_signals(
+            &proxy_name,
+            &input.ident,
+            &iface_name,
+            &signals,
+            &async_opts,
+            gen_sig_args,
+        );
+        methods.extend(method);
+        stream_types.extend(types);
+    }
+
     let AsyncOpts { usage, wait, .. } = async_opts;
     let (proxy_struct, connection, builder, proxy_trait) = if blocking {
         let connection = quote! { #zbus::blocking::Connection };
@@ -494,6 +555,9 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
         }
 
         impl<'p> #zbus::zvariant::Type for #proxy_name<'p> {
+            const SIGNATURE: &'static #zbus::zvariant::StaticSignature =
+                <#zbus::zvariant::OwnedObjectPath as #zbus::zvariant::Type>::SIGNATURE;
+
             fn signature() -> #zbus::zvariant::Signature<'static> {
                 #zbus::zvariant::OwnedObjectPath::signature()
             }
@@ -515,6 +579,330 @@ pub fn create_proxy<M: AttrParse + Into<MethodAttrs>>(
     })
 }
 
+fn create_mock<M: AttrParse + Into<MethodAttrs>>(
+    input: &ItemTrait,
+    iface_name: Option<&str>,
+    default_path: Option<&str>,
+    proxy_name: &str,
+) -> Result<TokenStream, Error> {
+    let zbus = zbus_path();
+
+    let ident = input.ident.to_string();
+    let iface_name = iface_name
+        .map(ToString::to_string)
+        .unwrap_or(format!("org.freedesktop.{ident}"));
+    let path = default_path.unwrap_or("/");
+    let trait_name = format_ident!("{ident}Api");
+    let mock_name = format_ident!("Mock{proxy_name}");
+    let proxy_name = Ident::new(proxy_name, Span::call_site());
+    let signal_enum_name = format_ident!("{ident}Signal");
+    let boxed_stream = |item: &Ident| {
+        quote! {
+            ::std::pin::Pin<::std::boxed::Box<
+                dyn #zbus::export::futures_core::stream::Stream<Item = #item> + ::std::marker::Send
+            >>
+        }
+    };
+
+    let mut trait_items = TokenStream::new();
+    let mut proxy_items = TokenStream::new();
+    let mut mock_items = TokenStream::new();
+    let mut mock_fields = TokenStream::new();
+    let mut mock_field_inits = TokenStream::new();
+    let mut mock_methods = TokenStream::new();
+    let mut has_signals = false;
+
+    for i in input.items.iter() {
+        let m = match i {
+            syn::TraitItem::Method(m) => m,
+            _ => continue,
+        };
+        let (mut name, signal, property, object) = match <M>::parse(&m.attrs)?.into() {
+            MethodAttrs::Old(old) => (old.name, old.signal, old.property.is_some(), old.object),
+            MethodAttrs::New(new) => (new.name, new.signal, new.property.is_some(), new.object),
+        };
+        if !m.sig.generics.params.is_empty() {
+            return Err(Error::new(
+                m.sig.generics.span(),
+                "`gen_mock` doesn't support generic methods",
+            ));
+        }
+        if object.is_some() {
+            return Err(Error::new(
+                m.span(),
+                "`gen_mock` doesn't support methods returning object proxies",
+            ));
+        }
+
+        let method_name = m.sig.ident.to_string();
+        let method = &m.sig.ident;
+        let has_inputs = m.sig.inputs.len() > 1;
+        let member_name = name
+            .take()
+            .unwrap_or_else(|| default_member_name(&method_name, property, has_inputs));
+        let other_attrs: Vec<_> = m
+            .attrs
+            .iter()
+            .filter(|a| !a.path.is_ident("zbus") && !a.path.is_ident("dbus_proxy"))
+            .collect();
+        let cfg_attrs: Vec<_> = m.attrs.iter().filter(|a| a.path.is_ident("cfg")).collect();
+        let inputs = &m.sig.inputs;
+        let args: Vec<_> = inputs
+            .iter()
+            .filter_map(typed_arg)
+            .filter_map(pat_ident)
+            .collect();
+        let arg_types: Vec<_> = inputs.iter().filter_map(typed_arg).map(|a| &a.ty).collect();
+
+        if signal {
+            has_signals = true;
+            let signal_name = format_ident!("{member_name}");
+            let receive = format_ident!("receive_{method_name}");
+            let emit = format_ident!("emit_{method_name}");
+            let stream = boxed_stream(&signal_name);
+            let receive_doc = format!("Create a stream that receives `{member_name}` signals.");
+            let emit_doc = format!(
+                "Emit a `{member_name}` signal to the streams created by [`{trait_name}::{receive}`]."
+            );
+
+            trait_items.extend(quote! {
+                #[doc = #receive_doc]
+                #(#cfg_attrs)*
+                async fn #receive(&self) -> #zbus::Result<#stream>;
+            });
+            proxy_items.extend(quote! {
+                #(#cfg_attrs)*
+                async fn #receive(&self) -> #zbus::Result<#stream> {
+                    let stream: #stream = ::std::boxed::Box::pin(#proxy_name::#receive(self).await?);
+
+                    ::std::result::Result::Ok(stream)
+                }
+            });
+            mock_items.extend(quote! {
+                #(#cfg_attrs)*
+                async fn #receive(&self) -> #zbus::Result<#stream> {
+                    let stream: #stream = ::std::boxed::Box::pin(
+                        #zbus::export::futures_util::StreamExt::filter_map(
+                            self.mock.receive_signals(),
+                            |msg| #zbus::export::futures_util::future::ready(#signal_name::from_message(msg)),
+                        ),
+                    );
+
+                    ::std::result::Result::Ok(stream)
+                }
+            });
+            mock_methods.extend(quote! {
+                #[doc = #emit_doc]
+                #(#cfg_attrs)*
+                pub fn #emit(&self, #(#args: #arg_types),*) -> #zbus::Result<()> {
+                    self.mock.emit_signal(
+                        #member_name,
+                        &#zbus::zvariant::DynamicTuple((#(&#args,)*)),
+                    )
+                }
+            });
+
+            continue;
+        }
+
+        let output = match &m.sig.output {
+            ReturnType::Type(_, ty) => ty,
+            ReturnType::Default => {
+                return Err(Error::new(m.sig.span(), "expected a `Result` return type"));
+            }
+        };
+        let on_method = format_ident!("on_{method_name}");
+        let (record, on_doc) = if !property {
+            (
+                quote! { record_call(#member_name, &#zbus::zvariant::DynamicTuple((#(&#args,)*))) },
+                format!("Program the response to `{member_name}` method calls."),
+            )
+        } else if has_inputs {
+            let value = &args[0];
+            (
+                quote! { record_set_property(#member_name, &#value) },
+                format!("Program the response to writes of the `{member_name}` property."),
+            )
+        } else {
+            (
+                quote! { record_get_property(#member_name) },
+                format!("Program the response to reads of the `{member_name}` property."),
+            )
+        };
+
+        trait_items.extend(quote! {
+            #(#other_attrs)*
+            async fn #method(#inputs) -> #output;
+        });
+        proxy_items.extend(quote! {
+            #(#cfg_attrs)*
+            async fn #method(#inputs) -> #output {
+                #proxy_name::#method(self, #(#args),*).await
+            }
+        });
+        mock_fields.extend(quote! {
+            #(#cfg_attrs)*
+            #method: ::std::option::Option<::std::boxed::Box<
+                dyn ::std::ops::Fn(#(#arg_types),*) -> #output + ::std::marker::Send + ::std::marker::Sync
+            >>,
+        });
+        mock_field_inits.extend(quote! {
+            #(#cfg_attrs)*
+            #method: ::std::option::Option::None,
+        });
+        mock_methods.extend(quote! {
+            #[doc = #on_doc]
+            #(#cfg_attrs)*
+            pub fn #on_method<F>(&mut self, handler: F) -> &mut Self
+            where
+                F: ::std::ops::Fn(#(#arg_types),*) -> #output
+                    + ::std::marker::Send
+                    + ::std::marker::Sync
+                    + 'static,
+            {
+                self.#method = ::std::option::Option::Some(::std::boxed::Box::new(handler));
+
+                self
+            }
+        });
+        mock_items.extend(quote! {
+            #(#cfg_attrs)*
+            async fn #method(#inputs) -> #output {
+                let call = self.mock.#record?;
+
+                match &self.#method {
+                    ::std::option::Option::Some(handler) => handler(#(#args),*),
+                    ::std::option::Option::None => ::std::result::Result::Err(
+                        ::std::convert::From::from(self.mock.no_response(&call)),
+                    ),
+                }
+            }
+        });
+    }
+
+    if has_signals {
+        let stream = boxed_stream(&signal_enum_name);
+        let receive_all_doc = format!(
+            "Create a stream that receives all the signals of this interface as \
+            [`{signal_enum_name}`] values."
+        );
+
+        trait_items.extend(quote! {
+            #[doc = #receive_all_doc]
+            async fn receive_all_signals(&self) -> #zbus::Result<#stream>;
+        });
+        proxy_items.extend(quote! {
+            async fn receive_all_signals(&self) -> #zbus::Result<#stream> {
+                let stream: #stream = ::std::boxed::Box::pin(#proxy_name::receive_all_signals(self).await?);
+
+                ::std::result::Result::Ok(stream)
+            }
+        });
+        mock_items.extend(quote! {
+            async fn receive_all_signals(&self) -> #zbus::Result<#stream> {
+                let stream: #stream = ::std::boxed::Box::pin(
+                    #zbus::export::futures_util::StreamExt::filter_map(
+                        self.mock.receive_signals(),
+                        |msg| #zbus::export::futures_util::future::ready(#signal_enum_name::from_message(msg)),
+                    ),
+                );
+
+                ::std::result::Result::Ok(stream)
+            }
+        });
+    }
+
+    let trait_doc = format!(
+        "The `{iface_name}` interface, implemented by both [`{proxy_name}`] and [`{mock_name}`].\n\
+            \n\
+            Write code against this trait (e.g. through a `&dyn {trait_name}`) to be able to test it \
+            against the mock.",
+    );
+    let mock_doc = format!(
+        "An in-memory implementation of [`{trait_name}`] for tests.\n\
+            \n\
+            Responses are programmed through the `on_*` methods and signals emitted through the \
+            `emit_*` methods. Calls without a programmed response fail with \
+            `org.freedesktop.DBus.Error.UnknownMethod` (or `UnknownProperty` for properties). All \
+            calls are recorded, see [`{mock_name}::calls`].",
+    );
+    let mock_name_str = mock_name.to_string();
+
+    Ok(quote! {
+        #[doc = #trait_doc]
+        #[#zbus::export::async_trait::async_trait]
+        pub trait #trait_name: ::std::marker::Send + ::std::marker::Sync {
+            #trait_items
+        }
+
+        #[#zbus::export::async_trait::async_trait]
+        impl<'p> #trait_name for #proxy_name<'p> {
+            #proxy_items
+        }
+
+        #[doc = #mock_doc]
+        #[allow(clippy::type_complexity)]
+        pub struct #mock_name {
+            mock: #zbus::proxy::Mock,
+            #mock_fields
+        }
+
+        impl #mock_name {
+            /// Creates a new mock without any programmed responses.
+            pub fn new() -> Self {
+                Self {
+                    mock: #zbus::proxy::Mock::new(#iface_name, #path)
+                        .expect("interface name and path validated at build time"),
+                    #mock_field_inits
+                }
+            }
+
+            /// The underlying [`zbus::proxy::Mock`].
+            pub fn mock(&self) -> &#zbus::proxy::Mock {
+                &self.mock
+            }
+
+            /// The calls made through this mock so far, oldest first.
+            pub fn calls(&self) -> ::std::vec::Vec<#zbus::message::Message> {
+                self.mock.calls()
+            }
+
+            #mock_methods
+        }
+
+        impl ::std::default::Default for #mock_name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl ::std::fmt::Debug for #mock_name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_struct(#mock_name_str)
+                    .field("mock", &self.mock)
+                    .finish_non_exhaustive()
+            }
+        }
+
+        #[#zbus::export::async_trait::async_trait]
+        impl #trait_name for #mock_name {
+            #mock_items
+        }
+    })
+}
+
+fn default_member_name(method_name: &str, is_property: bool, has_inputs: bool) -> String {
+    case::pascal_or_camel_case(
+        if is_property && has_inputs {
+            assert!(method_name.starts_with("set_"));
+            &method_name[4..]
+        } else {
+            method_name
+        },
+        true,
+    )
+}
+
 fn gen_proxy_method_call<M: AttrParse + Into<MethodAttrs>>(
     method_name: &str,
     snake_case_name: &str,
@@ -1192,3 +1580,245 @@ fn gen_proxy_signal(
 
     (receive_signal, stream_types)
 }
+
+fn gen_proxy_all_signals(
+    proxy_name: &Ident,
+    trait_name: &Ident,
+    iface_name: &str,
+    signals: &[(String, Vec<Attribute>)],
+    async_opts: &AsyncOpts,
+    gen_sig_enum: bool,
+) -> (TokenStream, TokenStream) {
+    let AsyncOpts {
+        usage,
+        wait,
+        blocking,
+    } = async_opts;
+    let zbus = zbus_path();
+    let signal_names: Vec<_> = signals.iter().map(|(name, _)| name).collect();
+    let variants: Vec<_> = signal_names
+        .iter()
+        .map(|name| format_ident!("{name}"))
+        .collect();
+    let cfg_attrs: Vec<_> = signals.iter().map(|(_, attrs)| attrs).collect();
+    let enum_name = format_ident!("{trait_name}Signal");
+
+    let (proxy_path, receive_all_signals_link, trait_name, trait_link, signal_type) = if *blocking {
+        (
+            "zbus::blocking::Proxy",
+            "https://docs.rs/zbus/latest/zbus/blocking/proxy/struct.Proxy.html#method.receive_all_signals",
+            "Iterator",
+            "https://doc.rust-lang.org/std/iter/trait.Iterator.html",
+            "blocking::proxy::SignalIterator",
+        )
+    } else {
+        (
+            "zbus::Proxy",
+            "https://docs.rs/zbus/latest/zbus/proxy/struct.Proxy.html#method.receive_all_signals",
+            "Stream",
+            "https://docs.rs/futures/0.3.15/futures/stream/trait.Stream.html",
+            "proxy::SignalStream",
+        )
+    };
+    let stream_name = format_ident!("{enum_name}{trait_name}");
+    let into_inner_doc =
+        format!("Consumes `self`, returning the underlying `zbus::{signal_type}`.");
+    let inner_doc = format!("The reference to the underlying `zbus::{signal_type}`.");
+    let signal_type = parse_str::<Path>(signal_type).expect("a valid path");
+
+    let receive_gen_doc = format!(
+        "Create a stream that receives all the signals of this interface, in the order they were \
+            received, as [`{enum_name}`] values.\n\
+            \n\
+            This a convenient wrapper around [`{proxy_path}::receive_all_signals`]({receive_all_signals_link}).",
+    );
+    let receive_all = quote! {
+        #[doc = #receive_gen_doc]
+        pub #usage fn receive_all_signals(&self) -> #zbus::Result<#stream_name<'static>>
+        {
+            self.0.receive_all_signals()#wait.map(#stream_name)
+        }
+    };
+
+    let enum_gen_doc = format!("A signal of the `{iface_name}` interface.");
+    let from_message_doc = format!("Try to construct a [`{enum_name}`] from a [`zbus::Message`].");
+    let enum_decl = if gen_sig_enum {
+        quote! {
+            #[doc = #enum_gen_doc]
+            #[derive(Debug, Clone)]
+            pub enum #enum_name {
+                #(
+                    #(#cfg_attrs)*
+                    #[doc = "A `"]
+                    #[doc = #signal_names]
+                    #[doc = "` signal."]
+                    #variants(#variants),
+                 )*
+            }
+
+            impl #enum_name {
+                #[doc = #from_message_doc]
+                pub fn from_message<M>(msg: M) -> ::std::option::Option<Self>
+                where
+                    M: ::std::convert::Into<#zbus::message::Message>,
+                {
+                    let msg = msg.into();
+                    let hdr = msg.header();
+                    let member = hdr.member();
+                    let member = member.as_ref().map(|m| m.as_str());
+
+                    match member {
+                        #(
+                            #(#cfg_attrs)*
+                            Some(#signal_names) => #variants::from_message(msg.clone()).map(Self::#variants),
+                         )*
+                        _ => None,
+                    }
+                }
+
+                #[doc = "The reference to the underlying [`zbus::Message`]."]
+                pub fn message(&self) -> &#zbus::message::Message {
+                    match self {
+                        #(
+                            #(#cfg_attrs)*
+                            Self::#variants(signal) => signal.message(),
+                         )*
+                    }
+                }
+            }
+
+            impl ::std::convert::From<#enum_name> for #zbus::message::Message {
+                fn from(signal: #enum_name) -> Self {
+                    signal.message().clone()
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    // Signals of the interface not declared in the proxy trait are skipped.
+    let stream_impl = if *blocking {
+        quote! {
+            impl ::std::iter::Iterator for #stream_name<'_> {
+                type Item = #enum_name;
+
+                fn next(&mut self) -> ::std::option::Option<Self::Item> {
+                    ::std::iter::Iterator::find_map(&mut self.0, #enum_name::from_message)
+                }
+            }
+        }
+    } else {
+        quote! {
+            impl #zbus::export::futures_core::stream::Stream for #stream_name<'_> {
+                type Item = #enum_name;
+
+                fn poll_next(
+                    self: ::std::pin::Pin<&mut Self>,
+                    cx: &mut ::std::task::Context<'_>,
+                    ) -> ::std::task::Poll<::std::option::Option<Self::Item>> {
+                    let this = self.get_mut();
+                    loop {
+                        match #zbus::export::futures_core::stream::Stream::poll_next(
+                            ::std::pin::Pin::new(&mut this.0),
+                            cx,
+                        ) {
+                            ::std::task::Poll::Ready(::std::option::Option::Some(msg)) => {
+                                if let Some(signal) = #enum_name::from_message(msg) {
+                                    return ::std::task::Poll::Ready(Some(signal));
+                                }
+                            }
+                            ::std::task::Poll::Ready(::std::option::Option::None) => {
+                                return ::std::task::Poll::Ready(None);
+                            }
+                            ::std::task::Poll::Pending => return ::std::task::Poll::Pending,
+                        }
+                    }
+                }
+            }
+
+            impl #zbus::export::ordered_stream::OrderedStream for #stream_name<'_> {
+                type Data = #enum_name;
+                type Ordering = #zbus::message::Sequence;
+
+                fn poll_next_before(
+                    self: ::std::pin::Pin<&mut Self>,
+                    cx: &mut ::std::task::Context<'_>,
+                    before: ::std::option::Option<&Self::Ordering>
+                    ) -> ::std::task::Poll<#zbus::export::ordered_stream::PollResult<Self::Ordering, Self::Data>> {
+                    use #zbus::export::ordered_stream::PollResult;
+
+                    let this = self.get_mut();
+                    loop {
+                        match #zbus::export::ordered_stream::OrderedStream::poll_next_before(
+                            ::std::pin::Pin::new(&mut this.0),
+                            cx,
+                            before,
+                        ) {
+                            ::std::task::Poll::Ready(PollResult::Item { data, ordering }) => {
+                                if let Some(data) = #enum_name::from_message(data) {
+                                    return ::std::task::Poll::Ready(PollResult::Item { data, ordering });
+                                }
+                            }
+                            ::std::task::Poll::Ready(PollResult::NoneBefore) => {
+                                return ::std::task::Poll::Ready(PollResult::NoneBefore);
+                            }
+                            ::std::task::Poll::Ready(PollResult::Terminated) => {
+                                return ::std::task::Poll::Ready(PollResult::Terminated);
+                            }
+                            ::std::task::Poll::Pending => return ::std::task::Poll::Pending,
+                        }
+                    }
+                }
+            }
+
+            impl #zbus::export::futures_core::stream::FusedStream for #stream_name<'_> {
+                fn is_terminated(&self) -> bool {
+                    self.0.is_terminated()
+                }
+            }
+
+            #[#zbus::export::async_trait::async_trait]
+            impl #zbus::AsyncDrop for #stream_name<'_> {
+                async fn async_drop(self) {
+                    self.0.async_drop().await
+                }
+            }
+        }
+    };
+
+    let stream_gen_doc = format!(
+        "A [`{trait_name}`] implementation that yields [`{enum_name}`] values.\n\
+            \n\
+            Use [`{proxy_name}::receive_all_signals`] to create an instance of this type.\n\
+            \n\
+            [`{trait_name}`]: {trait_link}",
+    );
+    let stream_types = quote! {
+        #[doc = #stream_gen_doc]
+        #[derive(Debug)]
+        pub struct #stream_name<'a>(#zbus::#signal_type<'a>);
+
+        #zbus::export::static_assertions::assert_impl_all!(
+            #stream_name<'_>: ::std::marker::Send, ::std::marker::Unpin
+        );
+
+        impl<'a> #stream_name<'a> {
+            #[doc = #into_inner_doc]
+            pub fn into_inner(self) -> #zbus::#signal_type<'a> {
+                self.0
+            }
+
+            #[doc = #inner_doc]
+            pub fn inner(&self) -> & #zbus::#signal_type<'a> {
+                &self.0
+            }
+        }
+
+        #stream_impl
+
+        #enum_decl
+    };
+
+    (receive_all, stream_types)
+}
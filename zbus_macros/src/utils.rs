@@ -5,6 +5,21 @@ use proc_macro_crate::{crate_name, FoundCrate};
 use quote::{format_ident, quote};
 use syn::{Attribute, FnArg, Ident, Pat, PatIdent, PatType};
 
+/// Check that a name or path given to one of our macros is valid, so that mistakes are reported at
+/// build time rather than when the generated code first runs.
+///
+/// `T` is the type from `zbus_names` that the value will eventually be converted into. Object paths
+/// are checked with [`zvariant_utils::object_path::validate`] instead.
+pub fn validate_name<'s, T>(value: &'s str, span: Span) -> syn::Result<()>
+where
+    T: TryFrom<&'s str>,
+    T::Error: Display,
+{
+    T::try_from(value)
+        .map(drop)
+        .map_err(|e| syn::Error::new(span, format!("`{value}`: {e}")))
+}
+
 pub fn zbus_path() -> TokenStream {
     if let Ok(FoundCrate::Name(name)) = crate_name("zbus") {
         let ident = format_ident!("{}", name);
@@ -0,0 +1,9 @@
+#[rustversion::stable]
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/error/*.rs");
+    t.compile_fail("tests/ui/interface/*.rs");
+    t.compile_fail("tests/ui/proxy/*.rs");
+    t.pass("tests/ui/pass/*.rs");
+}
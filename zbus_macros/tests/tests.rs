@@ -67,6 +67,16 @@ mod test {
 
 #[test]
 fn test_proxy() {
+    // Proxies are serialized as object paths.
+    assert_eq!(
+        <test::TestProxy<'_> as zbus::zvariant::Type>::SIGNATURE.as_str(),
+        Some("o"),
+    );
+    assert_eq!(
+        <test::TestProxyBlocking<'_> as zbus::zvariant::Type>::SIGNATURE.as_str(),
+        Some("o"),
+    );
+
     block_on(async move {
         let connection = zbus::Connection::session().await.unwrap();
         let proxy = test::TestProxy::builder(&connection)
@@ -107,10 +117,89 @@ fn test_proxy() {
     });
 }
 
+mod mocked {
+    use zbus::fdo;
+
+    #[zbus_macros::proxy(
+        interface = "org.freedesktop.zbus_macros.Mocked",
+        default_path = "/org/freedesktop/zbus_macros/mocked",
+        gen_mock = true
+    )]
+    trait Mocked {
+        fn greet(&self, name: &str, times: u32) -> zbus::Result<String>;
+
+        #[zbus(property)]
+        fn count(&self) -> fdo::Result<u32>;
+
+        #[zbus(property)]
+        fn set_count(&self, count: u32) -> fdo::Result<()>;
+
+        #[zbus(signal)]
+        fn greeted(&self, name: &str) -> zbus::Result<()>;
+    }
+}
+
+#[test]
+fn test_proxy_mock() {
+    use mocked::{MockMockedProxy, MockedApi, MockedSignal};
+    use zbus::{message::Type, zvariant::Value};
+
+    block_on(async move {
+        let mut mock = MockMockedProxy::new();
+        mock.on_greet(|name, times| Ok(format!("Hello {name}").repeat(times as usize)))
+            .on_count(|| Ok(42));
+        let api: &dyn MockedApi = &mock;
+
+        let mut greeted = api.receive_greeted().await.unwrap();
+        let mut all = api.receive_all_signals().await.unwrap();
+
+        assert_eq!(api.greet("you", 2).await.unwrap(), "Hello youHello you");
+        assert_eq!(api.count().await.unwrap(), 42);
+        // No response programmed for the setter.
+        assert!(matches!(
+            api.set_count(7).await,
+            Err(fdo::Error::UnknownProperty(_))
+        ));
+
+        let calls = mock.calls();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0].message_type(), Type::MethodCall);
+        let header = calls[0].header();
+        assert_eq!(header.member().unwrap(), "Greet");
+        assert_eq!(
+            header.interface().unwrap(),
+            "org.freedesktop.zbus_macros.Mocked"
+        );
+        assert_eq!(
+            header.path().unwrap(),
+            "/org/freedesktop/zbus_macros/mocked"
+        );
+        let body = calls[0].body();
+        let args: (&str, u32) = body.deserialize().unwrap();
+        assert_eq!(args, ("you", 2));
+        let header = calls[1].header();
+        assert_eq!(header.member().unwrap(), "Get");
+        let body = calls[1].body();
+        let args: (&str, &str) = body.deserialize().unwrap();
+        assert_eq!(args, ("org.freedesktop.zbus_macros.Mocked", "Count"));
+        let body = calls[2].body();
+        let (_, property, value): (&str, &str, Value<'_>) = body.deserialize().unwrap();
+        assert_eq!(property, "Count");
+        assert_eq!(value, Value::from(7u32));
+
+        mock.emit_greeted("you").unwrap();
+        let signal = greeted.next().await.unwrap();
+        assert_eq!(signal.args().unwrap().name(), &"you");
+        match all.next().await.unwrap() {
+            MockedSignal::Greeted(signal) => assert_eq!(signal.args().unwrap().name, "you"),
+        }
+    });
+}
+
 #[test]
 fn test_derive_error() {
     #[derive(Debug, DBusError)]
-    #[zbus(prefix = "org.freedesktop.zbus")]
+    #[zbus(prefix = "org.freedesktop.zbus", impl_error_conversions = true)]
     enum Test {
         #[zbus(error)]
         ZBus(zbus::Error),
@@ -120,6 +209,87 @@ fn test_derive_error() {
         LetItBe {
             desc: String,
         },
+        Bounds(String, u32, u32),
+        Retry {
+            after: u64,
+            reason: String,
+        },
+    }
+
+    let e = Test::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+    assert!(matches!(e, Test::ZBus(zbus::Error::InputOutput(_))));
+
+    // Without `impl_error_conversions`, the conversions are left to the user.
+    #[derive(Debug, DBusError)]
+    enum Custom {
+        #[zbus(error)]
+        ZBus(zbus::Error),
+        Io(String),
+    }
+
+    impl From<std::io::Error> for Custom {
+        fn from(e: std::io::Error) -> Self {
+            Custom::Io(e.to_string())
+        }
+    }
+
+    let e = Custom::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+    assert!(matches!(e, Custom::Io(_)));
+    assert_eq!(
+        zbus::DBusError::description(&Test::Bounds("out of range".into(), 3, 2)),
+        Some("out of range"),
+    );
+    assert_eq!(
+        zbus::DBusError::description(&Test::Retry {
+            after: 1,
+            reason: "busy".into(),
+        }),
+        None,
+    );
+
+    // Structured errors are carried in the reply body and decoded back into the same variant.
+    let call = zbus::message::Message::method("/", "Frobnicate")
+        .unwrap()
+        .build(&())
+        .unwrap();
+    let reply =
+        zbus::DBusError::create_reply(&Test::Bounds("out of range".into(), 3, 2), &call.header())
+            .unwrap();
+    assert_eq!(reply.body().signature().unwrap(), "suu");
+    match Test::from(zbus::Error::from(reply)) {
+        Test::Bounds(desc, index, len) => {
+            assert_eq!(desc, "out of range");
+            assert_eq!(index, 3);
+            assert_eq!(len, 2);
+        }
+        e => panic!("unexpected error: {e:?}"),
+    }
+
+    let retry = Test::Retry {
+        after: 5,
+        reason: "busy".into(),
+    };
+    let reply = zbus::DBusError::create_reply(&retry, &call.header()).unwrap();
+    assert_eq!(reply.body().signature().unwrap(), "ts");
+    match Test::from(zbus::Error::from(reply)) {
+        Test::Retry { after, reason } => {
+            assert_eq!(after, 5);
+            assert_eq!(reason, "busy");
+        }
+        e => panic!("unexpected error: {e:?}"),
+    }
+
+    // A body that doesn't match the fields of the variant is left as a generic method error.
+    let reply = zbus::message::Message::method_error(&call, "org.freedesktop.zbus.Bounds")
+        .unwrap()
+        .build(&("out of range", 3u32))
+        .unwrap();
+    match Test::from(zbus::Error::from(reply)) {
+        Test::ZBus(zbus::Error::MethodError(name, desc, _)) => {
+            assert_eq!(name.as_str(), "org.freedesktop.zbus.Bounds");
+            assert_eq!(desc.as_deref(), Some("out of range"));
+        }
+        e => panic!("unexpected error: {e:?}"),
     }
 }
 
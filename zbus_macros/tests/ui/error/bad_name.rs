@@ -0,0 +1,12 @@
+use zbus_macros::DBusError;
+
+#[derive(Debug, DBusError)]
+#[zbus(prefix = "org.freedesktop.zbus")]
+enum BadName {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    #[zbus(name = "Bad-Name")]
+    BadName,
+}
+
+fn main() {}
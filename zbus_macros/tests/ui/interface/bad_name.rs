@@ -0,0 +1,10 @@
+use zbus_macros::interface;
+
+struct BadName;
+
+#[interface(name = "org.freedesktop.zbus.Bad-Name")]
+impl BadName {
+    fn ping(&self) {}
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use zbus_macros::proxy;
+
+#[proxy(interface = "org.freedesktop.zbus.SignalAll", default_path = "/org/freedesktop/zbus")]
+trait SignalAll {
+    #[zbus(signal)]
+    fn all(&self, count: u32) -> zbus::Result<()>;
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use zbus_macros::proxy;
+
+#[proxy(interface = "org.freedesktop.zbus.AllSignals", default_path = "/org/freedesktop/zbus")]
+trait AllSignals {
+    #[zbus(signal)]
+    fn all_signals(&self) -> zbus::Result<()>;
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use zbus_macros::proxy;
+
+#[proxy(interface = "org.freedesktop.zbus.BadMember", default_path = "/org/freedesktop/zbus")]
+trait BadMember {
+    #[zbus(name = "Bad.Member")]
+    fn ping(&self) -> zbus::Result<()>;
+}
+
+fn main() {}
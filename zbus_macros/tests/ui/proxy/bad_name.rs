@@ -0,0 +1,8 @@
+use zbus_macros::proxy;
+
+#[proxy(interface = "org.freedesktop.zbus.Bad-Name", default_path = "/org/freedesktop/zbus")]
+trait BadName {
+    fn ping(&self) -> zbus::Result<()>;
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use zbus_macros::proxy;
+
+#[proxy(interface = "org.freedesktop.zbus.BadPath", default_path = "org/freedesktop/zbus/")]
+trait BadPath {
+    fn ping(&self) -> zbus::Result<()>;
+}
+
+fn main() {}
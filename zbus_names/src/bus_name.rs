@@ -195,9 +195,7 @@ impl<'de: 'name, 'name> Deserialize<'de> for BusName<'name> {
 }
 
 impl Type for BusName<'_> {
-    fn signature() -> zvariant::Signature<'static> {
-        <&str>::signature()
-    }
+    const SIGNATURE: &'static zvariant::StaticSignature = <&str>::SIGNATURE;
 }
 
 impl<'name> From<UniqueName<'name>> for BusName<'name> {
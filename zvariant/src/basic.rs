@@ -1,4 +1,4 @@
-use crate::{serialized::Format, Signature, Type};
+use crate::{serialized::Format, StaticSignature, Type};
 
 /// Trait for basic types.
 ///
@@ -31,9 +31,8 @@ where
 macro_rules! impl_type {
     ($for:ty) => {
         impl Type for $for {
-            fn signature() -> Signature<'static> {
-                Signature::from_static_str_unchecked(<$for>::SIGNATURE_STR)
-            }
+            const SIGNATURE: &'static StaticSignature =
+                &StaticSignature::from_str_unchecked(<$for>::SIGNATURE_STR);
         }
     };
 }
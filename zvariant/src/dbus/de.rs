@@ -15,6 +15,7 @@ use std::os::fd::AsFd;
 use crate::{
     container_depths::ContainerDepths,
     de::DeserializerCommon,
+    raw_value::{deserialize_raw_value, RAW_VALUE_TOKEN},
     serialized::{Context, Format},
     utils::*,
     value::parsed_signature::{ParsedSignature, SignatureEntry},
@@ -498,6 +499,18 @@ impl<'de, 'd, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deserializer<'d
                 )),
                 None => Err(Error::MissingSignature),
             },
+            RAW_VALUE_TOKEN => match self.parsed_signature.next() {
+                Some(signature) => {
+                    let ctxt = Context::new_dbus(self.common.ctxt.endian(), self.common.abs_pos());
+                    let bytes = subslice(self.common.bytes, self.common.pos..)?;
+                    let (len, value) =
+                        deserialize_raw_value(bytes, ctxt, signature.into(), visitor)?;
+                    self.common.pos += len;
+
+                    Ok(value)
+                }
+                None => Err(Error::MissingSignature),
+            },
             _ => visitor.visit_newtype_struct(self),
         }
     }
@@ -11,6 +11,7 @@ use std::{
 
 use crate::{
     container_depths::ContainerDepths,
+    raw_value::{can_copy_raw_value, unpack_context, RawBytesEmitter, Reencode, RAW_VALUE_TOKEN},
     serialized::{Context, Format},
     utils::*,
     value::{
@@ -136,6 +137,32 @@ where
     pub fn bytes_written(&self) -> usize {
         self.common.bytes_written
     }
+
+    // The encoded value of a `RawValue`, copied as is if possible.
+    fn serialize_raw_value<T>(&mut self, context: u32, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let entry = self.parsed_signature.next().ok_or_else(|| {
+            Error::UnexpectedValue("No signature found for serialized raw value".to_string())
+        })?;
+        let signature = Signature::from(&entry);
+        let source = unpack_context(context);
+
+        value.serialize(RawBytesEmitter(|bytes: &[u8]| {
+            self.common.add_padding(entry.alignment(Format::DBus))?;
+            if can_copy_raw_value(source, self.common.ctxt, self.common.abs_pos()) {
+                return self
+                    .common
+                    .write_all(bytes)
+                    .map_err(|e| Error::InputOutput(e.into()));
+            }
+
+            let value = Reencode::new(bytes, &signature, source)?;
+            self.parsed_signature = ParsedSignature::from(entry);
+            value.serialize(&mut *self)
+        }))
+    }
 }
 
 macro_rules! serialize_basic {
@@ -363,7 +390,7 @@ where
 
     fn serialize_newtype_variant<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         variant_index: u32,
         _variant: &'static str,
         value: &T,
@@ -371,6 +398,10 @@ where
     where
         T: ?Sized + Serialize,
     {
+        if name == RAW_VALUE_TOKEN {
+            return self.serialize_raw_value(variant_index, value);
+        }
+
         match self.parsed_signature.next().as_mut() {
             Some(SignatureEntry::Struct(fields)) => {
                 self.common.add_padding(STRUCT_ALIGNMENT_DBUS)?;
@@ -4,7 +4,7 @@ use std::marker::PhantomData;
 use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Unexpected, Visitor};
 use static_assertions::assert_impl_all;
 
-use crate::{Signature, Type, Value};
+use crate::{Signature, StaticSignature, Type, Value};
 
 /// A wrapper to deserialize a value to `T: Type + Deserialize`.
 ///
@@ -97,7 +97,5 @@ impl<'de, T: Type + Deserialize<'de>> Visitor<'de> for DeserializeValueVisitor<T
 }
 
 impl<'de, T: Type + Deserialize<'de>> Type for DeserializeValue<'de, T> {
-    fn signature() -> Signature<'static> {
-        Value::signature()
-    }
+    const SIGNATURE: &'static StaticSignature = Value::SIGNATURE;
 }
@@ -63,6 +63,9 @@ pub enum Error {
     UnexpectedValue(String),
     /// A value was requested but no signature is available
     MissingSignature,
+    /// The encoded data is not valid at the offset (first argument), for the reason given in the
+    /// second argument.
+    InvalidEncoding(usize, Box<Error>),
 }
 
 assert_impl_all!(Error: Send, Sync, Unpin);
@@ -79,6 +82,9 @@ impl PartialEq for Error {
             (Error::MaxDepthExceeded(max1), Error::MaxDepthExceeded(max2)) => max1 == max2,
             (Error::UnexpectedValue(left), Error::UnexpectedValue(right)) => left == right,
             (Error::MissingSignature, Error::MissingSignature) => true,
+            (Error::InvalidEncoding(o1, e1), Error::InvalidEncoding(o2, e2)) => {
+                o1 == o2 && e1 == e2
+            }
             (_, _) => false,
         }
     }
@@ -89,6 +95,7 @@ impl error::Error for Error {
         match self {
             Error::InputOutput(e) => Some(e),
             Error::Utf8(e) => Some(e),
+            Error::InvalidEncoding(_, e) => Some(&**e),
             _ => None,
         }
     }
@@ -124,6 +131,9 @@ impl fmt::Display for Error {
             Error::MissingSignature => {
                 write!(f, "A value was requested but no signature is available")
             }
+            Error::InvalidEncoding(offset, e) => {
+                write!(f, "Invalid encoding at offset {offset}: {e}")
+            }
         }
     }
 }
@@ -148,6 +158,7 @@ impl Clone for Error {
             Error::MaxDepthExceeded(max) => Error::MaxDepthExceeded(*max),
             Error::UnexpectedValue(s) => Error::UnexpectedValue(s.clone()),
             Error::MissingSignature => Error::MissingSignature,
+            Error::InvalidEncoding(offset, e) => Error::InvalidEncoding(*offset, e.clone()),
         }
     }
 }
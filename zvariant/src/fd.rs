@@ -2,7 +2,7 @@ use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 use static_assertions::assert_impl_all;
 use std::os::fd::{self, AsFd, AsRawFd, BorrowedFd, RawFd};
 
-use crate::{serialized::Format, Basic, Signature, Type};
+use crate::{serialized::Format, Basic, StaticSignature, Type};
 
 /// A file-descriptor type wrapper.
 ///
@@ -109,9 +109,8 @@ macro_rules! fd_impl {
         }
 
         impl Type for $i {
-            fn signature() -> Signature<'static> {
-                Signature::from_static_str_unchecked(Self::SIGNATURE_STR)
-            }
+            const SIGNATURE: &'static StaticSignature =
+                &StaticSignature::from_str_unchecked(<$i>::SIGNATURE_STR);
         }
     };
 }
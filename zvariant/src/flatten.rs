@@ -0,0 +1,340 @@
+//! Support for flattened fields of `SerializeDict` and `DeserializeDict` structs.
+//!
+//! Not part of the public API, only exported for use by the derive macros.
+
+use serde::{
+    de::{
+        value::BorrowedStrDeserializer, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess,
+        Visitor,
+    },
+    ser::{Error as _, Impossible, Serialize, SerializeMap, SerializeStruct, Serializer},
+};
+
+use crate::{value::de::ValueDeserializer, Error, Result, Value};
+
+/// A serializer that adds the entries of a map to an enclosing map being serialized.
+///
+/// Anything other than a map (or a struct, which gets serialized as one), an `Option` of one or
+/// a unit results in an error.
+pub struct FlatMapSerializer<'a, M>(pub &'a mut M);
+
+macro_rules! unsupported {
+    ($($method:ident($($arg:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> std::result::Result<$ret, Self::Error> {
+                Err(M::Error::custom(
+                    "only maps and structs can be flattened into a dictionary",
+                ))
+            }
+        )*
+    };
+}
+
+impl<'a, M> Serializer for FlatMapSerializer<'a, M>
+where
+    M: SerializeMap,
+{
+    type Ok = ();
+    type Error = M::Error;
+    type SerializeSeq = Impossible<(), M::Error>;
+    type SerializeTuple = Impossible<(), M::Error>;
+    type SerializeTupleStruct = Impossible<(), M::Error>;
+    type SerializeTupleVariant = Impossible<(), M::Error>;
+    type SerializeMap = FlatMapSerializeMap<'a, M>;
+    type SerializeStruct = FlatMapSerializeMap<'a, M>;
+    type SerializeStructVariant = Impossible<(), M::Error>;
+
+    unsupported! {
+        serialize_bool(bool) -> ();
+        serialize_i8(i8) -> ();
+        serialize_i16(i16) -> ();
+        serialize_i32(i32) -> ();
+        serialize_i64(i64) -> ();
+        serialize_u8(u8) -> ();
+        serialize_u16(u16) -> ();
+        serialize_u32(u32) -> ();
+        serialize_u64(u64) -> ();
+        serialize_f32(f32) -> ();
+        serialize_f64(f64) -> ();
+        serialize_char(char) -> ();
+        serialize_str(&str) -> ();
+        serialize_bytes(&[u8]) -> ();
+        serialize_unit_variant(&'static str, u32, &'static str) -> ();
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_none(self) -> std::result::Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> std::result::Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> std::result::Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> std::result::Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> std::result::Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> std::result::Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(M::Error::custom(
+            "only maps and structs can be flattened into a dictionary",
+        ))
+    }
+
+    fn serialize_map(
+        self,
+        _len: Option<usize>,
+    ) -> std::result::Result<Self::SerializeMap, Self::Error> {
+        Ok(FlatMapSerializeMap(self.0))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> std::result::Result<Self::SerializeStruct, Self::Error> {
+        Ok(FlatMapSerializeMap(self.0))
+    }
+}
+
+/// Forwards the entries of a flattened map to the enclosing map.
+pub struct FlatMapSerializeMap<'a, M>(&'a mut M);
+
+impl<'a, M> SerializeMap for FlatMapSerializeMap<'a, M>
+where
+    M: SerializeMap,
+{
+    type Ok = ();
+    type Error = M::Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> std::result::Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_key(key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> std::result::Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_value(value)
+    }
+
+    fn end(self) -> std::result::Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a, M> SerializeStruct for FlatMapSerializeMap<'a, M>
+where
+    M: SerializeMap,
+{
+    type Ok = ();
+    type Error = M::Error;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> std::result::Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_entry(key, value)
+    }
+
+    fn end(self) -> std::result::Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Deserialize a flattened field from the dictionary entries not claimed by other fields.
+///
+/// The values are expected to be the contents of the variants, as they appear in a [`Dict`] or
+/// [`Value::Value`].
+///
+/// [`Dict`]: crate::Dict
+pub fn deserialize_flattened<'de, T>(entries: Vec<(&'de str, Value<'de>)>) -> Result<T>
+where
+    T: serde::Deserialize<'de>,
+{
+    T::deserialize(FlatMapDeserializer(entries))
+}
+
+struct FlatMapDeserializer<'de>(Vec<(&'de str, Value<'de>)>);
+
+impl<'de> Deserializer<'de> for FlatMapDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(FlatMapAccess {
+            entries: self.0.into_iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct FlatMapAccess<'de> {
+    entries: std::vec::IntoIter<(&'de str, Value<'de>)>,
+    value: Option<Value<'de>>,
+}
+
+impl<'de> MapAccess<'de> for FlatMapAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(BorrowedStrDeserializer::new(key))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .expect("`next_value_seed` called before `next_key_seed`");
+        seed.deserialize(VariantDeserializer(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Deserializes the contents of a dictionary variant, as they would be from the stream.
+///
+/// Just like the deserializer of a [`Value::Value`], except that [`Value`] and
+/// [`DeserializeValue`] get the variant itself, rather than its contents.
+///
+/// [`DeserializeValue`]: crate::DeserializeValue
+struct VariantDeserializer<'de>(Value<'de>);
+
+impl<'de> VariantDeserializer<'de> {
+    fn into_variant(self) -> ValueDeserializer<'de> {
+        Value::Value(Box::new(self.0)).into_deserializer()
+    }
+}
+
+macro_rules! forward_to_variant {
+    ($($method:ident($($arg:ident: $type:ty),*);)*) => {
+        $(
+            fn $method<V>(self, $($arg: $type,)* visitor: V) -> Result<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                self.into_variant().$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for VariantDeserializer<'de> {
+    type Error = Error;
+
+    forward_to_variant! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if name == "zvariant::Value" {
+            self.into_variant().deserialize_any(visitor)
+        } else {
+            self.into_variant()
+                .deserialize_struct(name, fields, visitor)
+        }
+    }
+}
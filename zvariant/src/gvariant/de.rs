@@ -11,6 +11,7 @@ use crate::{
     de::DeserializerCommon,
     framing_offset_size::FramingOffsetSize,
     framing_offsets::FramingOffsets,
+    raw_value::{deserialize_raw_value, RAW_VALUE_TOKEN},
     serialized::{Context, Format},
     signature_parser::SignatureParser,
     utils::*,
@@ -381,10 +382,25 @@ impl<'de, 'd, 'sig, 'f, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deseriali
         self.deserialize_unit(visitor)
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == RAW_VALUE_TOKEN {
+            let signature = self.sig_parser.next_signature()?.to_owned();
+            self.sig_parser.skip_chars(signature.len())?;
+            let ctxt = Context::new(
+                self.common.ctxt.format(),
+                self.common.ctxt.endian(),
+                self.common.abs_pos(),
+            );
+            let bytes = subslice(self.common.bytes, self.common.pos..)?;
+            let (len, value) = deserialize_raw_value(bytes, ctxt, signature, visitor)?;
+            self.common.pos += len;
+
+            return Ok(value);
+        }
+
         visitor.visit_newtype_struct(self)
     }
 
@@ -10,6 +10,7 @@ use crate::{
     container_depths::ContainerDepths,
     framing_offset_size::FramingOffsetSize,
     framing_offsets::FramingOffsets,
+    raw_value::{can_copy_raw_value, unpack_context, RawBytesEmitter, Reencode, RAW_VALUE_TOKEN},
     serialized::{Context, Format},
     signature_parser::SignatureParser,
     utils::*,
@@ -134,6 +135,29 @@ where
 
         Ok(())
     }
+
+    // The encoded value of a `RawValue`, copied as is if possible.
+    fn serialize_raw_value<T>(&mut self, context: u32, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let signature = self.sig_parser.next_signature()?.to_owned();
+        let alignment = alignment_for_signature(&signature, self.common.ctxt.format())?;
+        let source = unpack_context(context);
+
+        value.serialize(RawBytesEmitter(|bytes: &[u8]| {
+            self.common.add_padding(alignment)?;
+            if can_copy_raw_value(source, self.common.ctxt, self.common.abs_pos()) {
+                self.common
+                    .write_all(bytes)
+                    .map_err(|e| Error::InputOutput(e.into()))?;
+
+                return self.sig_parser.skip_chars(signature.len());
+            }
+
+            Reencode::new(bytes, &signature, source)?.serialize(&mut *self)
+        }))
+    }
 }
 
 macro_rules! serialize_basic {
@@ -307,7 +331,7 @@ where
 
     fn serialize_newtype_variant<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         variant_index: u32,
         _variant: &'static str,
         value: &T,
@@ -315,6 +339,10 @@ where
     where
         T: ?Sized + Serialize,
     {
+        if name == RAW_VALUE_TOKEN {
+            return self.serialize_raw_value(variant_index, value);
+        }
+
         self.prep_serialize_enum_variant(variant_index)?;
 
         value.serialize(self)
@@ -534,11 +562,8 @@ where
 
         ser.sig_parser.skip_char()?;
 
-        let offsets = if c == STRUCT_SIG_START_CHAR {
-            Some(FramingOffsets::new())
-        } else {
-            None
-        };
+        // Dict entries need framing offsets as much as structures do, for non-fixed-sized keys.
+        let offsets = Some(FramingOffsets::new());
         let start = ser.common.bytes_written;
         let container_depths = ser.container_depths;
         ser.container_depths = ser.container_depths.inc_structure()?;
@@ -646,10 +671,8 @@ where
             None => return Ok(()),
         };
         let struct_len = self.ser.common.bytes_written - self.start;
-        if struct_len == 0 {
-            // Empty sequence
-            return Ok(());
-        }
+        // Note that even if all the fields are empty, the non-last non-fixed-sized ones still need
+        // their (0) framing offsets.
         if offsets.peek() == Some(struct_len) {
             // For structs, we don't want offset of last element
             offsets.pop();
@@ -0,0 +1,547 @@
+//! Conversion between [`Value`] and JSON.
+//!
+//! [`to_json`] converts a [`Value`] (or an [`OwnedValue`]) to a [`serde_json::Value`], dropping
+//! the D-Bus type information: integers and floating point numbers become JSON numbers, strings,
+//! object paths and signatures become JSON strings, arrays and structures become JSON arrays,
+//! dictionaries become JSON objects and variants are replaced by their contents.
+//!
+//! [`from_json`] goes the other way, using a target [`Signature`] to recover the type information.
+//! Structures can also be converted from JSON objects keyed by field index (`{"0": .., "1": ..}`).
+//! This makes it possible to put a JSON (e.g REST) gateway in front of a D-Bus service, using the
+//! introspection data to get the types of the method arguments and properties.
+//!
+//! # Example
+//!
+//! ```
+//! use serde_json::json;
+//! use zvariant::{json, Signature};
+//!
+//! let signature = Signature::try_from("(ta{sv})")?;
+//! let value = json::from_json(
+//!     &json!([42, { "brightness": 80, "modes": ["a", "b"] }]),
+//!     &signature,
+//! )?;
+//! assert_eq!(value.value_signature(), "(ta{sv})");
+//!
+//! // Variants get the most natural type for the JSON value.
+//! assert_eq!(
+//!     value.to_string(),
+//!     r#"(uint64 42, {"brightness": <int64 80>, "modes": <[<"a">, <"b">]>})"#,
+//! );
+//!
+//! assert_eq!(
+//!     json::to_json(&value),
+//!     json!([42, { "brightness": 80, "modes": ["a", "b"] }]),
+//! );
+//! # Ok::<(), zvariant::Error>(())
+//! ```
+//!
+//! This module is only available with the `serde_json` feature.
+
+#[cfg(unix)]
+use std::os::fd::AsRawFd;
+
+use serde_json::Map;
+
+#[cfg(feature = "gvariant")]
+use crate::Maybe;
+use crate::{
+    Array, Dict, Error, ObjectPath, OwnedValue, ParsedSignature, Result, Signature, SignatureEntry,
+    StructureBuilder, Value,
+};
+
+/// Convert `value` to JSON.
+///
+/// Dictionary keys are converted to strings, as required by JSON. Floating point numbers that
+/// can't be represented in JSON (infinities and NaN) become `null`, just like `nothing` maybes.
+pub fn to_json(value: &Value<'_>) -> serde_json::Value {
+    match value {
+        Value::U8(v) => (*v).into(),
+        Value::Bool(v) => (*v).into(),
+        Value::I16(v) => (*v).into(),
+        Value::U16(v) => (*v).into(),
+        Value::I32(v) => (*v).into(),
+        Value::U32(v) => (*v).into(),
+        Value::I64(v) => (*v).into(),
+        Value::U64(v) => (*v).into(),
+        Value::F64(v) => (*v).into(),
+        Value::Str(v) => v.as_str().into(),
+        Value::Signature(v) => v.as_str().into(),
+        Value::ObjectPath(v) => v.as_str().into(),
+        Value::Value(v) => to_json(v),
+        Value::Array(array) => array.iter().map(to_json).collect(),
+        Value::Dict(dict) => dict
+            .iter()
+            .map(|(k, v)| (key_to_string(k), to_json(v)))
+            .collect::<Map<_, _>>()
+            .into(),
+        Value::Structure(structure) => structure.fields().iter().map(to_json).collect(),
+        #[cfg(feature = "gvariant")]
+        Value::Maybe(maybe) => maybe
+            .inner()
+            .as_ref()
+            .map(to_json)
+            .unwrap_or(serde_json::Value::Null),
+        #[cfg(unix)]
+        Value::Fd(fd) => fd.as_raw_fd().into(),
+    }
+}
+
+/// Convert `json` to a [`Value`] of type `signature`.
+///
+/// The JSON value must have the shape of the signature:
+///
+/// * Numbers for the integer and floating point types, in the range of the type.
+/// * Strings for strings, object paths and signatures, and for dictionary keys of any type.
+/// * Arrays for arrays and structures.
+/// * Objects for dictionaries and structures. Since structure fields have no names, the keys of an
+///   object converted to a structure are the field indices (`"0"`, `"1"`, ...) and every field
+///   must be present. The fields are taken in declaration order, whatever the order of the keys.
+/// * Anything for variants. Integers become `x` (or `t` if too big), floating point numbers `d`,
+///   arrays `av` and objects `a{sv}`. `null` can't be converted to a variant.
+/// * `null` for `nothing` maybes (`gvariant` feature only).
+///
+/// If `signature` consists of multiple complete types (e.g the signature of a message body), the
+/// JSON value must be an array (or object) of values of these types and the resulting [`Value`]
+/// is a structure.
+///
+/// Use [`OwnedValue::try_from`] to get an [`OwnedValue`].
+pub fn from_json(json: &serde_json::Value, signature: &Signature<'_>) -> Result<Value<'static>> {
+    let mut entries: Vec<_> = ParsedSignature::new(signature).into_iter().collect();
+    let signature = match entries.len() {
+        0 => {
+            return Err(Error::Message(
+                "the empty signature can't be converted from JSON".to_string(),
+            ))
+        }
+        1 => entries.remove(0),
+        _ => SignatureEntry::structure(entries),
+    };
+
+    value_from_json(json, &signature, &mut String::new())
+}
+
+impl From<&Value<'_>> for serde_json::Value {
+    fn from(value: &Value<'_>) -> Self {
+        to_json(value)
+    }
+}
+
+impl From<Value<'_>> for serde_json::Value {
+    fn from(value: Value<'_>) -> Self {
+        to_json(&value)
+    }
+}
+
+impl From<&OwnedValue> for serde_json::Value {
+    fn from(value: &OwnedValue) -> Self {
+        to_json(value)
+    }
+}
+
+impl From<OwnedValue> for serde_json::Value {
+    fn from(value: OwnedValue) -> Self {
+        to_json(&value)
+    }
+}
+
+fn key_to_string(key: &Value<'_>) -> String {
+    match key {
+        Value::Str(v) => v.to_string(),
+        Value::Signature(v) => v.to_string(),
+        Value::ObjectPath(v) => v.to_string(),
+        key => to_json(key).to_string(),
+    }
+}
+
+/// Convert `json` to a value of the complete type `signature`.
+///
+/// `path` is the JSON pointer to `json`, for error messages.
+fn value_from_json(
+    json: &serde_json::Value,
+    signature: &SignatureEntry,
+    path: &mut String,
+) -> Result<Value<'static>> {
+    let value = match signature {
+        SignatureEntry::Bool => Value::Bool(
+            json.as_bool()
+                .ok_or_else(|| mismatch(json, signature, path))?,
+        ),
+        SignatureEntry::U8 => Value::U8(integer(json, signature, path)?),
+        SignatureEntry::I16 => Value::I16(integer(json, signature, path)?),
+        SignatureEntry::U16 => Value::U16(integer(json, signature, path)?),
+        SignatureEntry::I32 => Value::I32(integer(json, signature, path)?),
+        SignatureEntry::U32 => Value::U32(integer(json, signature, path)?),
+        SignatureEntry::I64 => Value::I64(integer(json, signature, path)?),
+        SignatureEntry::U64 => Value::U64(integer(json, signature, path)?),
+        SignatureEntry::F64 => Value::F64(
+            json.as_f64()
+                .ok_or_else(|| mismatch(json, signature, path))?,
+        ),
+        SignatureEntry::Str => Value::from(string(json, signature, path)?.to_string()),
+        SignatureEntry::ObjectPath => {
+            ObjectPath::try_from(string(json, signature, path)?.to_string())
+                .map_err(|e| at(path, e))?
+                .into()
+        }
+        SignatureEntry::Signature => {
+            Signature::try_from(string(json, signature, path)?.to_string())
+                .map_err(|e| at(path, e))?
+                .into()
+        }
+        SignatureEntry::Variant => {
+            let signature = variant_signature(json)
+                .ok_or_else(|| at(path, format!("`{json}` can't be converted to a variant")))?;
+
+            Value::Value(Box::new(value_from_json(json, &signature, path)?))
+        }
+        SignatureEntry::Array(element) => match signature.as_dict() {
+            Some((key_signature, value_signature)) => {
+                let object = json
+                    .as_object()
+                    .ok_or_else(|| mismatch(json, signature, path))?;
+
+                let mut dict = Dict::new(key_signature.into(), value_signature.into());
+                for (k, v) in object {
+                    let len = path.len();
+                    path.push('/');
+                    path.push_str(&k.replace('~', "~0").replace('/', "~1"));
+                    let key = key_from_json(k, key_signature, path)?;
+                    let value = value_from_json(v, value_signature, path)?;
+                    path.truncate(len);
+
+                    dict.append(key, value)?;
+                }
+
+                Value::Dict(dict)
+            }
+            None => {
+                let elements = json
+                    .as_array()
+                    .ok_or_else(|| mismatch(json, signature, path))?;
+
+                let mut array = Array::new(Signature::from(&**element));
+                for (i, element_json) in elements.iter().enumerate() {
+                    let len = path.len();
+                    path.push_str(&format!("/{i}"));
+                    array.append(value_from_json(element_json, element, path)?)?;
+                    path.truncate(len);
+                }
+
+                Value::Array(array)
+            }
+        },
+        SignatureEntry::Struct(field_signatures) => {
+            let fields = struct_fields(json, signature, field_signatures.len(), path)?;
+
+            let mut builder = StructureBuilder::new();
+            for (i, (field, signature)) in fields.into_iter().zip(field_signatures).enumerate() {
+                let len = path.len();
+                path.push_str(&format!("/{i}"));
+                builder = builder.append_field(value_from_json(field, signature, path)?);
+                path.truncate(len);
+            }
+
+            Value::Structure(builder.build())
+        }
+        #[cfg(feature = "gvariant")]
+        SignatureEntry::Maybe(inner) => match json {
+            serde_json::Value::Null => Value::Maybe(Maybe::nothing(Signature::from(&**inner))),
+            json => Value::Maybe(Maybe::just(value_from_json(json, inner, path)?)),
+        },
+        _ => {
+            return Err(at(
+                path,
+                format!("`{signature}` can't be converted from JSON"),
+            ))
+        }
+    };
+
+    Ok(value)
+}
+
+/// The JSON values of the `len` fields of the structure `signature`, in declaration order.
+///
+/// `json` is either an array of the fields, or an object with the field indices as keys.
+fn struct_fields<'j>(
+    json: &'j serde_json::Value,
+    signature: &SignatureEntry,
+    len: usize,
+    path: &str,
+) -> Result<Vec<&'j serde_json::Value>> {
+    match json {
+        serde_json::Value::Array(fields) => {
+            if fields.len() != len {
+                return Err(at(
+                    path,
+                    format!(
+                        "expected {len} fields for `{signature}`, found {}",
+                        fields.len(),
+                    ),
+                ));
+            }
+
+            Ok(fields.iter().collect())
+        }
+        serde_json::Value::Object(object) => {
+            let is_index = |key: &str| {
+                key.parse::<usize>()
+                    .is_ok_and(|i| i < len && i.to_string() == key)
+            };
+            if let Some(key) = object.keys().find(|key| !is_index(key)) {
+                return Err(at(path, format!("unknown field `{key}` for `{signature}`")));
+            }
+
+            (0..len)
+                .map(|i| {
+                    object
+                        .get(&i.to_string())
+                        .ok_or_else(|| at(path, format!("missing field `{i}` for `{signature}`")))
+                })
+                .collect()
+        }
+        json => Err(mismatch(json, signature, path)),
+    }
+}
+
+/// Convert the dictionary key `key` to a value of the basic type `signature`.
+fn key_from_json(key: &str, signature: &SignatureEntry, path: &str) -> Result<Value<'static>> {
+    match signature {
+        SignatureEntry::Str | SignatureEntry::ObjectPath | SignatureEntry::Signature => {
+            value_from_json(&key.into(), signature, &mut path.to_string())
+        }
+        _ => {
+            let json = serde_json::from_str(key)
+                .map_err(|_| at(path, format!("invalid key `{key}` for type `{signature}`")))?;
+
+            value_from_json(&json, signature, &mut path.to_string())
+        }
+    }
+}
+
+/// The most natural signature for `json` in a variant.
+fn variant_signature(json: &serde_json::Value) -> Option<SignatureEntry> {
+    let signature = match json {
+        serde_json::Value::Null => return None,
+        serde_json::Value::Bool(_) => SignatureEntry::Bool,
+        serde_json::Value::Number(n) if n.is_i64() => SignatureEntry::I64,
+        serde_json::Value::Number(n) if n.is_u64() => SignatureEntry::U64,
+        serde_json::Value::Number(_) => SignatureEntry::F64,
+        serde_json::Value::String(_) => SignatureEntry::Str,
+        serde_json::Value::Array(_) => SignatureEntry::array(SignatureEntry::Variant),
+        serde_json::Value::Object(_) => {
+            SignatureEntry::dict(SignatureEntry::Str, SignatureEntry::Variant)
+        }
+    };
+
+    Some(signature)
+}
+
+fn integer<T>(json: &serde_json::Value, signature: &SignatureEntry, path: &str) -> Result<T>
+where
+    T: TryFrom<i64> + TryFrom<u64>,
+{
+    let n = match json {
+        serde_json::Value::Number(n) => n,
+        _ => return Err(mismatch(json, signature, path)),
+    };
+    let converted = match (n.as_i64(), n.as_u64()) {
+        (Some(i), _) => T::try_from(i).ok(),
+        (None, Some(u)) => T::try_from(u).ok(),
+        (None, None) => return Err(mismatch(json, signature, path)),
+    };
+
+    converted.ok_or_else(|| at(path, format!("`{n}` is out of range for `{signature}`")))
+}
+
+fn string<'j>(
+    json: &'j serde_json::Value,
+    signature: &SignatureEntry,
+    path: &str,
+) -> Result<&'j str> {
+    json.as_str().ok_or_else(|| mismatch(json, signature, path))
+}
+
+fn mismatch(json: &serde_json::Value, signature: &SignatureEntry, path: &str) -> Error {
+    let found = match json {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "a boolean",
+        serde_json::Value::Number(n) if n.is_f64() => "a floating point number",
+        serde_json::Value::Number(_) => "an integer",
+        serde_json::Value::String(_) => "a string",
+        serde_json::Value::Array(_) => "an array",
+        serde_json::Value::Object(_) => "an object",
+    };
+
+    at(
+        path,
+        format!("expected a value of type `{signature}`, found {found}"),
+    )
+}
+
+/// An error about the JSON value at `path`.
+fn at(path: &str, message: impl std::fmt::Display) -> Error {
+    if path.is_empty() {
+        Error::Message(message.to_string())
+    } else {
+        Error::Message(format!("{message} at `{path}`"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use crate::{Array, ObjectPath, Signature, Value};
+
+    use super::{from_json, to_json};
+
+    fn convert(json: serde_json::Value, signature: &str) -> crate::Result<Value<'static>> {
+        from_json(&json, &Signature::try_from(signature).unwrap())
+    }
+
+    #[test]
+    fn basic() {
+        assert_eq!(convert(json!(42), "u").unwrap(), Value::U32(42));
+        assert_eq!(convert(json!(42), "t").unwrap(), Value::U64(42));
+        assert_eq!(convert(json!(42), "d").unwrap(), Value::F64(42.));
+        assert_eq!(convert(json!(-1), "n").unwrap(), Value::I16(-1));
+        assert_eq!(convert(json!(u64::MAX), "t").unwrap(), Value::U64(u64::MAX));
+        assert_eq!(convert(json!(true), "b").unwrap(), Value::Bool(true));
+        assert_eq!(
+            convert(json!("/org/zbus"), "o").unwrap(),
+            Value::from(ObjectPath::try_from("/org/zbus").unwrap()),
+        );
+        assert_eq!(
+            convert(json!(1.5), "v").unwrap(),
+            Value::new(Value::F64(1.5))
+        );
+        assert_eq!(convert(json!(-1), "v").unwrap(), Value::new(Value::I64(-1)));
+    }
+
+    #[test]
+    fn containers() {
+        assert_eq!(
+            convert(json!([1, 2]), "ay").unwrap(),
+            Value::from(vec![1u8, 2]),
+        );
+        assert_eq!(
+            convert(json!([]), "as").unwrap(),
+            Value::from(Array::new(Signature::try_from("s").unwrap())),
+        );
+
+        let mut dict = HashMap::new();
+        dict.insert(1u32, "one");
+        dict.insert(2u32, "two");
+        assert_eq!(
+            convert(json!({ "1": "one", "2": "two" }), "a{us}").unwrap(),
+            Value::from(dict),
+        );
+
+        let mut dict = HashMap::new();
+        dict.insert("brightness", Value::I64(80));
+        dict.insert("modes", Value::new(vec![Value::new("a"), Value::new("b")]));
+        assert_eq!(
+            convert(json!({ "brightness": 80, "modes": ["a", "b"] }), "a{sv}").unwrap(),
+            Value::from(dict),
+        );
+
+        let expected = Value::from((1u8, "a"));
+        assert_eq!(convert(json!([1, "a"]), "(ys)").unwrap(), expected);
+        // Fields are taken in declaration order, whatever the order of the keys.
+        assert_eq!(
+            convert(json!({ "1": "a", "0": 1 }), "(ys)").unwrap(),
+            expected
+        );
+        // Multiple complete types.
+        assert_eq!(convert(json!([1, "a"]), "ys").unwrap(), expected);
+        assert_eq!(
+            convert(json!({ "0": 1, "1": "a" }), "ys").unwrap(),
+            expected
+        );
+    }
+
+    #[cfg(feature = "gvariant")]
+    #[test]
+    fn maybe() {
+        assert_eq!(
+            convert(json!([1, null]), "amu").unwrap(),
+            Value::from(vec![Some(1u32), None]),
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let json = json!({
+            "name": "zbus",
+            "answer": 42,
+            "ratio": 2.5,
+            "list": [true, false],
+            "nested": { "empty": [] },
+        });
+        let value = convert(json.clone(), "a{sv}").unwrap();
+        assert_eq!(to_json(&value), json);
+        assert_eq!(
+            serde_json::Value::from(&value.try_to_owned().unwrap()),
+            json
+        );
+
+        let value = Value::from((
+            (1u8, -2i16, u64::MAX),
+            ObjectPath::try_from("/").unwrap(),
+            f64::NAN,
+        ));
+        let json = to_json(&value);
+        assert_eq!(json, json!([[1, -2, u64::MAX], "/", null]));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |json, signature| convert(json, signature).unwrap_err().to_string();
+
+        assert_eq!(
+            error(json!("42"), "u"),
+            "expected a value of type `u`, found a string",
+        );
+        assert_eq!(error(json!(256), "y"), "`256` is out of range for `y`");
+        assert_eq!(error(json!(-1), "t"), "`-1` is out of range for `t`");
+        assert_eq!(
+            error(json!({ "a": [1, 1.5] }), "a{sai}"),
+            "expected a value of type `i`, found a floating point number at `/a/1`",
+        );
+        assert_eq!(
+            error(json!({ "x": 1 }), "a{us}"),
+            "invalid key `x` for type `u` at `/x`",
+        );
+        assert_eq!(
+            error(json!([1]), "(ii)"),
+            "expected 2 fields for `(ii)`, found 1",
+        );
+        assert_eq!(
+            error(json!({ "b": 1, "a": "x" }), "(ys)"),
+            "unknown field `a` for `(ys)`",
+        );
+        assert_eq!(
+            error(json!({ "0": 1, "01": "x" }), "(ys)"),
+            "unknown field `01` for `(ys)`",
+        );
+        assert_eq!(
+            error(json!({ "s": { "0": 1 } }), "a{s(ys)}"),
+            "missing field `1` for `(ys)` at `/s`",
+        );
+        assert_eq!(
+            error(json!({ "0": 1, "1": 2 }), "(ys)"),
+            "expected a value of type `s`, found an integer at `/1`",
+        );
+        assert_eq!(
+            error(json!("a"), "(ys)"),
+            "expected a value of type `(ys)`, found a string",
+        );
+        assert_eq!(
+            error(json!({ "a": null }), "a{sv}"),
+            "`null` can't be converted to a variant at `/a`",
+        );
+    }
+}
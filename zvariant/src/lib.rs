@@ -57,12 +57,20 @@ pub mod gvariant;
 mod signature;
 pub use crate::signature::*;
 
+mod static_signature;
+pub use static_signature::StaticSignature;
+
 mod complete_type;
 pub use complete_type::*;
 
 mod str;
 pub use crate::str::*;
 
+pub mod text;
+
+#[cfg(feature = "serde_json")]
+pub mod json;
+
 mod structure;
 pub use crate::structure::*;
 
@@ -75,7 +83,11 @@ mod optional;
 pub use crate::optional::*;
 
 mod value;
-pub use value::{from_value, Value};
+pub use value::{
+    from_value,
+    parsed_signature::{ParsedSignature, SignatureEntry},
+    Value,
+};
 
 mod serialize_value;
 pub use serialize_value::*;
@@ -93,6 +105,8 @@ pub use r#type::*;
 mod tuple;
 pub use tuple::*;
 
+mod flatten;
+
 mod from_value;
 
 mod into_value;
@@ -100,6 +114,9 @@ mod into_value;
 mod owned_value;
 pub use owned_value::*;
 
+mod raw_value;
+pub use raw_value::RawValue;
+
 #[cfg(feature = "gvariant")]
 mod framing_offset_size;
 #[cfg(feature = "gvariant")]
@@ -107,7 +124,9 @@ mod framing_offsets;
 
 mod signature_parser;
 
-pub use zvariant_derive::{DeserializeDict, OwnedValue, SerializeDict, Type, Value};
+pub use zvariant_derive::{
+    DeserializeDict, DeserializeTagged, OwnedValue, SerializeDict, SerializeTagged, Type, Value,
+};
 
 // Required for the macros to function within this crate.
 extern crate self as zvariant;
@@ -115,6 +134,7 @@ extern crate self as zvariant;
 // Macro support module, not part of the public API.
 #[doc(hidden)]
 pub mod export {
+    pub use crate::flatten::{deserialize_flattened, FlatMapSerializer};
     pub use serde;
 }
 
@@ -1245,6 +1265,30 @@ mod tests {
             panic!();
         }
 
+        // The keys of dict entries in a `Value` need framing offsets in GVariant-format too.
+        #[cfg(feature = "gvariant")]
+        {
+            let ctxt = Context::new_gvariant(NATIVE_ENDIAN, 0);
+            let mut dict = Dict::new(<&str>::signature(), Value::signature());
+            dict.add("hello", Value::new("there")).unwrap();
+            dict.add("bye", Value::new(7u32)).unwrap();
+            let v: Value<'_> = dict.into();
+            let gv_encoded = to_bytes(ctxt, &v).unwrap();
+            assert_eq!(gv_encoded.len(), 41);
+            let decoded: Value<'_> = gv_encoded.deserialize().unwrap().0;
+            assert_eq!(decoded, v);
+
+            // Check encoding against GLib
+            let bytes = Bytes::from_owned(gv_encoded);
+            let variant = Variant::from_bytes::<Variant>(&bytes);
+            let dict = variant.as_variant().unwrap();
+            assert_eq!(dict.type_().as_str(), "a{sv}");
+            assert_eq!(dict.n_children(), 2);
+            let map: HashMap<String, Variant> = HashMap::from_variant(&dict).unwrap();
+            assert_eq!(map["hello"].str(), Some("there"));
+            assert_eq!(map["bye"].get::<u32>(), Some(7));
+        }
+
         #[derive(SerializeDict, DeserializeDict, Type, PartialEq, Debug)]
         #[zvariant(signature = "a{sv}")]
         struct Test {
@@ -1841,6 +1885,24 @@ mod tests {
             assert!(variant.get::<Option<String>>().unwrap().is_none());
         }
 
+        // In a structure, where the first field still needs a framing offset.
+        #[cfg(all(feature = "gvariant", not(feature = "option-as-array")))]
+        {
+            let mss: (Option<String>, Option<String>) = (None, None);
+            let encoded = to_bytes(ctxt, &mss).unwrap();
+            assert_eq!(encoded.len(), 1);
+            let decoded: (Option<String>, Option<String>) = encoded.deserialize().unwrap().0;
+            assert_eq!(decoded, mss);
+
+            // Check encoding against GLib
+            let bytes = Bytes::from_owned(encoded);
+            let variant = Variant::from_bytes::<(Option<String>, Option<String>)>(&bytes);
+            assert_eq!(
+                variant.get::<(Option<String>, Option<String>)>().unwrap(),
+                mss
+            );
+        }
+
         // In a seq type
         let ams = vec![
             Some(String::from("hello world")),
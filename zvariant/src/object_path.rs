@@ -6,7 +6,7 @@ use serde::{
 use static_assertions::assert_impl_all;
 use std::borrow::Cow;
 
-use crate::{serialized::Format, Basic, Error, Result, Signature, Str, Type};
+use crate::{serialized::Format, Basic, Error, Result, StaticSignature, Str, Type};
 
 /// String that identifies objects at a given destination on the D-Bus bus.
 ///
@@ -134,9 +134,8 @@ impl<'a> Basic for ObjectPath<'a> {
 }
 
 impl<'a> Type for ObjectPath<'a> {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked(Self::SIGNATURE_STR)
-    }
+    const SIGNATURE: &'static StaticSignature =
+        &StaticSignature::from_str_unchecked(Self::SIGNATURE_STR);
 }
 
 impl<'a> TryFrom<&'a [u8]> for ObjectPath<'a> {
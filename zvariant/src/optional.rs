@@ -80,6 +80,8 @@ impl<T> Type for Optional<T>
 where
     T: Type,
 {
+    const SIGNATURE: &'static crate::StaticSignature = T::SIGNATURE;
+
     fn signature() -> crate::Signature<'static> {
         T::signature()
     }
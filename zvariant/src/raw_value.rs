@@ -0,0 +1,717 @@
+use std::borrow::Cow;
+
+use serde::{
+    de::{
+        self,
+        value::{BorrowedBytesDeserializer, U32Deserializer},
+        DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor,
+    },
+    ser::{
+        self, Impossible, Serialize, SerializeSeq, SerializeStruct, SerializeTupleStruct,
+        Serializer,
+    },
+    Deserialize,
+};
+use static_assertions::assert_impl_all;
+
+use crate::{
+    serialized::{Context, Data, Format, View},
+    Endian, Error, ObjectPath, Result, Signature, StaticSignature, Type, Value, LE,
+};
+
+/// The name used to hand encoded bytes between [`RawValue`] and our (de)serializers.
+pub(crate) const RAW_VALUE_TOKEN: &str = "zvariant::RawValue";
+
+/// A variant, kept in its encoded form.
+///
+/// This is for forwarding a `v` from one message to another, without decoding it into a [`Value`]
+/// and encoding it again: deserializing a `RawValue` only borrows the encoded bytes of the value
+/// and serializing it copies them back out. In between, it can be decoded on demand, through
+/// [`RawValue::to_value`] or [`RawValue::deserialize`].
+///
+/// Copying the bytes as-is is only possible if the encoding context is compatible, i-e same
+/// format and endianness, and same alignment of the value. Otherwise, the value gets decoded and
+/// encoded again as part of the serialization.
+///
+/// File descriptors are not supported since only the bytes are kept around, not the file
+/// descriptors themselves.
+///
+/// With serializers and deserializers other than the ones from this crate (e.g JSON), a
+/// `RawValue` is (de)serialized the same way as a [`Value`] is.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{serialized::Context, to_bytes, RawValue, Value, LE};
+///
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let encoded = to_bytes(ctxt, &("forwarded", Value::from((42u32, "hello")))).unwrap();
+///
+/// // A router decodes the string it cares about and keeps the variant as is.
+/// let (target, raw): (&str, RawValue<'_>) = encoded.deserialize().unwrap().0;
+/// assert_eq!(target, "forwarded");
+/// assert_eq!(raw.signature(), "(us)");
+///
+/// // Encoding it again only copies the bytes.
+/// let forwarded = to_bytes(ctxt, &raw).unwrap();
+/// let value: Value<'_> = forwarded.deserialize().unwrap().0;
+/// assert_eq!(value, Value::from((42u32, "hello")));
+///
+/// // And it can be decoded on demand.
+/// let decoded: (u32, &str) = raw.deserialize().unwrap();
+/// assert_eq!(decoded, (42, "hello"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawValue<'a> {
+    bytes: Cow<'a, [u8]>,
+    signature: Signature<'a>,
+    context: Context,
+}
+
+assert_impl_all!(RawValue<'_>: Send, Sync, Unpin);
+
+impl<'a> RawValue<'a> {
+    /// Create a new `RawValue` from encoded bytes.
+    ///
+    /// `bytes` must be the encoding of a single value of type `signature`, at the position of
+    /// `context`. This is not checked until the value is decoded.
+    pub fn new<B>(bytes: B, signature: Signature<'a>, context: Context) -> Self
+    where
+        B: Into<Cow<'a, [u8]>>,
+    {
+        Self {
+            bytes: bytes.into(),
+            signature,
+            context,
+        }
+    }
+
+    /// Encode `value` into a `RawValue`.
+    ///
+    /// Since [`Value`] already knows its signature, this is typically only useful for sending a
+    /// [`Value`] through an API that expects a `RawValue`.
+    pub fn from_value(value: &Value<'_>) -> Result<RawValue<'static>> {
+        let ctxt = Context::new_dbus(LE, 0);
+        let encoded = crate::to_bytes(ctxt, value)?;
+        let view = encoded.view(Value::signature())?.variant()?;
+
+        Ok(RawValue {
+            bytes: Cow::Owned(view.bytes().to_vec()),
+            signature: view.signature().to_owned(),
+            context: view.context(),
+        })
+    }
+
+    /// The encoded bytes of the value.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The signature of the value.
+    pub fn signature(&self) -> &Signature<'a> {
+        &self.signature
+    }
+
+    /// The encoding context of the value.
+    ///
+    /// Only the alignment of the position is kept, so it's not necessarily the position the
+    /// value was originally encoded at.
+    pub fn context(&self) -> Context {
+        self.context
+    }
+
+    /// A [`View`] of the value.
+    pub fn view(&self) -> Result<View<'_>> {
+        View::new(
+            &self.bytes,
+            self.context,
+            #[cfg(unix)]
+            &[],
+            self.signature.clone(),
+        )
+    }
+
+    /// Decode the value as a [`Value`].
+    pub fn to_value(&self) -> Result<Value<'_>> {
+        self.view()?.to_value()
+    }
+
+    /// Decode the value as `T`.
+    ///
+    /// The signature of `T` must be the same as that of the value.
+    pub fn deserialize<'d, T>(&'d self) -> Result<T>
+    where
+        T: Deserialize<'d> + Type,
+    {
+        self.view()?.deserialize()
+    }
+
+    /// Creates an owned clone of `self`.
+    pub fn to_owned(&self) -> RawValue<'static> {
+        RawValue {
+            bytes: Cow::Owned(self.bytes.to_vec()),
+            signature: self.signature.to_owned(),
+            context: self.context,
+        }
+    }
+
+    /// Creates an owned clone of `self`.
+    pub fn into_owned(self) -> RawValue<'static> {
+        RawValue {
+            bytes: Cow::Owned(self.bytes.into_owned()),
+            signature: self.signature.into_owned(),
+            context: self.context,
+        }
+    }
+}
+
+impl<'a> From<View<'a>> for RawValue<'a> {
+    fn from(view: View<'a>) -> Self {
+        RawValue {
+            bytes: Cow::Borrowed(view.bytes()),
+            signature: view.signature().clone(),
+            context: view.context(),
+        }
+    }
+}
+
+impl Type for RawValue<'_> {
+    const SIGNATURE: &'static StaticSignature = Value::SIGNATURE;
+}
+
+impl Serialize for RawValue<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            return self
+                .to_value()
+                .map_err(ser::Error::custom)?
+                .serialize(serializer);
+        }
+
+        // Same as `Value`, except for the value itself.
+        let mut structure = serializer.serialize_struct("zvariant::Value", 2)?;
+        structure.serialize_field("zvariant::Value::Signature", &self.signature)?;
+        structure.serialize_field("zvariant::Value::Value", &RawPayload(self))?;
+        structure.end()
+    }
+}
+
+/// The encoded value, handed to our serializers along with its context.
+struct RawPayload<'r, 'a>(&'r RawValue<'a>);
+
+impl Serialize for RawPayload<'_, '_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_variant(
+            RAW_VALUE_TOKEN,
+            pack_context(self.0.context),
+            RAW_VALUE_TOKEN,
+            &RawBytes(&self.0.bytes),
+        )
+    }
+}
+
+struct RawBytes<'r>(&'r [u8]);
+
+impl Serialize for RawBytes<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for RawValue<'a> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            let value = Value::deserialize(deserializer)?;
+
+            return RawValue::from_value(&value).map_err(de::Error::custom);
+        }
+
+        deserializer.deserialize_struct(
+            "zvariant::Value",
+            &["zvariant::Value::Signature", "zvariant::Value::Value"],
+            RawValueVisitor,
+        )
+    }
+}
+
+struct RawValueVisitor;
+
+impl<'de> Visitor<'de> for RawValueVisitor {
+    type Value = RawValue<'de>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("a Value")
+    }
+
+    fn visit_seq<V>(self, mut visitor: V) -> std::result::Result<RawValue<'de>, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let signature = visitor
+            .next_element::<Signature<'de>>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &"a Value signature"))?;
+        let (context, bytes) = visitor
+            .next_element_seed(RawBytesSeed)?
+            .ok_or_else(|| de::Error::invalid_length(1, &"a Value value"))?;
+
+        raw_value(bytes, signature, context)
+    }
+
+    fn visit_map<V>(self, mut visitor: V) -> std::result::Result<RawValue<'de>, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let (_, signature) = visitor
+            .next_entry::<IgnoredAny, Signature<'de>>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &"a Value signature"))?;
+        let _ = visitor.next_key::<IgnoredAny>()?;
+        let (context, bytes) = visitor.next_value_seed(RawBytesSeed)?;
+
+        raw_value(bytes, signature, context)
+    }
+}
+
+fn raw_value<'de, E>(
+    bytes: &'de [u8],
+    signature: Signature<'de>,
+    context: Context,
+) -> std::result::Result<RawValue<'de>, E>
+where
+    E: de::Error,
+{
+    #[cfg(unix)]
+    if signature.contains(<crate::Fd<'_> as crate::Basic>::SIGNATURE_CHAR) {
+        return Err(de::Error::custom(
+            "file descriptors can't be kept in a `RawValue`",
+        ));
+    }
+
+    Ok(RawValue::new(bytes, signature, context))
+}
+
+struct RawBytesSeed;
+
+impl<'de> DeserializeSeed<'de> for RawBytesSeed {
+    type Value = (Context, &'de [u8]);
+
+    fn deserialize<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(RAW_VALUE_TOKEN, RawBytesVisitor)
+    }
+}
+
+struct RawBytesVisitor;
+
+impl<'de> Visitor<'de> for RawBytesVisitor {
+    type Value = (Context, &'de [u8]);
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("an encoded value")
+    }
+
+    fn visit_seq<V>(self, mut visitor: V) -> std::result::Result<Self::Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let context = visitor
+            .next_element::<u32>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &"an encoding context"))?;
+        let bytes = visitor
+            .next_element::<&'de [u8]>()?
+            .ok_or_else(|| de::Error::invalid_length(1, &"encoded bytes"))?;
+
+        Ok((unpack_context(context), bytes))
+    }
+}
+
+/// Hand the encoded value in `bytes` over to `visitor` of a [`RawValue`].
+///
+/// Returns the number of bytes the value (including its padding) took.
+pub(crate) fn deserialize_raw_value<'de, V>(
+    bytes: &'de [u8],
+    context: Context,
+    signature: Signature<'de>,
+    visitor: V,
+) -> Result<(usize, V::Value)>
+where
+    V: Visitor<'de>,
+{
+    let view = View::new(
+        bytes,
+        context,
+        #[cfg(unix)]
+        &[],
+        signature,
+    )?;
+    let len = view.context().position() - context.position() + view.bytes().len();
+    let value = visitor.visit_seq(RawValueAccess {
+        context: Some(pack_context(view.context())),
+        bytes: Some(view.bytes()),
+    })?;
+
+    Ok((len, value))
+}
+
+struct RawValueAccess<'de> {
+    context: Option<u32>,
+    bytes: Option<&'de [u8]>,
+}
+
+impl<'de> SeqAccess<'de> for RawValueAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if let Some(context) = self.context.take() {
+            return seed.deserialize(U32Deserializer::new(context)).map(Some);
+        }
+
+        self.bytes
+            .take()
+            .map(|bytes| seed.deserialize(BorrowedBytesDeserializer::new(bytes)))
+            .transpose()
+    }
+}
+
+/// Whether a value encoded in `source` can be copied as is at `position` in `target`.
+pub(crate) fn can_copy_raw_value(source: Context, target: Context, position: usize) -> bool {
+    source.format() == target.format()
+        && source.endian() == target.endian()
+        && source.position() % 8 == position % 8
+}
+
+/// An encoded value, serialized through its parts for encoding it again in a different context.
+///
+/// Going through [`Value`] instead is not an option since decoding a [`Value`] doesn't preserve
+/// nested variants.
+pub(crate) struct Reencode<'b>(View<'b>);
+
+impl<'b> Reencode<'b> {
+    pub(crate) fn new(
+        bytes: &'b [u8],
+        signature: &Signature<'_>,
+        context: Context,
+    ) -> Result<Self> {
+        View::new(
+            bytes,
+            context,
+            #[cfg(unix)]
+            &[],
+            signature.to_owned(),
+        )
+        .map(Self)
+    }
+}
+
+impl Serialize for Reencode<'_> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let view = &self.0;
+        let signature = view.signature();
+
+        match signature.as_bytes().first().copied().unwrap_or_default() {
+            b'y' => serializer.serialize_u8(decode(view)?),
+            b'b' => serializer.serialize_bool(decode(view)?),
+            b'n' => serializer.serialize_i16(decode(view)?),
+            b'q' => serializer.serialize_u16(decode(view)?),
+            b'i' => serializer.serialize_i32(decode(view)?),
+            b'u' => serializer.serialize_u32(decode(view)?),
+            b'x' => serializer.serialize_i64(decode(view)?),
+            b't' => serializer.serialize_u64(decode(view)?),
+            b'd' => serializer.serialize_f64(decode(view)?),
+            b's' => serializer.serialize_str(decode(view)?),
+            b'o' => decode::<ObjectPath<'_>, _>(view)?.serialize(serializer),
+            b'g' => decode::<Signature<'_>, _>(view)?.serialize(serializer),
+            b'v' => {
+                let inner = view.variant().map_err(ser::Error::custom)?;
+
+                let mut structure = serializer.serialize_struct("zvariant::Value", 2)?;
+                structure.serialize_field("zvariant::Value::Signature", inner.signature())?;
+                structure.serialize_field("zvariant::Value::Value", &Reencode(inner))?;
+                structure.end()
+            }
+            b'a' => {
+                let mut seq = serializer.serialize_seq(None)?;
+                for element in view.elements().map_err(ser::Error::custom)? {
+                    seq.serialize_element(&Reencode(element.map_err(ser::Error::custom)?))?;
+                }
+                seq.end()
+            }
+            b'{' => {
+                let mut fields = view.fields().map_err(ser::Error::custom)?;
+                let mut next_field = || match fields.next() {
+                    Some(field) => field.map(Reencode).map_err(ser::Error::custom),
+                    None => Err(ser::Error::custom("incomplete dictionary entry")),
+                };
+
+                let mut entry = serializer.serialize_struct("zvariant::DictEntry", 2)?;
+                entry.serialize_field("zvariant::DictEntry::Key", &next_field()?)?;
+                entry.serialize_field("zvariant::DictEntry::Value", &next_field()?)?;
+                entry.end()
+            }
+            b'(' => {
+                let fields = view
+                    .fields()
+                    .and_then(|fields| fields.map(|field| field.map(Reencode)).collect())
+                    .map_err(ser::Error::custom)?;
+                let fields: Vec<Reencode<'_>> = fields;
+
+                let mut structure =
+                    serializer.serialize_tuple_struct("zvariant::Structure", fields.len())?;
+                for field in &fields {
+                    structure.serialize_field(field)?;
+                }
+                structure.end()
+            }
+            #[cfg(feature = "gvariant")]
+            b'm' => match view.maybe().map_err(ser::Error::custom)? {
+                Some(inner) => serializer.serialize_some(&Reencode(inner)),
+                None => serializer.serialize_none(),
+            },
+            _ => Err(ser::Error::custom(format!(
+                "can't encode a `RawValue` of signature `{signature}` again"
+            ))),
+        }
+    }
+}
+
+fn decode<'b, T, E>(view: &View<'b>) -> std::result::Result<T, E>
+where
+    T: Deserialize<'b> + Type,
+    E: ser::Error,
+{
+    view.deserialize().map_err(E::custom)
+}
+
+// Only the alignment of the position matters so we can pack all of the context into the variant
+// index of `serialize_newtype_variant`.
+fn pack_context(context: Context) -> u32 {
+    let format = match context.format() {
+        Format::DBus => 0,
+        #[cfg(feature = "gvariant")]
+        Format::GVariant => 1,
+    };
+    let endian = match context.endian() {
+        Endian::Little => 0,
+        Endian::Big => 1,
+    };
+
+    (context.position() % 8) as u32 | endian << 3 | format << 4
+}
+
+pub(crate) fn unpack_context(packed: u32) -> Context {
+    let format = match packed >> 4 & 1 {
+        #[cfg(feature = "gvariant")]
+        1 => Format::GVariant,
+        _ => Format::DBus,
+    };
+    let endian = match packed >> 3 & 1 {
+        1 => Endian::Big,
+        _ => Endian::Little,
+    };
+
+    Context::new(format, endian, (packed & 0b111) as usize)
+}
+
+/// A serializer that only accepts bytes, passing them to the function it holds.
+///
+/// Used by our serializers to get to the bytes of a [`RawValue`].
+pub(crate) struct RawBytesEmitter<F>(pub F);
+
+macro_rules! unsupported {
+    ($($method:ident($($arg:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ret> {
+                Err(Error::Message("expected the bytes of a `RawValue`".to_string()))
+            }
+        )*
+    };
+}
+
+impl<F> Serializer for RawBytesEmitter<F>
+where
+    F: FnOnce(&[u8]) -> Result<()>,
+{
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        (self.0)(v)
+    }
+
+    unsupported! {
+        serialize_bool(bool) -> ();
+        serialize_i8(i8) -> ();
+        serialize_i16(i16) -> ();
+        serialize_i32(i32) -> ();
+        serialize_i64(i64) -> ();
+        serialize_u8(u8) -> ();
+        serialize_u16(u16) -> ();
+        serialize_u32(u32) -> ();
+        serialize_u64(u64) -> ();
+        serialize_f32(f32) -> ();
+        serialize_f64(f64) -> ();
+        serialize_char(char) -> ();
+        serialize_str(&str) -> ();
+        serialize_none() -> ();
+        serialize_unit() -> ();
+        serialize_unit_struct(&'static str) -> ();
+        serialize_unit_variant(&'static str, u32, &'static str) -> ();
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::Message(
+            "expected the bytes of a `RawValue`".to_string(),
+        ))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::Message(
+            "expected the bytes of a `RawValue`".to_string(),
+        ))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::Message(
+            "expected the bytes of a `RawValue`".to_string(),
+        ))
+    }
+}
+
+/// Allows `Data` to be turned into a `RawValue` without any copies.
+impl<'d> TryFrom<&'d Data<'_, '_>> for RawValue<'d> {
+    type Error = Error;
+
+    /// The data is expected to be a variant, whose value becomes the `RawValue`.
+    fn try_from(data: &'d Data<'_, '_>) -> Result<Self> {
+        data.view(Value::signature())?.variant().map(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        serialized::{Context, Format},
+        to_bytes, Endian, RawValue, Value, BE, LE,
+    };
+
+    fn value() -> Value<'static> {
+        let dict = HashMap::from([("first", Value::from(7u64)), ("second", Value::from("two"))]);
+
+        Value::from((1u8, vec![1u64, 2, 3], dict, Value::from(42u32)))
+    }
+
+    // Decoding turns the nested variants into their values so we can't compare with the original.
+    fn decoded(ctxt: Context, value: &Value<'_>) -> Value<'static> {
+        let encoded = to_bytes(ctxt, value).unwrap();
+        let decoded: Value<'_> = encoded.deserialize().unwrap().0;
+
+        decoded.try_to_owned().unwrap().into()
+    }
+
+    fn forward(from: Context, to: Context) {
+        let value = value();
+        let encoded = to_bytes(from, &(1u8, &value)).unwrap();
+        let (byte, raw): (u8, RawValue<'_>) = encoded.deserialize().unwrap().0;
+        assert_eq!(byte, 1);
+        assert_eq!(raw.signature(), &value.value_signature());
+        assert_eq!(raw.to_value().unwrap(), decoded(from, &value));
+
+        let forwarded = to_bytes(to, &(raw, 2u16)).unwrap();
+        let expected = to_bytes(to, &(&value, 2u16)).unwrap();
+        assert_eq!(forwarded.bytes(), expected.bytes());
+    }
+
+    #[test]
+    fn dbus() {
+        forward(Context::new_dbus(LE, 0), Context::new_dbus(LE, 0));
+        // Different alignment.
+        forward(Context::new_dbus(LE, 0), Context::new_dbus(LE, 4));
+        forward(Context::new_dbus(LE, 3), Context::new_dbus(LE, 11));
+        // Different endianness.
+        forward(Context::new_dbus(LE, 0), Context::new_dbus(BE, 0));
+    }
+
+    #[cfg(feature = "gvariant")]
+    #[test]
+    fn gvariant() {
+        forward(Context::new_gvariant(LE, 0), Context::new_gvariant(LE, 0));
+        forward(Context::new_gvariant(BE, 1), Context::new_gvariant(LE, 0));
+        forward(Context::new_dbus(LE, 0), Context::new_gvariant(LE, 0));
+        forward(Context::new_gvariant(LE, 0), Context::new_dbus(BE, 0));
+    }
+
+    #[test]
+    fn from_value() {
+        let value = value();
+        let raw = RawValue::from_value(&value).unwrap();
+        assert_eq!(raw.context().format(), Format::DBus);
+        assert_eq!(raw.context().endian(), Endian::Little);
+        let expected = decoded(Context::new_dbus(LE, 0), &value);
+        assert_eq!(raw.to_value().unwrap(), expected);
+
+        let ctxt = Context::new_dbus(BE, 0);
+        let encoded = to_bytes(ctxt, &raw).unwrap();
+        let value: Value<'_> = encoded.deserialize().unwrap().0;
+        assert_eq!(value, expected);
+
+        let raw = RawValue::try_from(&encoded).unwrap();
+        assert_eq!(raw.to_value().unwrap(), expected);
+    }
+
+    #[test]
+    fn json() {
+        let raw = RawValue::from_value(&Value::from("hello")).unwrap();
+        let json = serde_json::to_string(&raw).unwrap();
+        assert_eq!(json, serde_json::to_string(&Value::from("hello")).unwrap());
+    }
+}
@@ -347,7 +347,7 @@ where
         Ok(padding)
     }
 
-    fn abs_pos(&self) -> usize {
+    pub(crate) fn abs_pos(&self) -> usize {
         self.ctxt.position() + self.bytes_written
     }
 }
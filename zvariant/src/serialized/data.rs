@@ -11,8 +11,8 @@ use serde::{de::DeserializeSeed, Deserialize};
 
 use crate::{
     de::Deserializer,
-    serialized::{Context, Format},
-    DynamicDeserialize, DynamicType, Error, Result, Signature, Type,
+    serialized::{swap_endian, validate::Validator, Context, Format, View},
+    DynamicDeserialize, DynamicType, Endian, Error, Result, Signature, Type,
 };
 
 /// Represents serialized bytes in a specific format.
@@ -328,6 +328,138 @@ impl<'bytes, 'fds> Data<'bytes, 'fds> {
             Deserializer::DBus(de, _) => (t, de.common.pos),
         })
     }
+
+    /// A lazily decoded [`View`] of `self`, with the given signature.
+    ///
+    /// Unlike the deserialization methods, this doesn't decode anything upfront. See [`View`] for
+    /// details.
+    ///
+    /// If `signature` consists of more than one complete type, they are treated as the fields of
+    /// a structure, the same way a message body is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zvariant::{serialized::Context, to_bytes, LE};
+    ///
+    /// let ctxt = Context::new_dbus(LE, 0);
+    /// let encoded = to_bytes(ctxt, &(vec!["hello", "world"], 42u32)).unwrap();
+    /// let view = encoded.view("asu").unwrap();
+    ///
+    /// let strings = view.field(0).unwrap().unwrap();
+    /// let second = strings.elements().unwrap().nth(1).unwrap().unwrap();
+    /// assert_eq!(second.deserialize::<&str>().unwrap(), "world");
+    /// ```
+    pub fn view<'d, S>(&'d self, signature: S) -> Result<View<'d>>
+    where
+        S: TryInto<Signature<'d>>,
+        S::Error: Into<Error>,
+    {
+        let mut signature = signature.try_into().map_err(Into::into)?;
+        if signature.n_complete_types()? > 1 {
+            signature = Signature::from_string_unchecked(format!("({signature})"));
+        }
+
+        View::new(
+            self.bytes(),
+            self.context,
+            #[cfg(unix)]
+            &self.inner.fds,
+            signature,
+        )
+    }
+
+    /// Check that `self` is a well-formed encoding of a value with the given signature.
+    ///
+    /// This walks the encoded data, checking alignment padding, strings, object paths,
+    /// signatures, array lengths, container depths and file descriptor indices, without decoding
+    /// any values or allocating. On success, the size of the encoded value is returned. On
+    /// failure, [`Error::InvalidEncoding`] reports the offset of the offending byte(s), relative to
+    /// the start of `self`.
+    ///
+    /// If `signature` consists of more than one complete type, they are treated as the fields of
+    /// a structure, the same way a message body is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zvariant::{serialized::{Context, Data}, to_bytes, Error, LE};
+    ///
+    /// let ctxt = Context::new_dbus(LE, 0);
+    /// let encoded = to_bytes(ctxt, &("hello", true)).unwrap();
+    /// assert_eq!(encoded.validate("sb").unwrap(), encoded.len());
+    ///
+    /// // Corrupt the boolean.
+    /// let mut bytes = encoded.bytes().to_vec();
+    /// bytes[12] = 2;
+    /// let corrupted = Data::new(bytes, ctxt);
+    /// assert!(matches!(corrupted.validate("sb"), Err(Error::InvalidEncoding(12, _))));
+    /// ```
+    pub fn validate<'s, S>(&self, signature: S) -> Result<usize>
+    where
+        S: TryInto<Signature<'s>>,
+        S::Error: Into<Error>,
+    {
+        let signature = signature.try_into().map_err(Into::into)?;
+
+        let validator = Validator::new(self.bytes(), self.context);
+        #[cfg(unix)]
+        let mut validator = validator.check_fds(self.inner.fds.len());
+        #[cfg(not(unix))]
+        let mut validator = validator;
+
+        validator.validate(&signature)
+    }
+
+    /// The same data, encoded in the given byte order.
+    ///
+    /// If the byte order is different, the data is converted using [`swap_endian`] into a new
+    /// buffer, and the file descriptors are duplicated. Otherwise, this is just a cheap clone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zvariant::{serialized::Context, to_bytes, BE, LE};
+    ///
+    /// let encoded = to_bytes(Context::new_dbus(BE, 0), &(42u32, "hello")).unwrap();
+    /// let converted = encoded.to_endian("us", LE).unwrap();
+    ///
+    /// assert_eq!(converted.context().endian(), LE);
+    /// assert_eq!(converted.deserialize::<(u32, &str)>().unwrap().0, (42, "hello"));
+    /// ```
+    pub fn to_endian<'s, S>(&self, signature: S, endian: Endian) -> Result<Data<'bytes, 'fds>>
+    where
+        S: TryInto<Signature<'s>>,
+        S::Error: Into<Error>,
+    {
+        if endian == self.context.endian() {
+            return Ok(self.clone());
+        }
+
+        let mut bytes = self.bytes().to_vec();
+        let context = swap_endian(&mut bytes, self.context, signature)?;
+        let range = Range {
+            start: 0,
+            end: bytes.len(),
+        };
+
+        Ok(Data {
+            inner: Arc::new(Inner {
+                bytes: Cow::Owned(bytes),
+                #[cfg(unix)]
+                fds: self
+                    .inner
+                    .fds
+                    .iter()
+                    .map(Fd::try_clone)
+                    .collect::<Result<_>>()?,
+                #[cfg(not(unix))]
+                _fds: std::marker::PhantomData,
+            }),
+            context,
+            range,
+        })
+    }
 }
 
 impl<'bytes> Data<'bytes, 'static> {
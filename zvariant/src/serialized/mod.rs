@@ -8,3 +8,8 @@ mod format;
 pub use format::Format;
 mod context;
 pub use context::Context;
+mod swap;
+pub use swap::swap_endian;
+mod validate;
+mod view;
+pub use view::{Elements, Entries, Fields, View};
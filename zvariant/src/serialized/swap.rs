@@ -0,0 +1,189 @@
+use crate::{
+    serialized::{validate::Validator, Context},
+    Endian, Error, Result, Signature,
+};
+
+/// Convert the encoding of a value with the given signature to the other byte order, in place.
+///
+/// `context` describes the current encoding of `bytes` and the context of the converted encoding
+/// is returned. Since only the integers (including lengths) change, this is a lot cheaper than
+/// decoding and re-encoding the value.
+///
+/// The data is validated as part of the conversion, the same way as [`Data::validate`] does,
+/// except for the file descriptor indices. If the data is not valid, it's left untouched.
+///
+/// If `signature` consists of more than one complete type, they are treated as the fields of a
+/// structure, the same way a message body is.
+///
+/// [`Data::validate`]: crate::serialized::Data::validate
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{serialized::{swap_endian, Context}, to_bytes, BE, LE};
+///
+/// let le = to_bytes(Context::new_dbus(LE, 0), &(vec![1u16, 2], "hello")).unwrap();
+/// let be = to_bytes(Context::new_dbus(BE, 0), &(vec![1u16, 2], "hello")).unwrap();
+///
+/// let mut bytes = le.bytes().to_vec();
+/// let ctxt = swap_endian(&mut bytes, le.context(), "aqs").unwrap();
+/// assert_eq!(ctxt, be.context());
+/// assert_eq!(bytes, be.bytes());
+/// ```
+pub fn swap_endian<'s, S>(bytes: &mut [u8], context: Context, signature: S) -> Result<Context>
+where
+    S: TryInto<Signature<'s>>,
+    S::Error: Into<Error>,
+{
+    let signature = signature.try_into().map_err(Into::into)?;
+    let swaps = Validator::new(bytes, context).swaps(&signature)?;
+    for range in swaps {
+        bytes[range].reverse();
+    }
+
+    let endian = match context.endian() {
+        Endian::Little => Endian::Big,
+        Endian::Big => Endian::Little,
+    };
+
+    Ok(Context::new(context.format(), endian, context.position()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::{distributions::Alphanumeric, thread_rng, Rng};
+    use serde::Serialize;
+
+    use super::swap_endian;
+    use crate::{
+        serialized::{Context, Format},
+        to_bytes, DynamicType, ObjectPath, Type, Value, BE, LE,
+    };
+
+    fn string(rng: &mut impl Rng) -> String {
+        let len = rng.gen_range(0..20);
+
+        rng.sample_iter(&Alphanumeric)
+            .take(len)
+            .map(char::from)
+            .collect()
+    }
+
+    fn value(rng: &mut impl Rng, depth: u8) -> Value<'static> {
+        match rng.gen_range(0..if depth < 3 { 8 } else { 5 }) {
+            0 => Value::from(rng.gen::<u8>()),
+            1 => Value::from(rng.gen::<i16>()),
+            2 => Value::from(rng.gen::<u64>()),
+            3 => Value::from(rng.gen::<f64>()),
+            4 => Value::from(string(rng)),
+            5 => Value::new(value(rng, depth + 1)),
+            6 => Value::from((rng.gen::<bool>(), rng.gen::<u32>(), string(rng))),
+            _ => Value::from(
+                (0..rng.gen_range(0..4))
+                    .map(|_| (string(rng), value(rng, depth + 1)))
+                    .collect::<HashMap<_, _>>(),
+            ),
+        }
+    }
+
+    type Item = (
+        u16,
+        bool,
+        i32,
+        String,
+        Vec<u64>,
+        f64,
+        HashMap<String, Value<'static>>,
+    );
+
+    fn items(rng: &mut impl Rng) -> Vec<Item> {
+        (0..rng.gen_range(0..8))
+            .map(|_| {
+                (
+                    rng.gen(),
+                    rng.gen(),
+                    rng.gen(),
+                    string(rng),
+                    (0..rng.gen_range(0..4)).map(|_| rng.gen()).collect(),
+                    rng.gen(),
+                    (0..rng.gen_range(0..4))
+                        .map(|_| (string(rng), value(rng, 0)))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn check<T>(format: Format, value: &T)
+    where
+        T: Serialize + DynamicType,
+    {
+        let signature = value.dynamic_signature();
+        let position = thread_rng().gen_range(0..8);
+        let le = to_bytes(Context::new(format, LE, position), value).unwrap();
+        let be = to_bytes(Context::new(format, BE, position), value).unwrap();
+
+        let mut bytes = le.bytes().to_vec();
+        let ctxt = swap_endian(&mut bytes, le.context(), &signature).unwrap();
+        assert_eq!(ctxt, be.context());
+        assert_eq!(bytes, be.bytes());
+
+        let ctxt = swap_endian(&mut bytes, ctxt, &signature).unwrap();
+        assert_eq!(ctxt, le.context());
+        assert_eq!(bytes, le.bytes());
+
+        let converted = be.to_endian(&signature, LE).unwrap();
+        assert_eq!(converted.context(), le.context());
+        assert_eq!(converted.bytes(), le.bytes());
+    }
+
+    #[test]
+    fn dbus() {
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            let items = items(&mut rng);
+            check(Format::DBus, &items);
+            check(Format::DBus, &(string(&mut rng), items, rng.gen::<u8>()));
+            check(Format::DBus, &value(&mut rng, 0));
+        }
+        check(
+            Format::DBus,
+            &ObjectPath::try_from("/org/zbus/Object").unwrap(),
+        );
+    }
+
+    #[cfg(feature = "gvariant")]
+    #[test]
+    fn gvariant() {
+        let mut rng = thread_rng();
+        for _ in 0..100 {
+            let items = items(&mut rng);
+            check(Format::GVariant, &items);
+            check(
+                Format::GVariant,
+                &(string(&mut rng), items, rng.gen::<u8>()),
+            );
+            check(Format::GVariant, &value(&mut rng, 0));
+            #[cfg(not(feature = "option-as-array"))]
+            check(
+                Format::GVariant,
+                &(
+                    rng.gen::<bool>().then(|| rng.gen::<u32>()),
+                    rng.gen::<bool>().then(|| string(&mut rng)),
+                ),
+            );
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let le = to_bytes(Context::new_dbus(LE, 0), &("hello", 42u32)).unwrap();
+        let mut bytes = le.bytes().to_vec();
+        bytes[5] = 0xff;
+        let copy = bytes.clone();
+        assert!(swap_endian(&mut bytes, le.context(), <(&str, u32)>::signature()).is_err());
+        assert_eq!(bytes, copy);
+    }
+}
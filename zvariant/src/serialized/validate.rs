@@ -0,0 +1,650 @@
+use std::{ops::Range, str};
+
+use serde::de::{self, Unexpected};
+
+#[cfg(unix)]
+use crate::Fd;
+use crate::{
+    container_depths::ContainerDepths,
+    serialized::{Context, Format},
+    signature_parser::SignatureParser,
+    utils::{
+        alignment_for_signature, padding_for_n_bytes, ARRAY_SIGNATURE_CHAR,
+        DICT_ENTRY_SIG_START_CHAR, STRUCT_SIG_START_CHAR, VARIANT_SIGNATURE_CHAR,
+    },
+    Basic, Error, ObjectPath, Result, Signature,
+};
+#[cfg(feature = "gvariant")]
+use crate::{
+    framing_offset_size::FramingOffsetSize, serialized::view::fixed_size,
+    utils::MAYBE_SIGNATURE_CHAR,
+};
+
+/// The maximum length of an array in bytes, as per the D-Bus specification.
+///
+/// Like for the container depths, we use the same limit for GVariant.
+const MAX_ARRAY_LEN: usize = 1 << 26;
+
+/// Checks encoded data against a signature, without decoding any values.
+///
+/// All offsets in the errors are relative to the start of `bytes`.
+pub(super) struct Validator<'d> {
+    bytes: &'d [u8],
+    context: Context,
+    /// The number of file descriptors, if the indices are to be checked.
+    #[cfg(unix)]
+    n_fds: Option<usize>,
+    /// The multi-byte integers encountered, if they're to be recorded.
+    swaps: Option<Vec<Range<usize>>>,
+}
+
+impl<'d> Validator<'d> {
+    pub(super) fn new(bytes: &'d [u8], context: Context) -> Self {
+        Self {
+            bytes,
+            context,
+            #[cfg(unix)]
+            n_fds: None,
+            swaps: None,
+        }
+    }
+
+    /// Check that the file descriptor indices are less than `n_fds`.
+    #[cfg(unix)]
+    pub(super) fn check_fds(mut self, n_fds: usize) -> Self {
+        self.n_fds = Some(n_fds);
+
+        self
+    }
+
+    /// Validate the data for `signature`, returning the ranges of all the multi-byte integers
+    /// (including lengths), which need to be byte-swapped to change the byte order.
+    pub(super) fn swaps(mut self, signature: &Signature<'_>) -> Result<Vec<Range<usize>>> {
+        self.swaps = Some(Vec::new());
+        self.validate(signature)?;
+
+        Ok(self.swaps.unwrap_or_default())
+    }
+
+    /// Validate the data for `signature`, returning the size of the encoded value.
+    ///
+    /// If `signature` consists of more than one complete type, they're treated as the fields of
+    /// a structure.
+    pub(super) fn validate(&mut self, signature: &Signature<'_>) -> Result<usize> {
+        let multiple = signature.n_complete_types()? > 1;
+        let depths = ContainerDepths::default();
+
+        match self.context.format() {
+            Format::DBus if multiple => {
+                let start = self.padding(0, alignment_for_signature(signature, Format::DBus)?)?;
+                let depths = depths.inc_structure().map_err(|e| invalid(start, e))?;
+
+                self.dbus_fields(start, signature, depths)
+            }
+            Format::DBus => self.dbus(0, signature, depths),
+            #[cfg(feature = "gvariant")]
+            Format::GVariant => {
+                let end = self.bytes.len();
+                let alignment = alignment_for_signature(signature, Format::GVariant)?;
+                let start = self.padding(0, alignment)?;
+                if multiple {
+                    let depths = depths.inc_structure().map_err(|e| invalid(start, e))?;
+                    self.gvariant_fields(start, end, signature, depths)?;
+                } else {
+                    self.gvariant(start, end, signature, depths)?;
+                }
+
+                Ok(end)
+            }
+        }
+    }
+
+    /// Validate a D-Bus encoded value, starting (before any padding) at `pos`.
+    ///
+    /// Returns the end of the value.
+    fn dbus(
+        &mut self,
+        pos: usize,
+        signature: &Signature<'_>,
+        depths: ContainerDepths,
+    ) -> Result<usize> {
+        let start = self.padding(pos, alignment_for_signature(signature, Format::DBus)?)?;
+        let endian = self.context.endian();
+
+        match first_char(signature)? {
+            u8::SIGNATURE_CHAR => self.slice(start, 1).map(|_| start + 1),
+            i16::SIGNATURE_CHAR | u16::SIGNATURE_CHAR => self.integer(start, 2).map(|_| start + 2),
+            i32::SIGNATURE_CHAR | u32::SIGNATURE_CHAR => self.integer(start, 4).map(|_| start + 4),
+            i64::SIGNATURE_CHAR | u64::SIGNATURE_CHAR | f64::SIGNATURE_CHAR => {
+                self.integer(start, 8).map(|_| start + 8)
+            }
+            bool::SIGNATURE_CHAR => {
+                let value = endian.read_u32(self.integer(start, 4)?);
+                self.bool(start, value)?;
+
+                Ok(start + 4)
+            }
+            #[cfg(unix)]
+            Fd::SIGNATURE_CHAR => {
+                let value = endian.read_u32(self.integer(start, 4)?);
+                self.fd(start, value)?;
+
+                Ok(start + 4)
+            }
+            c @ (<&str>::SIGNATURE_CHAR | ObjectPath::SIGNATURE_CHAR) => {
+                let len = endian.read_u32(self.integer(start, 4)?) as usize;
+
+                self.dbus_string(start + 4, len, c)
+            }
+            Signature::SIGNATURE_CHAR => {
+                let len = self.slice(start, 1)?[0] as usize;
+
+                self.dbus_string(start + 1, len, Signature::SIGNATURE_CHAR)
+            }
+            VARIANT_SIGNATURE_CHAR => {
+                let depths = depths.inc_variant().map_err(|e| invalid(start, e))?;
+                let len = self.slice(start, 1)?[0] as usize;
+                let value_start = self.dbus_string(start + 1, len, Signature::SIGNATURE_CHAR)?;
+                let signature = self.variant_signature(start + 1, self.slice(start + 1, len)?)?;
+
+                self.dbus(value_start, &signature, depths)
+            }
+            ARRAY_SIGNATURE_CHAR => {
+                let depths = depths.inc_array().map_err(|e| invalid(start, e))?;
+                let len = endian.read_u32(self.integer(start, 4)?) as usize;
+                self.array_len(start, len)?;
+
+                let element_signature = signature.slice(1..);
+                let alignment = alignment_for_signature(&element_signature, Format::DBus)?;
+                // The padding for the first element is there even if the array is empty.
+                let elements_start = self.padding(start + 4, alignment)?;
+                let end = elements_start + len;
+                self.slice(elements_start, len)?;
+
+                let mut pos = elements_start;
+                while pos < end {
+                    pos = self.dbus(pos, &element_signature, depths)?;
+                }
+                if pos != end {
+                    return Err(invalid(end, Error::OutOfBounds));
+                }
+
+                Ok(end)
+            }
+            STRUCT_SIG_START_CHAR | DICT_ENTRY_SIG_START_CHAR => {
+                let depths = depths.inc_structure().map_err(|e| invalid(start, e))?;
+                let fields_signature = signature.slice(1..signature.len() - 1);
+
+                self.dbus_fields(start, &fields_signature, depths)
+            }
+            _ => Err(invalid(
+                start,
+                Error::IncompatibleFormat(signature.to_owned(), Format::DBus),
+            )),
+        }
+    }
+
+    /// Validate the fields of a D-Bus encoded structure, starting at `start`.
+    fn dbus_fields(
+        &mut self,
+        start: usize,
+        fields_signature: &Signature<'_>,
+        depths: ContainerDepths,
+    ) -> Result<usize> {
+        let mut parser = SignatureParser::new(fields_signature.clone());
+        let mut pos = start;
+        while !parser.done() {
+            let signature = parser.parse_next_signature()?;
+            pos = self.dbus(pos, &signature, depths)?;
+        }
+
+        Ok(pos)
+    }
+
+    /// Validate a D-Bus encoded string of `len` bytes at `start`, followed by a nul byte.
+    fn dbus_string(&self, start: usize, len: usize, kind: char) -> Result<usize> {
+        let bytes = self.slice(start, len)?;
+        let nul = self.slice(start + len, 1)?[0];
+        if nul != 0 {
+            return Err(invalid(start + len, missing_nul(nul)));
+        }
+        self.string(start, bytes, kind)?;
+
+        Ok(start + len + 1)
+    }
+
+    /// Validate a GVariant encoded value, taking exactly `start..end`.
+    ///
+    /// `start` must already be aligned.
+    #[cfg(feature = "gvariant")]
+    fn gvariant(
+        &mut self,
+        start: usize,
+        end: usize,
+        signature: &Signature<'_>,
+        depths: ContainerDepths,
+    ) -> Result<()> {
+        let endian = self.context.endian();
+        let bytes = self.slice(start, end - start)?;
+        if let Some(size) = fixed_size(signature)? {
+            if bytes.len() != size {
+                return Err(invalid(
+                    start,
+                    de::Error::invalid_length(bytes.len(), &format!("{size} bytes").as_str()),
+                ));
+            }
+        }
+
+        match first_char(signature)? {
+            u8::SIGNATURE_CHAR
+            | i16::SIGNATURE_CHAR
+            | u16::SIGNATURE_CHAR
+            | i32::SIGNATURE_CHAR
+            | u32::SIGNATURE_CHAR
+            | i64::SIGNATURE_CHAR
+            | u64::SIGNATURE_CHAR
+            | f64::SIGNATURE_CHAR => self.integer(start, bytes.len()).map(drop),
+            bool::SIGNATURE_CHAR => {
+                self.integer(start, 4)?;
+
+                self.bool(start, endian.read_u32(bytes))
+            }
+            #[cfg(unix)]
+            Fd::SIGNATURE_CHAR => {
+                self.integer(start, 4)?;
+
+                self.fd(start, endian.read_u32(bytes))
+            }
+            c @ (<&str>::SIGNATURE_CHAR
+            | ObjectPath::SIGNATURE_CHAR
+            | Signature::SIGNATURE_CHAR) => match bytes.split_last() {
+                Some((0, string)) => self.string(start, string, c),
+                Some((nul, _)) => Err(invalid(end - 1, missing_nul(*nul))),
+                None => Err(invalid(start, Error::OutOfBounds)),
+            },
+            VARIANT_SIGNATURE_CHAR => {
+                let depths = depths.inc_variant().map_err(|e| invalid(start, e))?;
+                // The signature comes last, after a nul byte separator.
+                let separator = bytes[..bytes.len().saturating_sub(1)]
+                    .iter()
+                    .rposition(|b| *b == 0)
+                    .map(|i| start + i)
+                    .ok_or_else(|| invalid(start, Error::OutOfBounds))?;
+                let signature =
+                    self.variant_signature(separator + 1, &self.bytes[separator + 1..end])?;
+
+                self.gvariant(start, separator, &signature, depths)
+            }
+            ARRAY_SIGNATURE_CHAR => {
+                let depths = depths.inc_array().map_err(|e| invalid(start, e))?;
+                self.array_len(start, bytes.len())?;
+
+                let element_signature = signature.slice(1..);
+                let alignment = alignment_for_signature(&element_signature, Format::GVariant)?;
+                if let Some(size) = fixed_size(&element_signature)? {
+                    let mut pos = start;
+                    while pos < end {
+                        let element_start = self.padding(pos, alignment)?;
+                        let element_end = element_start + size;
+                        if element_end > end {
+                            return Err(invalid(element_start, Error::OutOfBounds));
+                        }
+                        self.gvariant(element_start, element_end, &element_signature, depths)?;
+                        pos = element_end;
+                    }
+
+                    return Ok(());
+                }
+                if bytes.is_empty() {
+                    return Ok(());
+                }
+
+                let offset_size = FramingOffsetSize::for_encoded_container(bytes.len());
+                let size = offset_size as usize;
+                let offsets_start = start + offset_size.read_last_offset_from_buffer(bytes);
+                if bytes.len() < size || offsets_start > end || (end - offsets_start) % size != 0 {
+                    return Err(invalid(
+                        end.saturating_sub(size),
+                        Error::MissingFramingOffset,
+                    ));
+                }
+
+                let mut pos = start;
+                for offset in (offsets_start..end).step_by(size) {
+                    let element_end =
+                        start + offset_size.read_last_offset_from_buffer(self.slice(offset, size)?);
+                    let element_start = self.padding(pos, alignment)?;
+                    if element_end < element_start || element_end > offsets_start {
+                        return Err(invalid(offset, Error::OutOfBounds));
+                    }
+                    self.gvariant(element_start, element_end, &element_signature, depths)?;
+                    pos = element_end;
+                }
+
+                Ok(())
+            }
+            STRUCT_SIG_START_CHAR | DICT_ENTRY_SIG_START_CHAR => {
+                let depths = depths.inc_structure().map_err(|e| invalid(start, e))?;
+                let fields_signature = signature.slice(1..signature.len() - 1);
+
+                self.gvariant_fields(start, end, &fields_signature, depths)
+            }
+            MAYBE_SIGNATURE_CHAR => {
+                #[cfg(not(feature = "option-as-array"))]
+                let depths = depths.inc_maybe().map_err(|e| invalid(start, e))?;
+                let inner_signature = signature.slice(1..);
+                if bytes.is_empty() {
+                    return Ok(());
+                }
+
+                match fixed_size(&inner_signature)? {
+                    Some(_) => self.gvariant(start, end, &inner_signature, depths),
+                    // Non-fixed-sized values are followed by a nul byte.
+                    None => match bytes[bytes.len() - 1] {
+                        0 => self.gvariant(start, end - 1, &inner_signature, depths),
+                        nul => Err(invalid(end - 1, missing_nul(nul))),
+                    },
+                }
+            }
+            _ => Err(invalid(
+                start,
+                Error::IncompatibleFormat(signature.to_owned(), Format::GVariant),
+            )),
+        }
+    }
+
+    /// Validate the fields of a GVariant encoded structure, taking exactly `start..end`.
+    #[cfg(feature = "gvariant")]
+    fn gvariant_fields(
+        &mut self,
+        start: usize,
+        end: usize,
+        fields_signature: &Signature<'_>,
+        depths: ContainerDepths,
+    ) -> Result<()> {
+        let offset_size = FramingOffsetSize::for_encoded_container(end - start);
+        let size = offset_size as usize;
+        let mut parser = SignatureParser::new(fields_signature.clone());
+        let mut pos = start;
+        // The framing offsets of the non-fixed-sized fields are at the end, in reverse order.
+        let mut fields_end = end;
+        while !parser.done() {
+            let signature = parser.parse_next_signature()?;
+            let alignment = alignment_for_signature(&signature, Format::GVariant)?;
+            let field_start = self.padding(pos, alignment)?;
+            let field_end = match fixed_size(&signature)? {
+                Some(size) => field_start + size,
+                // The last field doesn't get a framing offset.
+                None if parser.done() => fields_end,
+                None => {
+                    if fields_end < start + size {
+                        return Err(invalid(start, Error::MissingFramingOffset));
+                    }
+                    fields_end -= size;
+                    let offset = self.slice(fields_end, size)?;
+
+                    start + offset_size.read_last_offset_from_buffer(offset)
+                }
+            };
+            if field_end < field_start || field_end > fields_end {
+                return Err(invalid(field_start, Error::OutOfBounds));
+            }
+            self.gvariant(field_start, field_end, &signature, depths)?;
+            pos = field_end;
+        }
+        if pos != fields_end {
+            return Err(invalid(pos, Error::OutOfBounds));
+        }
+
+        Ok(())
+    }
+
+    /// Validate the contents of a string of type `kind` (string, object path or signature), not
+    /// including the trailing nul byte.
+    fn string(&self, start: usize, bytes: &[u8], kind: char) -> Result<()> {
+        if let Some(i) = bytes.iter().position(|b| *b == 0) {
+            return Err(invalid(
+                start + i,
+                de::Error::invalid_value(Unexpected::Char('\0'), &"no interior nul bytes"),
+            ));
+        }
+        let s =
+            str::from_utf8(bytes).map_err(|e| invalid(start + e.valid_up_to(), Error::Utf8(e)))?;
+
+        match kind {
+            ObjectPath::SIGNATURE_CHAR => ObjectPath::try_from(s).map(drop),
+            Signature::SIGNATURE_CHAR => Signature::try_from(s).map(drop),
+            _ => Ok(()),
+        }
+        .map_err(|e| invalid(start, e))
+    }
+
+    /// Parse the signature of a variant, which must be a single complete type.
+    fn variant_signature<'s>(&self, start: usize, bytes: &'s [u8]) -> Result<Signature<'s>> {
+        let signature = Signature::try_from(bytes).map_err(|e| invalid(start, e))?;
+        let n_types = signature
+            .n_complete_types()
+            .map_err(|e| invalid(start, e))?;
+        if n_types != 1 {
+            return Err(invalid(
+                start,
+                de::Error::invalid_length(n_types, &"a single complete type"),
+            ));
+        }
+
+        Ok(signature)
+    }
+
+    fn bool(&self, start: usize, value: u32) -> Result<()> {
+        // As per D-Bus spec, only 0 and 1 values are allowed
+        if value > 1 {
+            return Err(invalid(
+                start,
+                de::Error::invalid_value(Unexpected::Unsigned(value as u64), &"0 or 1"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn fd(&self, start: usize, index: u32) -> Result<()> {
+        if self.n_fds.is_some_and(|n_fds| index as usize >= n_fds) {
+            return Err(invalid(start, Error::UnknownFd));
+        }
+
+        Ok(())
+    }
+
+    fn array_len(&self, start: usize, len: usize) -> Result<()> {
+        if len > MAX_ARRAY_LEN {
+            return Err(invalid(
+                start,
+                de::Error::invalid_length(len, &format!("<= {MAX_ARRAY_LEN}").as_str()),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Check the padding for `alignment` at `pos`, returning the aligned position.
+    fn padding(&self, pos: usize, alignment: usize) -> Result<usize> {
+        let padding = padding_for_n_bytes(self.context.position() + pos, alignment);
+        let bytes = self.slice(pos, padding)?;
+        if let Some(i) = bytes.iter().position(|b| *b != 0) {
+            return Err(invalid(pos + i, Error::PaddingNot0(bytes[i])));
+        }
+
+        Ok(pos + padding)
+    }
+
+    /// The bytes of an integer of `size` bytes at `start`, recording it for swapping.
+    fn integer(&mut self, start: usize, size: usize) -> Result<&'d [u8]> {
+        let bytes = self.slice(start, size)?;
+        if let Some(swaps) = &mut self.swaps {
+            if size > 1 {
+                swaps.push(start..start + size);
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    fn slice(&self, start: usize, len: usize) -> Result<&'d [u8]> {
+        start
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(start..end))
+            .ok_or_else(|| invalid(start, Error::OutOfBounds))
+    }
+}
+
+fn first_char(signature: &Signature<'_>) -> Result<char> {
+    signature
+        .as_bytes()
+        .first()
+        .map(|b| *b as char)
+        .ok_or_else(|| de::Error::invalid_length(0, &">= 1 character"))
+}
+
+fn missing_nul(byte: u8) -> Error {
+    de::Error::invalid_value(Unexpected::Unsigned(byte as u64), &"a nul byte")
+}
+
+fn invalid(offset: usize, error: Error) -> Error {
+    Error::InvalidEncoding(offset, Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        serialized::{Context, Data},
+        to_bytes, Error, MaxDepthExceeded, ObjectPath, Result, Value, LE,
+    };
+
+    type Item<'a> = (ObjectPath<'a>, bool, Vec<u16>, HashMap<&'a str, Value<'a>>);
+
+    fn items() -> Vec<Item<'static>> {
+        (0..5u16)
+            .map(|i| {
+                let path = ObjectPath::try_from("/org/zbus/Object").unwrap();
+                let props = HashMap::from([
+                    ("Index", Value::from(i)),
+                    ("Nested", Value::new(Value::from("variant"))),
+                ]);
+
+                (path, i % 2 == 0, vec![i; i as usize], props)
+            })
+            .collect()
+    }
+
+    fn invalid_at(result: Result<usize>) -> (usize, Error) {
+        match result {
+            Err(Error::InvalidEncoding(offset, e)) => (offset, *e),
+            r => panic!("expected an invalid encoding error, got {r:?}"),
+        }
+    }
+
+    #[test]
+    fn dbus() {
+        let ctxt = Context::new_dbus(LE, 0);
+        let encoded = to_bytes(ctxt, &(items(), "end", 7u8)).unwrap();
+        assert_eq!(encoded.validate("a(obaqa{sv})sy").unwrap(), encoded.len());
+        let encoded = to_bytes(ctxt, &items()).unwrap();
+        assert_eq!(encoded.validate("a(obaqa{sv})").unwrap(), encoded.len());
+
+        // Truncated data.
+        let truncated = encoded.slice(..encoded.len() - 1);
+        let (_, e) = invalid_at(truncated.validate("a(obaqa{sv})"));
+        assert!(matches!(e, Error::OutOfBounds));
+
+        // The string's length is at 0..4, followed by "hello\0", padding and then the bool at 12.
+        let encoded = to_bytes(ctxt, &("hello", true)).unwrap();
+        let corrupt = |i: usize, byte: u8| {
+            let mut bytes = encoded.bytes().to_vec();
+            bytes[i] = byte;
+            Data::new(bytes, ctxt).validate("sb")
+        };
+        assert_eq!(invalid_at(corrupt(10, 1)), (10, Error::PaddingNot0(1)));
+        assert!(matches!(invalid_at(corrupt(5, 0xff)), (5, Error::Utf8(_))));
+        assert!(matches!(invalid_at(corrupt(6, 0)), (6, Error::Message(_))));
+        assert!(matches!(
+            invalid_at(corrupt(9, b'!')),
+            (9, Error::Message(_))
+        ));
+        assert!(matches!(
+            invalid_at(corrupt(12, 2)),
+            (12, Error::Message(_))
+        ));
+
+        // "/a-b" is not a valid object path.
+        let encoded = to_bytes(ctxt, &ObjectPath::try_from("/a/b").unwrap()).unwrap();
+        let mut bytes = encoded.bytes().to_vec();
+        bytes[6] = b'-';
+        let (offset, _) = invalid_at(Data::new(bytes, ctxt).validate("o"));
+        assert_eq!(offset, 4);
+
+        // Array length above the limit.
+        let mut bytes = to_bytes(ctxt, &Vec::<u8>::new()).unwrap().bytes().to_vec();
+        bytes[..4].copy_from_slice(&(1u32 << 27).to_le_bytes());
+        let (offset, _) = invalid_at(Data::new(bytes, ctxt).validate("ay"));
+        assert_eq!(offset, 0);
+
+        // Variant signatures must be a single complete type.
+        let bytes = vec![2, b'y', b'y', 0, 1, 2];
+        let (offset, _) = invalid_at(Data::new(bytes, ctxt).validate("v"));
+        assert_eq!(offset, 1);
+
+        // Too many nested variants.
+        let mut bytes = [1, b'v', 0].repeat(70);
+        bytes.extend([1, b'y', 0, 7]);
+        let (offset, e) = invalid_at(Data::new(bytes, ctxt).validate("v"));
+        assert_eq!(offset, 64 * 3);
+        assert_eq!(e, Error::MaxDepthExceeded(MaxDepthExceeded::Container));
+
+        // No file descriptors to refer to.
+        #[cfg(unix)]
+        {
+            let encoded = to_bytes(ctxt, &0u32).unwrap();
+            assert_eq!(invalid_at(encoded.validate("h")), (0, Error::UnknownFd));
+        }
+    }
+
+    #[cfg(feature = "gvariant")]
+    #[test]
+    fn gvariant() {
+        let ctxt = Context::new_gvariant(LE, 0);
+        let encoded = to_bytes(ctxt, &(items(), "end", 7u8)).unwrap();
+        assert_eq!(encoded.validate("a(obaqa{sv})sy").unwrap(), encoded.len());
+        let encoded = to_bytes(ctxt, &items()).unwrap();
+        assert_eq!(encoded.validate("a(obaqa{sv})").unwrap(), encoded.len());
+        #[cfg(not(feature = "option-as-array"))]
+        {
+            let encoded = to_bytes(ctxt, &(Some("maybe"), None::<i64>, Some(3u32))).unwrap();
+            assert_eq!(encoded.validate("(msmxmu)").unwrap(), encoded.len());
+        }
+
+        // A framing offset pointing past the end.
+        let mut bytes = encoded.bytes().to_vec();
+        let last = bytes.len() - 1;
+        bytes[last] = 0xff;
+        assert!(Data::new(bytes, ctxt).validate("a(obaqa{sv})").is_err());
+
+        // "hello\0" is at 0..6, followed by padding, the bool at 8 and the framing offset.
+        let encoded = to_bytes(ctxt, &("hello", true)).unwrap();
+        assert_eq!(encoded.validate("(sb)").unwrap(), encoded.len());
+        let corrupt = |i: usize, byte: u8| {
+            let mut bytes = encoded.bytes().to_vec();
+            bytes[i] = byte;
+            Data::new(bytes, ctxt).validate("(sb)")
+        };
+        assert_eq!(invalid_at(corrupt(6, 1)), (6, Error::PaddingNot0(1)));
+        assert!(matches!(invalid_at(corrupt(1, 0xff)), (1, Error::Utf8(_))));
+        assert!(matches!(
+            invalid_at(corrupt(5, b'!')),
+            (5, Error::Message(_))
+        ));
+        assert!(matches!(invalid_at(corrupt(8, 2)), (8, Error::Message(_))));
+    }
+}
@@ -0,0 +1,799 @@
+#[cfg(unix)]
+use crate::Fd;
+use std::marker::PhantomData;
+
+use serde::de::{Deserialize, DeserializeSeed};
+
+use crate::{
+    container_depths::ContainerDepths,
+    de::Deserializer,
+    serialized::{Context, Format},
+    signature_parser::SignatureParser,
+    utils::{
+        alignment_for_signature, padding_for_n_bytes, subslice, ARRAY_SIGNATURE_CHAR,
+        DICT_ENTRY_SIG_START_CHAR, STRUCT_SIG_START_CHAR, VARIANT_SIGNATURE_CHAR,
+        VARIANT_SIGNATURE_STR,
+    },
+    value::value_seed,
+    Basic, Error, ObjectPath, Result, Signature, Type, Value,
+};
+#[cfg(feature = "gvariant")]
+use crate::{framing_offset_size::FramingOffsetSize, utils::is_fixed_sized_signature};
+
+/// A lazily decoded view of a value in serialized [`Data`].
+///
+/// A view only knows where the encoded value is and what its signature is. Nothing gets decoded
+/// until asked for: containers can be walked through [`View::elements`], [`View::fields`],
+/// [`View::entries`], [`View::get`] and [`View::variant`], each of which gives out views of the
+/// children, borrowing the same bytes. Only the leaves you actually need are then deserialized,
+/// through [`View::deserialize`] or [`View::to_value`].
+///
+/// This makes it cheap to pick a few items out of large messages, in both D-Bus and GVariant
+/// formats.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use zvariant::{serialized::Context, to_bytes, ObjectPath, OwnedObjectPath, LE};
+///
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let objects: Vec<(OwnedObjectPath, HashMap<&str, u32>)> = (0..100)
+///     .map(|i| {
+///         let path = OwnedObjectPath::try_from(format!("/org/zbus/{i}")).unwrap();
+///         (path, HashMap::from([("Index", i)]))
+///     })
+///     .collect();
+/// let encoded = to_bytes(ctxt, &objects).unwrap();
+///
+/// let view = encoded.view("a(oa{su})").unwrap();
+/// let object = view.elements().unwrap().nth(42).unwrap().unwrap();
+/// let path: ObjectPath<'_> = object.field(0).unwrap().unwrap().deserialize().unwrap();
+/// assert_eq!(path, "/org/zbus/42");
+///
+/// let properties = object.field(1).unwrap().unwrap();
+/// let index: u32 = properties.get("Index").unwrap().unwrap().deserialize().unwrap();
+/// assert_eq!(index, 42);
+/// ```
+///
+/// [`Data`]: crate::serialized::Data
+#[derive(Clone, Debug)]
+pub struct View<'d> {
+    bytes: &'d [u8],
+    context: Context,
+    #[cfg(unix)]
+    fds: &'d [Fd<'d>],
+    signature: Signature<'d>,
+}
+
+impl<'d> View<'d> {
+    /// Create a view of the value of `signature`, encoded at the start of `bytes`.
+    pub(crate) fn new(
+        bytes: &'d [u8],
+        context: Context,
+        #[cfg(unix)] fds: &'d [Fd<'d>],
+        signature: Signature<'d>,
+    ) -> Result<Self> {
+        let view = View {
+            bytes,
+            context,
+            #[cfg(unix)]
+            fds,
+            signature,
+        };
+        let start = view.align(0, &view.signature)?;
+        let end = match context.format() {
+            Format::DBus => view.dbus_value_end(start, &view.signature, Default::default())?,
+            #[cfg(feature = "gvariant")]
+            Format::GVariant => bytes.len().max(start),
+        };
+
+        view.child(start, end, view.signature.clone())
+    }
+
+    /// The signature of the value.
+    pub fn signature(&self) -> &Signature<'d> {
+        &self.signature
+    }
+
+    /// The encoded bytes of the value.
+    pub fn bytes(&self) -> &'d [u8] {
+        self.bytes
+    }
+
+    /// The encoding context of the value.
+    ///
+    /// The position of the context is that of the first byte of the value.
+    pub fn context(&self) -> Context {
+        self.context
+    }
+
+    /// Deserialize the value as `T`.
+    ///
+    /// The signature of `T` must be the same as that of the value.
+    pub fn deserialize<T>(&self) -> Result<T>
+    where
+        T: Deserialize<'d> + Type,
+    {
+        let signature = T::signature();
+        if signature != self.signature {
+            return Err(Error::SignatureMismatch(
+                self.signature.to_owned(),
+                format!("`{signature}`"),
+            ));
+        }
+
+        self.deserialize_with_seed(PhantomData)
+    }
+
+    /// Deserialize the value as a [`Value`].
+    pub fn to_value(&self) -> Result<Value<'d>> {
+        // Like when deserializing a `Value` directly, a variant gives the value it contains.
+        if self.signature == VARIANT_SIGNATURE_STR {
+            return self.deserialize_with_seed(PhantomData);
+        }
+
+        self.deserialize_with_seed(value_seed(self.signature.clone()))
+    }
+
+    /// An iterator over the elements of an array.
+    ///
+    /// For dictionaries, the elements are the dictionary entries. See also [`View::entries`].
+    pub fn elements(&self) -> Result<Elements<'d>> {
+        if self.signature.as_bytes().first() != Some(&(ARRAY_SIGNATURE_CHAR as u8)) {
+            return Err(Error::SignatureMismatch(
+                self.signature.to_owned(),
+                "an array".to_string(),
+            ));
+        }
+        let element_signature = self.signature.slice(1..);
+
+        let (pos, end, framing) = match self.context.format() {
+            Format::DBus => {
+                let len = self.context.endian().read_u32(subslice(self.bytes, 0..4)?) as usize;
+                let start = self.align(4, &element_signature)?;
+                let end = start.checked_add(len).ok_or(Error::OutOfBounds)?;
+                if end > self.bytes.len() {
+                    return Err(Error::OutOfBounds);
+                }
+
+                (start, end, Framing::Walk)
+            }
+            #[cfg(feature = "gvariant")]
+            Format::GVariant => {
+                let len = self.bytes.len();
+                match fixed_size(&element_signature)? {
+                    Some(size) => (0, len, Framing::Fixed(size)),
+                    None if len == 0 => (0, 0, Framing::Walk),
+                    None => {
+                        let size = FramingOffsetSize::for_encoded_container(len);
+                        let offsets_start = size.read_last_offset_from_buffer(self.bytes);
+                        if offsets_start > len || (len - offsets_start) % size as usize != 0 {
+                            return Err(Error::MissingFramingOffset);
+                        }
+
+                        (
+                            0,
+                            offsets_start,
+                            Framing::Offsets {
+                                size,
+                                next: offsets_start,
+                            },
+                        )
+                    }
+                }
+            }
+        };
+
+        Ok(Elements {
+            array: self.clone(),
+            element_signature,
+            pos,
+            end,
+            framing,
+            done: false,
+        })
+    }
+
+    /// An iterator over the fields of a structure or a dictionary entry.
+    pub fn fields(&self) -> Result<Fields<'d>> {
+        let first = self.signature.as_bytes().first().map(|b| *b as char);
+        if first != Some(STRUCT_SIG_START_CHAR) && first != Some(DICT_ENTRY_SIG_START_CHAR) {
+            return Err(Error::SignatureMismatch(
+                self.signature.to_owned(),
+                "a structure or a dictionary entry".to_string(),
+            ));
+        }
+        let parser = SignatureParser::new(self.signature.slice(1..self.signature.len() - 1));
+
+        Ok(Fields {
+            parent: self.clone(),
+            parser,
+            pos: 0,
+            end: self.bytes.len(),
+            #[cfg(feature = "gvariant")]
+            offset_size: FramingOffsetSize::for_encoded_container(self.bytes.len()),
+            done: false,
+        })
+    }
+
+    /// The field at `index` of a structure or a dictionary entry.
+    ///
+    /// Returns `None` if there are fewer fields than that.
+    pub fn field(&self, index: usize) -> Result<Option<View<'d>>> {
+        self.fields()?.nth(index).transpose()
+    }
+
+    /// An iterator over the key-value pairs of a dictionary.
+    pub fn entries(&self) -> Result<Entries<'d>> {
+        if self.signature.as_bytes().get(1) != Some(&(DICT_ENTRY_SIG_START_CHAR as u8)) {
+            return Err(Error::SignatureMismatch(
+                self.signature.to_owned(),
+                "a dictionary".to_string(),
+            ));
+        }
+
+        self.elements().map(|elements| Entries { elements })
+    }
+
+    /// The value for `key` in a dictionary.
+    ///
+    /// The keys are deserialized one by one and compared against `key` until a match is found, so
+    /// this is a linear search. The values are never decoded.
+    pub fn get<K>(&self, key: K) -> Result<Option<View<'d>>>
+    where
+        K: Deserialize<'d> + Type + PartialEq,
+    {
+        for entry in self.entries()? {
+            let (k, v) = entry?;
+            if k.deserialize::<K>()? == key {
+                return Ok(Some(v));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The value contained in a variant.
+    pub fn variant(&self) -> Result<View<'d>> {
+        if self.signature != VARIANT_SIGNATURE_STR {
+            return Err(Error::SignatureMismatch(
+                self.signature.to_owned(),
+                format!("`{VARIANT_SIGNATURE_STR}`"),
+            ));
+        }
+
+        match self.context.format() {
+            Format::DBus => {
+                let (signature, start) = self.dbus_variant_signature(0)?;
+                let end = self.dbus_value_end(start, &signature, Default::default())?;
+
+                self.child(start, end, signature)
+            }
+            #[cfg(feature = "gvariant")]
+            Format::GVariant => {
+                // The signature comes last, after a nul byte separator.
+                let len = self.bytes.len();
+                let separator = self.bytes[..len.saturating_sub(1)]
+                    .iter()
+                    .rposition(|b| *b == b'\0')
+                    .ok_or(Error::OutOfBounds)?;
+                let signature = Signature::try_from(&self.bytes[separator + 1..])?;
+
+                self.child(0, separator, signature)
+            }
+        }
+    }
+
+    /// The value contained in a GVariant maybe, if any.
+    ///
+    /// This method is only available with the `gvariant` feature.
+    #[cfg(feature = "gvariant")]
+    pub fn maybe(&self) -> Result<Option<View<'d>>> {
+        use crate::utils::MAYBE_SIGNATURE_CHAR;
+
+        if self.signature.as_bytes().first() != Some(&(MAYBE_SIGNATURE_CHAR as u8)) {
+            return Err(Error::SignatureMismatch(
+                self.signature.to_owned(),
+                "a maybe".to_string(),
+            ));
+        }
+        if self.context.format() != Format::GVariant {
+            return Err(Error::IncompatibleFormat(
+                self.signature.to_owned(),
+                self.context.format(),
+            ));
+        }
+        if self.bytes.is_empty() {
+            return Ok(None);
+        }
+
+        let signature = self.signature.slice(1..);
+        let end = match fixed_size(&signature)? {
+            Some(_) => self.bytes.len(),
+            // Non-fixed-sized values are followed by a nul byte.
+            None => self.bytes.len() - 1,
+        };
+
+        self.child(0, end, signature).map(Some)
+    }
+
+    fn deserialize_with_seed<S>(&self, seed: S) -> Result<S::Value>
+    where
+        S: DeserializeSeed<'d>,
+    {
+        let signature = self.signature.clone();
+        let mut de = match self.context.format() {
+            #[cfg(feature = "gvariant")]
+            Format::GVariant => {
+                #[cfg(unix)]
+                {
+                    crate::gvariant::Deserializer::new(
+                        self.bytes,
+                        Some(self.fds),
+                        signature,
+                        self.context,
+                    )
+                }
+                #[cfg(not(unix))]
+                {
+                    crate::gvariant::Deserializer::<()>::new(self.bytes, signature, self.context)
+                }
+            }
+            .map(Deserializer::GVariant)?,
+            Format::DBus => {
+                #[cfg(unix)]
+                {
+                    crate::dbus::Deserializer::new(
+                        self.bytes,
+                        Some(self.fds),
+                        signature,
+                        self.context,
+                    )
+                }
+                #[cfg(not(unix))]
+                {
+                    crate::dbus::Deserializer::<()>::new(self.bytes, signature, self.context)
+                }
+            }
+            .map(|de| Deserializer::DBus(de, PhantomData))?,
+        };
+
+        seed.deserialize(&mut de)
+    }
+
+    /// A view of `self.bytes[start..end]`.
+    fn child(&self, start: usize, end: usize, signature: Signature<'d>) -> Result<View<'d>> {
+        let context = Context::new(
+            self.context.format(),
+            self.context.endian(),
+            self.context.position() + start,
+        );
+
+        Ok(View {
+            bytes: subslice(self.bytes, start..end)?,
+            context,
+            #[cfg(unix)]
+            fds: self.fds,
+            signature,
+        })
+    }
+
+    /// The offset at which a value of `signature` would start, after `offset`.
+    fn align(&self, offset: usize, signature: &Signature<'_>) -> Result<usize> {
+        let alignment = alignment_for_signature(signature, self.context.format())?;
+
+        Ok(offset + padding_for_n_bytes(self.context.position() + offset, alignment))
+    }
+
+    /// The signature of a D-Bus variant at `offset` and the offset of its value.
+    fn dbus_variant_signature(&self, offset: usize) -> Result<(Signature<'d>, usize)> {
+        let len = *subslice(self.bytes, offset)? as usize;
+        let bytes = subslice(self.bytes, offset + 1..offset + 1 + len)?;
+        let signature = Signature::try_from(bytes)?;
+        // Skip the signature's trailing nul byte.
+        let start = self.align(offset + len + 2, &signature)?;
+
+        Ok((signature, start))
+    }
+
+    /// The end of a D-Bus encoded value of `signature`, starting (after any padding) at `start`.
+    ///
+    /// This only looks at the lengths encoded in the data, none of the actual values are decoded.
+    fn dbus_value_end(
+        &self,
+        start: usize,
+        signature: &Signature<'_>,
+        depths: ContainerDepths,
+    ) -> Result<usize> {
+        let endian = self.context.endian();
+        let c = *subslice(signature.as_bytes(), 0)? as char;
+        let end = match c {
+            u8::SIGNATURE_CHAR => start + 1,
+            i16::SIGNATURE_CHAR | u16::SIGNATURE_CHAR => start + 2,
+            bool::SIGNATURE_CHAR | i32::SIGNATURE_CHAR | u32::SIGNATURE_CHAR => start + 4,
+            #[cfg(unix)]
+            Fd::SIGNATURE_CHAR => start + 4,
+            i64::SIGNATURE_CHAR | u64::SIGNATURE_CHAR | f64::SIGNATURE_CHAR => start + 8,
+            <&str>::SIGNATURE_CHAR | ObjectPath::SIGNATURE_CHAR => {
+                let len = endian.read_u32(subslice(self.bytes, start..start + 4)?) as usize;
+
+                // Length, string and the trailing nul byte.
+                start + 4 + len + 1
+            }
+            Signature::SIGNATURE_CHAR => start + 1 + *subslice(self.bytes, start)? as usize + 1,
+            VARIANT_SIGNATURE_CHAR => {
+                let depths = depths.inc_variant()?;
+                let (signature, value_start) = self.dbus_variant_signature(start)?;
+
+                self.dbus_value_end(value_start, &signature, depths)?
+            }
+            ARRAY_SIGNATURE_CHAR => {
+                let len = endian.read_u32(subslice(self.bytes, start..start + 4)?) as usize;
+                let elements_start = self.align(start + 4, &signature.slice(1..))?;
+
+                elements_start + len
+            }
+            STRUCT_SIG_START_CHAR | DICT_ENTRY_SIG_START_CHAR => {
+                let depths = depths.inc_structure()?;
+                let mut parser = SignatureParser::new(signature.slice(1..signature.len() - 1));
+                let mut end = start;
+                while !parser.done() {
+                    let field_signature = parser.parse_next_signature()?;
+                    let field_start = self.align(end, &field_signature)?;
+                    end = self.dbus_value_end(field_start, &field_signature, depths)?;
+                }
+
+                end
+            }
+            _ => {
+                return Err(Error::IncompatibleFormat(
+                    signature.to_owned(),
+                    self.context.format(),
+                ))
+            }
+        };
+
+        if end > self.bytes.len() {
+            return Err(Error::OutOfBounds);
+        }
+
+        Ok(end)
+    }
+}
+
+/// The size of a fixed-sized GVariant encoded value of `signature`, or `None` if it isn't fixed
+/// sized.
+#[cfg(feature = "gvariant")]
+pub(super) fn fixed_size(signature: &Signature<'_>) -> Result<Option<usize>> {
+    if !is_fixed_sized_signature(signature)? {
+        return Ok(None);
+    }
+
+    match *subslice(signature.as_bytes(), 0)? as char {
+        STRUCT_SIG_START_CHAR | DICT_ENTRY_SIG_START_CHAR => {
+            let mut parser = SignatureParser::new(signature.slice(1..signature.len() - 1));
+            let mut size = 0;
+            while !parser.done() {
+                let field_signature = parser.parse_next_signature()?;
+                let alignment = alignment_for_signature(&field_signature, Format::GVariant)?;
+                size += padding_for_n_bytes(size, alignment);
+                size += fixed_size(&field_signature)?.unwrap_or_default();
+            }
+
+            Ok(Some(size))
+        }
+        // The size of all the fixed-sized basic types is the same as their alignment.
+        _ => alignment_for_signature(signature, Format::GVariant).map(Some),
+    }
+}
+
+/// How the elements of an array are delimited.
+#[derive(Clone, Copy, Debug)]
+enum Framing {
+    /// The extent of each element is found by walking its encoding (D-Bus).
+    Walk,
+    /// All elements have the same size.
+    #[cfg(feature = "gvariant")]
+    Fixed(usize),
+    /// The end of each element is given by the framing offsets (GVariant), starting at `next`.
+    #[cfg(feature = "gvariant")]
+    Offsets {
+        size: FramingOffsetSize,
+        next: usize,
+    },
+}
+
+/// An iterator over the elements of an array [`View`].
+///
+/// Created by [`View::elements`]. Iteration stops after the first error.
+#[derive(Clone, Debug)]
+pub struct Elements<'d> {
+    array: View<'d>,
+    element_signature: Signature<'d>,
+    pos: usize,
+    end: usize,
+    framing: Framing,
+    done: bool,
+}
+
+impl<'d> Elements<'d> {
+    fn next_element(&mut self) -> Result<Option<View<'d>>> {
+        let start = match self.framing {
+            #[cfg(feature = "gvariant")]
+            Framing::Offsets { next, .. } if next >= self.array.bytes.len() => return Ok(None),
+            #[cfg(feature = "gvariant")]
+            Framing::Offsets { .. } => self.array.align(self.pos, &self.element_signature)?,
+            _ if self.pos >= self.end => return Ok(None),
+            _ => self.array.align(self.pos, &self.element_signature)?,
+        };
+        let end = match &mut self.framing {
+            Framing::Walk => {
+                self.array
+                    .dbus_value_end(start, &self.element_signature, Default::default())?
+            }
+            #[cfg(feature = "gvariant")]
+            Framing::Fixed(size) => start + *size,
+            #[cfg(feature = "gvariant")]
+            Framing::Offsets { size, next } => {
+                let offset_end = *next + *size as usize;
+                let end = size
+                    .read_last_offset_from_buffer(subslice(self.array.bytes, *next..offset_end)?);
+                *next = offset_end;
+
+                end
+            }
+        };
+        if start > end || end > self.end {
+            return Err(Error::OutOfBounds);
+        }
+        self.pos = end;
+
+        self.array
+            .child(start, end, self.element_signature.clone())
+            .map(Some)
+    }
+}
+
+impl<'d> Iterator for Elements<'d> {
+    type Item = Result<View<'d>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let element = self.next_element();
+        if !matches!(element, Ok(Some(_))) {
+            self.done = true;
+        }
+
+        element.transpose()
+    }
+}
+
+/// An iterator over the fields of a structure or dictionary entry [`View`].
+///
+/// Created by [`View::fields`]. Iteration stops after the first error.
+#[derive(Clone, Debug)]
+pub struct Fields<'d> {
+    parent: View<'d>,
+    parser: SignatureParser<'d>,
+    pos: usize,
+    end: usize,
+    #[cfg(feature = "gvariant")]
+    offset_size: FramingOffsetSize,
+    done: bool,
+}
+
+impl<'d> Fields<'d> {
+    fn next_field(&mut self) -> Result<Option<View<'d>>> {
+        if self.parser.done() {
+            return Ok(None);
+        }
+
+        let signature = self.parser.parse_next_signature()?;
+        let start = self.parent.align(self.pos, &signature)?;
+        let end = match self.parent.context.format() {
+            Format::DBus => self
+                .parent
+                .dbus_value_end(start, &signature, Default::default())?,
+            #[cfg(feature = "gvariant")]
+            Format::GVariant => match fixed_size(&signature)? {
+                Some(size) => start + size,
+                // The last field doesn't get a framing offset.
+                None if self.parser.done() => self.end,
+                None => {
+                    let offset_size = self.offset_size as usize;
+                    if offset_size > self.end {
+                        return Err(Error::MissingFramingOffset);
+                    }
+                    let offsets = subslice(self.parent.bytes, ..self.end)?;
+                    let end = self.offset_size.read_last_offset_from_buffer(offsets);
+                    self.end -= offset_size;
+
+                    end
+                }
+            },
+        };
+        if start > end || end > self.end {
+            return Err(Error::OutOfBounds);
+        }
+        self.pos = end;
+
+        self.parent.child(start, end, signature).map(Some)
+    }
+}
+
+impl<'d> Iterator for Fields<'d> {
+    type Item = Result<View<'d>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let field = self.next_field();
+        if !matches!(field, Ok(Some(_))) {
+            self.done = true;
+        }
+
+        field.transpose()
+    }
+}
+
+/// An iterator over the key-value pairs of a dictionary [`View`].
+///
+/// Created by [`View::entries`]. Iteration stops after the first error.
+#[derive(Clone, Debug)]
+pub struct Entries<'d> {
+    elements: Elements<'d>,
+}
+
+impl<'d> Iterator for Entries<'d> {
+    type Item = Result<(View<'d>, View<'d>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = match self.elements.next()? {
+            Ok(entry) => entry,
+            Err(e) => return Some(Err(e)),
+        };
+        let entry = entry.fields().and_then(|mut fields| {
+            let key = fields.next().ok_or(Error::OutOfBounds)??;
+            let value = fields.next().ok_or(Error::OutOfBounds)??;
+
+            Ok((key, value))
+        });
+        if entry.is_err() {
+            self.elements.done = true;
+        }
+
+        Some(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        serialized::{Context, Format},
+        to_bytes, ObjectPath, OwnedObjectPath, Value, LE,
+    };
+
+    fn objects() -> Vec<(
+        OwnedObjectPath,
+        &'static str,
+        HashMap<&'static str, Value<'static>>,
+    )> {
+        (0..10u32)
+            .map(|i| {
+                let path = ObjectPath::try_from(format!("/org/zbus/{i}")).unwrap();
+                let props = HashMap::from([
+                    ("Index", Value::from(i)),
+                    ("Name", Value::from(format!("object{i}"))),
+                ]);
+
+                (path.into(), "org.zbus.Object", props)
+            })
+            .collect()
+    }
+
+    fn check_views(format: Format, position: usize) {
+        let ctxt = Context::new(format, LE, position);
+        let objects = objects();
+        let encoded = to_bytes(ctxt, &objects).unwrap();
+        let view = encoded.view("a(osa{sv})").unwrap();
+
+        let elements = view.elements().unwrap();
+        assert_eq!(elements.count(), objects.len());
+
+        for (i, element) in view.elements().unwrap().enumerate() {
+            let element = element.unwrap();
+            let path: ObjectPath<'_> = element.field(0).unwrap().unwrap().deserialize().unwrap();
+            assert_eq!(path.as_str(), objects[i].0.as_str());
+            let iface: &str = element.field(1).unwrap().unwrap().deserialize().unwrap();
+            assert_eq!(iface, "org.zbus.Object");
+            assert!(element.field(3).unwrap().is_none());
+            let value = element.to_value().unwrap();
+            assert_eq!(value.value_signature(), "(osa{sv})");
+
+            let props = element.field(2).unwrap().unwrap();
+            assert_eq!(props.entries().unwrap().count(), 2);
+            let name = props.get("Name").unwrap().unwrap();
+            let name = name.variant().unwrap();
+            assert_eq!(name.signature(), "s");
+            assert_eq!(name.deserialize::<&str>().unwrap(), format!("object{i}"));
+            let index = props.get("Index").unwrap().unwrap();
+            assert_eq!(index.to_value().unwrap(), Value::new(i as u32));
+            assert!(props.get("Missing").unwrap().is_none());
+        }
+
+        // Multiple types are treated as a structure.
+        let encoded = to_bytes(ctxt, &(42u8, "hello", 7u64)).unwrap();
+        let view = encoded.view("yst").unwrap();
+        let fields: Vec<_> = view.fields().unwrap().map(Result::unwrap).collect();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0].deserialize::<u8>().unwrap(), 42);
+        assert_eq!(fields[1].deserialize::<&str>().unwrap(), "hello");
+        assert_eq!(fields[2].deserialize::<u64>().unwrap(), 7);
+
+        // Fixed-sized elements.
+        let encoded = to_bytes(ctxt, &vec![(1u64, 2u8), (3, 4), (5, 6)]).unwrap();
+        let view = encoded.view("a(ty)").unwrap();
+        let elements: Vec<(u64, u8)> = view
+            .elements()
+            .unwrap()
+            .map(|e| e.unwrap().deserialize().unwrap())
+            .collect();
+        assert_eq!(elements, [(1, 2), (3, 4), (5, 6)]);
+
+        let encoded = to_bytes(ctxt, &Vec::<&str>::new()).unwrap();
+        let view = encoded.view("as").unwrap();
+        assert_eq!(view.elements().unwrap().count(), 0);
+
+        // Wrong container kind.
+        let encoded = to_bytes(ctxt, &"hello").unwrap();
+        let view = encoded.view("s").unwrap();
+        assert!(view.elements().is_err());
+        assert!(view.fields().is_err());
+        assert!(view.variant().is_err());
+        assert!(view.deserialize::<u32>().is_err());
+    }
+
+    #[test]
+    fn dbus() {
+        check_views(Format::DBus, 0);
+        check_views(Format::DBus, 3);
+    }
+
+    #[cfg(feature = "gvariant")]
+    #[test]
+    fn gvariant() {
+        check_views(Format::GVariant, 0);
+
+        let ctxt = Context::new_gvariant(LE, 0);
+        let encoded = to_bytes(ctxt, &Some("hello")).unwrap();
+        let view = encoded.view("ms").unwrap();
+        let inner = view.maybe().unwrap().unwrap();
+        assert_eq!(inner.deserialize::<&str>().unwrap(), "hello");
+
+        let encoded = to_bytes(ctxt, &None::<&str>).unwrap();
+        let view = encoded.view("ms").unwrap();
+        assert!(view.maybe().unwrap().is_none());
+    }
+
+    #[test]
+    fn truncated() {
+        let ctxt = Context::new_dbus(LE, 0);
+        let encoded = to_bytes(ctxt, &vec!["hello", "world"]).unwrap();
+        let truncated = encoded.slice(..encoded.len() - 3);
+        assert!(truncated.view("as").is_err());
+
+        // Claims more elements than there are bytes for.
+        let mut bytes = encoded.to_vec();
+        bytes[0] = 0xff;
+        let data = crate::serialized::Data::new(bytes, ctxt);
+        assert!(data.view("as").is_err());
+    }
+}
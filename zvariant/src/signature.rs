@@ -15,7 +15,10 @@ use std::{
     sync::Arc,
 };
 
-use crate::{serialized::Format, signature_parser::SignatureParser, Basic, Error, Result, Type};
+use crate::{
+    serialized::Format, signature_parser::SignatureParser, Basic, Error, Result, StaticSignature,
+    Type,
+};
 
 // A data type similar to Cow and [`bytes::Bytes`] but unlike the former won't allow us to only keep
 // the owned bytes in Arc and latter doesn't have a notion of borrowed data and would require API
@@ -358,9 +361,8 @@ impl<'a> Basic for Signature<'a> {
 }
 
 impl<'a> Type for Signature<'a> {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked(Self::SIGNATURE_STR)
-    }
+    const SIGNATURE: &'static StaticSignature =
+        &StaticSignature::from_str_unchecked(Self::SIGNATURE_STR);
 }
 
 impl<'a> From<&Signature<'a>> for Signature<'a> {
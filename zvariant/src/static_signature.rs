@@ -0,0 +1,219 @@
+use std::{fmt, str};
+
+use crate::Signature;
+
+/// The maximum length of a signature, as per the D-Bus specification.
+const MAX_LEN: usize = 255;
+
+/// A signature built at compile time.
+///
+/// This is what [`Type::SIGNATURE`] is made of. Since strings can't be concatenated in `const`
+/// contexts, the signature is kept in a fixed-size buffer, big enough for the longest signature
+/// allowed by the D-Bus specification. The constructors are all `const fn`s, so signatures of
+/// containers can be built from the signatures of their children:
+///
+/// ```
+/// use zvariant::{StaticSignature, Type};
+///
+/// const SIGNATURE: StaticSignature =
+///     StaticSignature::dict(<&str>::SIGNATURE, &StaticSignature::structure(&[
+///         u32::SIGNATURE,
+///         <Vec<u8>>::SIGNATURE,
+///     ]));
+/// assert_eq!(SIGNATURE.as_str(), Some("a{s(uay)}"));
+/// ```
+///
+/// A signature can be *unknown*, which is the case for types that only implement
+/// [`Type::signature`], and for any container of them. Longer signatures (only possible with
+/// GVariant) are also unknown. In these cases, the signature is built at runtime instead.
+///
+/// [`Type::SIGNATURE`]: crate::Type::SIGNATURE
+/// [`Type::signature`]: crate::Type::signature
+#[derive(Clone, Copy)]
+pub struct StaticSignature {
+    bytes: [u8; MAX_LEN],
+    len: usize,
+    known: bool,
+}
+
+impl StaticSignature {
+    /// An unknown signature.
+    pub const UNKNOWN: Self = Self {
+        bytes: [0; MAX_LEN],
+        len: 0,
+        known: false,
+    };
+
+    /// Create a signature from a string, without checking its validity.
+    ///
+    /// The signature is unknown if `signature` is longer than 255 bytes.
+    pub const fn from_str_unchecked(signature: &str) -> Self {
+        Self::EMPTY.push(signature.as_bytes())
+    }
+
+    /// The signature of an array of elements with the given signature.
+    pub const fn array(element: &Self) -> Self {
+        Self::concat(b"a", &[element], b"")
+    }
+
+    /// The signature of a dictionary with the given key and value signatures.
+    pub const fn dict(key: &Self, value: &Self) -> Self {
+        Self::concat(b"a{", &[key, value], b"}")
+    }
+
+    /// The signature of a maybe (GVariant-only) of a value with the given signature.
+    pub const fn maybe(child: &Self) -> Self {
+        Self::concat(b"m", &[child], b"")
+    }
+
+    /// The signature of a structure with fields of the given signatures.
+    pub const fn structure(fields: &[&Self]) -> Self {
+        Self::concat(b"(", fields, b")")
+    }
+
+    /// The signature of a structure with `n` fields of the same signature, e.g of an array.
+    pub(crate) const fn repeated_structure(field: &Self, n: usize) -> Self {
+        // `n` can be huge, so check the length up front rather than while looping.
+        if !field.known || field.len.saturating_mul(n).saturating_add(2) > MAX_LEN {
+            return Self::UNKNOWN;
+        }
+
+        let mut signature = Self::EMPTY.push(b"(");
+        let mut i = 0;
+        while i < n && field.len > 0 {
+            signature = signature.push(field.as_bytes());
+            i += 1;
+        }
+
+        signature.push(b")")
+    }
+
+    /// If the signature is known.
+    pub const fn is_known(&self) -> bool {
+        self.known
+    }
+
+    /// The signature as a string, if it's known.
+    pub fn as_str(&self) -> Option<&str> {
+        // SAFETY: The bytes are only ever copied whole from `str`s and ASCII strings.
+        self.known
+            .then(|| unsafe { str::from_utf8_unchecked(&self.bytes[..self.len]) })
+    }
+
+    /// The signature as a [`Signature`], if it's known.
+    ///
+    /// This doesn't allocate.
+    pub fn as_signature(&'static self) -> Option<Signature<'static>> {
+        self.as_str().map(Signature::from_static_str_unchecked)
+    }
+
+    const EMPTY: Self = Self {
+        known: true,
+        ..Self::UNKNOWN
+    };
+
+    const fn concat(prefix: &[u8], children: &[&Self], suffix: &[u8]) -> Self {
+        let mut signature = Self::EMPTY.push(prefix);
+        let mut i = 0;
+        while i < children.len() {
+            if !children[i].known || !signature.known {
+                return Self::UNKNOWN;
+            }
+            signature = signature.push(children[i].as_bytes());
+            i += 1;
+        }
+
+        signature.push(suffix)
+    }
+
+    const fn push(mut self, bytes: &[u8]) -> Self {
+        if !self.known || self.len + bytes.len() > MAX_LEN {
+            return Self::UNKNOWN;
+        }
+
+        let mut i = 0;
+        while i < bytes.len() {
+            self.bytes[self.len + i] = bytes[i];
+            i += 1;
+        }
+        self.len += bytes.len();
+
+        self
+    }
+
+    const fn as_bytes(&self) -> &[u8] {
+        self.bytes.split_at(self.len).0
+    }
+}
+
+impl PartialEq for StaticSignature {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for StaticSignature {}
+
+impl fmt::Debug for StaticSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.as_str() {
+            Some(s) => f.debug_tuple("StaticSignature").field(&s).finish(),
+            None => f.write_str("StaticSignature::UNKNOWN"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::StaticSignature;
+    use crate::{Signature, Type, Value};
+
+    #[test]
+    fn containers() {
+        assert_eq!(u32::SIGNATURE.as_str(), Some("u"));
+        assert_eq!(<Vec<&str>>::SIGNATURE.as_str(), Some("as"));
+        assert_eq!(
+            <HashMap<String, Value<'_>>>::SIGNATURE.as_str(),
+            Some("a{sv}")
+        );
+        assert_eq!(<(u8, [i16; 2], ())>::SIGNATURE.as_str(), Some("(y(nn))"));
+        assert_eq!(
+            <Box<(bool, Signature<'_>)>>::SIGNATURE.as_str(),
+            Some("(bg)")
+        );
+        assert_eq!(<Vec<(u32, String)>>::signature(), "a(us)");
+    }
+
+    #[test]
+    fn unknown() {
+        struct Dynamic;
+
+        impl Type for Dynamic {
+            fn signature() -> Signature<'static> {
+                Signature::from_static_str_unchecked("x")
+            }
+        }
+
+        assert_eq!(Dynamic::SIGNATURE, &StaticSignature::UNKNOWN);
+        assert_eq!(<Vec<Dynamic>>::SIGNATURE, &StaticSignature::UNKNOWN);
+        assert_eq!(<Vec<Dynamic>>::signature(), "ax");
+        assert_eq!(<(u8, Dynamic)>::signature(), "(yx)");
+
+        // Too long for D-Bus, but not for GVariant.
+        assert_eq!(<[u64; 300]>::SIGNATURE, &StaticSignature::UNKNOWN);
+        assert_eq!(<[u64; 300]>::signature().len(), 302);
+    }
+
+    #[test]
+    fn large_arrays() {
+        assert_eq!(<[u8; 253]>::SIGNATURE.as_str().map(str::len), Some(255));
+        assert_eq!(<[u8; 254]>::SIGNATURE, &StaticSignature::UNKNOWN);
+
+        // The length is checked up front, rather than by building the whole signature.
+        assert_eq!(<[u8; 1_000_000]>::SIGNATURE, &StaticSignature::UNKNOWN);
+        assert_eq!(<[[u8; 1_000]; 1_000]>::SIGNATURE, &StaticSignature::UNKNOWN);
+        assert_eq!(<[u8; 1_000_000]>::signature().len(), 1_000_002);
+    }
+}
@@ -0,0 +1,1103 @@
+//! The GVariant text format.
+//!
+//! This is the human-readable format GLib uses in `g_variant_print` and `g_variant_parse`, e.g
+//! `{'brightness': <uint32 80>, 'modes': <['a', 'b']>}`. It's commonly found in configuration
+//! files (e.g GSettings schemas) and command-line tools (e.g `gdbus` and `gsettings`).
+//!
+//! [`print`] formats a [`Value`] in this format, just like its `Display` implementation, and
+//! [`parse`] parses it back into a [`Value`].
+//!
+//! # Example
+//!
+//! ```
+//! use zvariant::{text, Signature, Value};
+//!
+//! let value = text::parse("{'brightness': <uint32 80>, 'modes': <['a', 'b']>}", None)?;
+//! assert_eq!(value.value_signature(), "a{sv}");
+//! assert_eq!(
+//!     text::print(&value, true),
+//!     r#"{"brightness": <uint32 80>, "modes": <["a", "b"]>}"#,
+//! );
+//!
+//! // Without type annotations, the expected type can be given instead.
+//! let signature = Signature::try_from("(qx)")?;
+//! let value = text::parse("(80, -1)", Some(&signature))?;
+//! assert_eq!(value, Value::from((80u16, -1i64)));
+//!
+//! // Errors point at the faulty part of the text.
+//! let err = text::parse("[1, 'two', 3]", None).unwrap_err();
+//! assert_eq!(err.span(), 4..9);
+//! # Ok::<(), zvariant::Error>(())
+//! ```
+
+use std::{fmt, ops::Range};
+
+use static_assertions::assert_impl_all;
+
+#[cfg(feature = "gvariant")]
+use crate::Maybe;
+use crate::{
+    container_depths::ContainerDepths, value::value_display_fmt, Array, Dict, Error, ObjectPath,
+    ParsedSignature, Signature, SignatureEntry, StructureBuilder, Value,
+};
+
+/// Format `value` in the GVariant text format.
+///
+/// If `type_annotate` is `true`, the types that can't be inferred from the text (e.g `uint32` for
+/// numbers or the type of empty arrays) are annotated, so that [`parse`] gives back the same value.
+/// Otherwise, the expected type must be given to [`parse`] to get the same value back.
+///
+/// This is the same as the `Display` implementation of [`Value`], which always annotates types.
+pub fn print(value: &Value<'_>, type_annotate: bool) -> String {
+    struct Printer<'a, 'v>(&'a Value<'v>, bool);
+
+    impl fmt::Display for Printer<'_, '_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            value_display_fmt(self.0, f, self.1)
+        }
+    }
+
+    Printer(value, type_annotate).to_string()
+}
+
+/// Parse `text` in the GVariant text format into a [`Value`].
+///
+/// If `signature` is given, the value is parsed as that type. Otherwise, the type is inferred from
+/// the text: integers default to `int32`, floating point numbers to `double` and strings to
+/// `string`, unless annotated (e.g `uint32 42` or `@o '/org/zbus'`) or unified with the other
+/// elements of the same array or dictionary.
+///
+/// Both single and double quoted strings are supported, with C and Rust style escapes.
+///
+/// `signature` must be a single complete type, and containers can't be nested deeper than the
+/// D-Bus specification allows.
+pub fn parse(text: &str, signature: Option<&Signature<'_>>) -> Result<Value<'static>> {
+    let mut parser = Parser {
+        text,
+        pos: 0,
+        depths: ContainerDepths::default(),
+        annotated: false,
+    };
+    let node = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < text.len() {
+        return Err(ParseError::new(
+            parser.pos..text.len(),
+            "unexpected text after the value",
+        ));
+    }
+
+    let signature = match signature {
+        Some(signature) => {
+            let mut parsed = ParsedSignature::new(signature).into_iter();
+            match (parsed.next(), parsed.next()) {
+                (Some(entry), None) => entry,
+                _ => {
+                    return Err(ParseError::new(
+                        0..text.len(),
+                        format!("`{signature}` is not a single complete type"),
+                    ))
+                }
+            }
+        }
+        None => node.signature()?,
+    };
+
+    node.build(&signature)
+}
+
+/// Error returned by [`parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    span: Range<usize>,
+    message: String,
+}
+
+assert_impl_all!(ParseError: Send, Sync, Unpin);
+
+impl ParseError {
+    fn new(span: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// The byte range of the text the error is about.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// The description of the error, without the position.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}: {}", self.span.start, self.span.end, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Message(e.to_string())
+    }
+}
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+/// A parsed, but not yet typed, value.
+#[derive(Debug)]
+struct Node {
+    span: Range<usize>,
+    kind: Kind,
+}
+
+#[derive(Debug)]
+enum Kind {
+    Bool(bool),
+    /// The text of the number, as its type is only known once the whole value is parsed.
+    Number(String),
+    String(String),
+    ByteString(Vec<u8>),
+    Array(Vec<Node>),
+    Dict(Vec<(Node, Node)>),
+    DictEntry(Box<Node>, Box<Node>),
+    Tuple(Vec<Node>),
+    Variant(Box<Node>),
+    Nothing,
+    Just(Box<Node>),
+    Typed(SignatureEntry, Box<Node>),
+}
+
+struct Parser<'t> {
+    text: &'t str,
+    pos: usize,
+    /// The depths of the containers the current value is in.
+    depths: ContainerDepths,
+    /// Whether the current value follows a type annotation.
+    annotated: bool,
+}
+
+impl<'t> Parser<'t> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume `c` if it's the next non-whitespace character.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expected(&self, what: &str) -> ParseError {
+        let end = self
+            .peek()
+            .map(|c| self.pos + c.len_utf8())
+            .unwrap_or(self.pos);
+
+        ParseError::new(self.pos..end, format!("expected {what}"))
+    }
+
+    fn value(&mut self) -> Result<Node> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let annotated = std::mem::take(&mut self.annotated);
+        let kind = match self.peek() {
+            Some('[') => self.container(start, ContainerDepths::inc_array, Self::array)?,
+            // Dictionaries are counted as their entries, which are structures.
+            Some('{') => self.container(start, ContainerDepths::inc_structure, Self::dict)?,
+            Some('(') => self.container(start, ContainerDepths::inc_structure, Self::tuple)?,
+            Some('<') => self.container(start, ContainerDepths::inc_variant, |parser| {
+                parser.pos += 1;
+                let value = parser.value()?;
+                if !parser.eat('>') {
+                    return Err(parser.expected("`>` to end the variant"));
+                }
+
+                Ok(Kind::Variant(Box::new(value)))
+            })?,
+            Some('@') if annotated => {
+                return Err(ParseError::new(
+                    start..start + 1,
+                    "a value can only have one type annotation",
+                ))
+            }
+            Some('@') => {
+                self.pos += 1;
+                let signature = self.signature()?;
+                self.annotated = true;
+
+                Kind::Typed(signature, Box::new(self.value()?))
+            }
+            Some(quote @ ('\'' | '"')) => {
+                let s = self.quoted(quote, false)?;
+
+                Kind::String(String::from_utf8(s).expect("only UTF-8 is produced for strings"))
+            }
+            Some('b') if matches!(self.text[self.pos + 1..].chars().next(), Some('\'' | '"')) => {
+                self.pos += 1;
+                let quote = self.peek().expect("quote");
+                let mut bytes = self.quoted(quote, true)?;
+                // Byte strings are nul-terminated.
+                bytes.push(b'\0');
+
+                Kind::ByteString(bytes)
+            }
+            Some(c) if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') => self.number(),
+            Some(c) if c.is_ascii_alphabetic() => {
+                let word = self.word();
+                match word {
+                    "true" => Kind::Bool(true),
+                    "false" => Kind::Bool(false),
+                    "nothing" => Kind::Nothing,
+                    "just" => self.container(start, inc_maybe, |parser| {
+                        Ok(Kind::Just(Box::new(parser.value()?)))
+                    })?,
+                    _ if is_special_float(word) => Kind::Number(word.to_string()),
+                    _ => match keyword_signature(word) {
+                        Some(_) if annotated => {
+                            return Err(ParseError::new(
+                                start..self.pos,
+                                "a value can only have one type annotation",
+                            ))
+                        }
+                        Some(signature) => {
+                            self.annotated = true;
+
+                            Kind::Typed(signature, Box::new(self.value()?))
+                        }
+                        None => {
+                            return Err(ParseError::new(
+                                start..self.pos,
+                                format!("unknown keyword `{word}`"),
+                            ))
+                        }
+                    },
+                }
+            }
+            _ => return Err(self.expected("a value")),
+        };
+
+        Ok(Node {
+            span: start..self.pos,
+            kind,
+        })
+    }
+
+    /// Parse a container starting at `start` with `parse`, one level deeper according to `inc`.
+    fn container<F>(
+        &mut self,
+        start: usize,
+        inc: fn(ContainerDepths) -> crate::Result<ContainerDepths>,
+        parse: F,
+    ) -> Result<Kind>
+    where
+        F: FnOnce(&mut Self) -> Result<Kind>,
+    {
+        let depths = self.depths;
+        self.depths = inc(depths)
+            .map_err(|e| ParseError::new(start..self.pos.max(start + 1), e.to_string()))?;
+        let kind = parse(self);
+        self.depths = depths;
+
+        kind
+    }
+
+    fn word(&mut self) -> &'t str {
+        let start = self.pos;
+        let rest = &self.text[start..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        self.pos += len;
+
+        &self.text[start..self.pos]
+    }
+
+    fn number(&mut self) -> Kind {
+        let start = self.pos;
+        let bytes = self.text.as_bytes();
+        let mut hex = false;
+        while let Some(&c) = bytes.get(self.pos) {
+            let prev = self.pos.checked_sub(1).map(|i| bytes[i]);
+            let sign_allowed = self.pos == start || (!hex && matches!(prev, Some(b'e' | b'E')));
+            match c {
+                b'x' | b'X' => hex = true,
+                b'+' | b'-' if sign_allowed => (),
+                c if c.is_ascii_alphanumeric() || c == b'.' => (),
+                _ => break,
+            }
+            self.pos += 1;
+        }
+
+        Kind::Number(self.text[start..self.pos].to_string())
+    }
+
+    /// Parse a single complete type after a `@`.
+    fn signature(&mut self) -> Result<SignatureEntry> {
+        let start = self.pos;
+        let (signature, len) = SignatureEntry::parse_prefix(&self.text.as_bytes()[start..])
+            .map_err(|_| {
+                let end = self.text[start..]
+                    .find(char::is_whitespace)
+                    .map(|i| start + i)
+                    .unwrap_or(self.text.len());
+
+                ParseError::new(start..end, "expected a valid type after `@`")
+            })?;
+        self.pos += len;
+
+        Ok(signature)
+    }
+
+    /// Parse a quoted string, with its escapes.
+    ///
+    /// For `bytes`, octal escapes give the raw byte rather than the character.
+    fn quoted(&mut self, quote: char, bytes: bool) -> Result<Vec<u8>> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut s = vec![];
+        let mut buf = [0; 4];
+        loop {
+            let c = self
+                .peek()
+                .ok_or_else(|| ParseError::new(start..self.text.len(), "unterminated string"))?;
+            self.pos += c.len_utf8();
+            match c {
+                c if c == quote => break,
+                '\\' => match self.escape(bytes)? {
+                    Escaped::Char(c) => s.extend_from_slice(c.encode_utf8(&mut buf).as_bytes()),
+                    Escaped::Byte(b) => s.push(b),
+                },
+                c => s.extend_from_slice(c.encode_utf8(&mut buf).as_bytes()),
+            }
+        }
+
+        Ok(s)
+    }
+
+    fn escape(&mut self, bytes: bool) -> Result<Escaped> {
+        let start = self.pos - 1;
+        let c = self
+            .peek()
+            .ok_or_else(|| ParseError::new(start..self.pos, "unterminated escape sequence"))?;
+        self.pos += c.len_utf8();
+        let escaped = match c {
+            'a' => '\x07',
+            'b' => '\x08',
+            'f' => '\x0c',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\x0b',
+            '0'..='7' => {
+                let digits = self.text[start + 1..]
+                    .bytes()
+                    .take(3)
+                    .take_while(|b| (b'0'..=b'7').contains(b))
+                    .count();
+                self.pos = start + 1 + digits;
+                let n =
+                    u32::from_str_radix(&self.text[start + 1..self.pos], 8).expect("octal digits");
+                match u8::try_from(n) {
+                    Ok(b) if bytes => return Ok(Escaped::Byte(b)),
+                    _ => char::from_u32(n).expect("octal escapes are valid characters"),
+                }
+            }
+            'u' | 'U' => {
+                let (digits, len) = if c == 'u' && self.peek() == Some('{') {
+                    // Rust style, as printed by `Value`'s `Display` implementation.
+                    let rest = &self.text[self.pos + 1..];
+                    let end = rest.find('}').ok_or_else(|| {
+                        ParseError::new(start..self.pos + 1, "unterminated unicode escape")
+                    })?;
+
+                    (&rest[..end], end + 2)
+                } else {
+                    let len = if c == 'u' { 4 } else { 8 };
+                    let digits = self.text.get(self.pos..self.pos + len).unwrap_or_default();
+
+                    (digits, len)
+                };
+                let escaped = u32::from_str_radix(digits, 16)
+                    .ok()
+                    .filter(|_| !digits.starts_with('+'))
+                    .and_then(char::from_u32);
+                match escaped {
+                    Some(escaped) => {
+                        self.pos += len;
+
+                        escaped
+                    }
+                    None => {
+                        return Err(ParseError::new(
+                            start..(self.pos + len).min(self.text.len()),
+                            "invalid unicode escape",
+                        ))
+                    }
+                }
+            }
+            c => c,
+        };
+
+        Ok(Escaped::Char(escaped))
+    }
+
+    fn array(&mut self) -> Result<Kind> {
+        self.pos += 1;
+        let mut elements = vec![];
+        if self.eat(']') {
+            return Ok(Kind::Array(elements));
+        }
+        loop {
+            elements.push(self.value()?);
+            if self.eat(']') {
+                return Ok(Kind::Array(elements));
+            }
+            if !self.eat(',') {
+                return Err(self.expected("`,` or `]`"));
+            }
+        }
+    }
+
+    fn dict(&mut self) -> Result<Kind> {
+        self.pos += 1;
+        let mut entries = vec![];
+        if self.eat('}') {
+            return Ok(Kind::Dict(entries));
+        }
+
+        let key = self.value()?;
+        if self.eat(',') {
+            // A single dictionary entry, e.g `{'a', 1}`.
+            let value = self.value()?;
+            if !self.eat('}') {
+                return Err(self.expected("`}` to end the dictionary entry"));
+            }
+
+            return Ok(Kind::DictEntry(Box::new(key), Box::new(value)));
+        }
+        if !self.eat(':') {
+            return Err(self.expected("`:` or `,`"));
+        }
+        entries.push((key, self.value()?));
+
+        loop {
+            if self.eat('}') {
+                return Ok(Kind::Dict(entries));
+            }
+            if !self.eat(',') {
+                return Err(self.expected("`,` or `}`"));
+            }
+            let key = self.value()?;
+            if !self.eat(':') {
+                return Err(self.expected("`:`"));
+            }
+            entries.push((key, self.value()?));
+        }
+    }
+
+    fn tuple(&mut self) -> Result<Kind> {
+        self.pos += 1;
+        let mut fields = vec![];
+        loop {
+            if self.eat(')') {
+                return Ok(Kind::Tuple(fields));
+            }
+            fields.push(self.value()?);
+            if self.eat(')') {
+                return Ok(Kind::Tuple(fields));
+            }
+            if !self.eat(',') {
+                return Err(self.expected("`,` or `)`"));
+            }
+        }
+    }
+}
+
+enum Escaped {
+    Char(char),
+    Byte(u8),
+}
+
+/// The type of a value, as inferred from the text.
+#[derive(Debug, Clone, PartialEq)]
+enum Ty {
+    /// Nothing is known about the type (e.g the elements of `[]`).
+    Unknown,
+    /// An integer literal, `int32` unless unified with another type.
+    Integer,
+    /// A floating point literal, always a `double`.
+    Float,
+    /// A string literal, `string` unless unified with another type.
+    String,
+    Basic(SignatureEntry),
+    Array(Box<Ty>),
+    DictEntry(Box<Ty>, Box<Ty>),
+    Tuple(Vec<Ty>),
+    Maybe(Box<Ty>),
+}
+
+impl Ty {
+    fn from_signature(signature: &SignatureEntry) -> Ty {
+        match signature {
+            SignatureEntry::Array(element) => Ty::Array(Box::new(Ty::from_signature(element))),
+            #[cfg(feature = "gvariant")]
+            SignatureEntry::Maybe(inner) => Ty::Maybe(Box::new(Ty::from_signature(inner))),
+            SignatureEntry::DictEntry(key, value) => Ty::DictEntry(
+                Box::new(Ty::from_signature(key)),
+                Box::new(Ty::from_signature(value)),
+            ),
+            SignatureEntry::Struct(fields) => {
+                Ty::Tuple(fields.iter().map(Ty::from_signature).collect())
+            }
+            basic => Ty::Basic(basic.clone()),
+        }
+    }
+
+    /// The most specific type compatible with both `self` and `other`, if any.
+    fn unify(self, other: Ty) -> Option<Ty> {
+        use SignatureEntry as E;
+
+        let ty = match (self, other) {
+            (Ty::Unknown, ty) | (ty, Ty::Unknown) => ty,
+            (Ty::Integer, Ty::Integer) => Ty::Integer,
+            (Ty::Integer | Ty::Float, Ty::Float) | (Ty::Float, Ty::Integer) => Ty::Float,
+            (Ty::Integer, Ty::Basic(e)) | (Ty::Basic(e), Ty::Integer) if is_numeric(&e) => {
+                Ty::Basic(e)
+            }
+            (Ty::Float, Ty::Basic(E::F64)) | (Ty::Basic(E::F64), Ty::Float) => Ty::Basic(E::F64),
+            (Ty::String, Ty::String) => Ty::String,
+            (Ty::String, Ty::Basic(e)) | (Ty::Basic(e), Ty::String)
+                if matches!(e, E::Str | E::ObjectPath | E::Signature) =>
+            {
+                Ty::Basic(e)
+            }
+            (Ty::Basic(a), Ty::Basic(b)) if a == b => Ty::Basic(a),
+            (Ty::Array(a), Ty::Array(b)) => Ty::Array(Box::new(a.unify(*b)?)),
+            (Ty::DictEntry(k1, v1), Ty::DictEntry(k2, v2)) => {
+                Ty::DictEntry(Box::new(k1.unify(*k2)?), Box::new(v1.unify(*v2)?))
+            }
+            (Ty::Tuple(a), Ty::Tuple(b)) if a.len() == b.len() => Ty::Tuple(
+                a.into_iter()
+                    .zip(b)
+                    .map(|(a, b)| a.unify(b))
+                    .collect::<Option<_>>()?,
+            ),
+            (Ty::Maybe(a), Ty::Maybe(b)) => Ty::Maybe(Box::new(a.unify(*b)?)),
+            // `just` is optional.
+            (Ty::Maybe(a), b) | (b, Ty::Maybe(a)) => Ty::Maybe(Box::new(a.unify(b)?)),
+            _ => return None,
+        };
+
+        Some(ty)
+    }
+
+    /// The signature of the type, using the defaults for literals.
+    ///
+    /// Maybe types only have a signature with the `gvariant` feature.
+    fn signature(&self) -> Option<SignatureEntry> {
+        let signature = match self {
+            Ty::Unknown => return None,
+            Ty::Integer => SignatureEntry::I32,
+            Ty::Float => SignatureEntry::F64,
+            Ty::String => SignatureEntry::Str,
+            Ty::Basic(basic) => basic.clone(),
+            Ty::Array(element) => SignatureEntry::array(element.signature()?),
+            Ty::DictEntry(key, value) => {
+                SignatureEntry::dict_entry(key.signature()?, value.signature()?)
+            }
+            Ty::Tuple(fields) => SignatureEntry::structure(
+                fields
+                    .iter()
+                    .map(Ty::signature)
+                    .collect::<Option<Vec<_>>>()?,
+            ),
+            #[cfg(feature = "gvariant")]
+            Ty::Maybe(inner) => SignatureEntry::maybe(inner.signature()?),
+            #[cfg(not(feature = "gvariant"))]
+            Ty::Maybe(_) => return None,
+        };
+
+        Some(signature)
+    }
+}
+
+impl Node {
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.span.clone(), message)
+    }
+
+    /// The signature of the value, inferred from the text.
+    fn signature(&self) -> Result<SignatureEntry> {
+        let signature = self.ty()?.signature().ok_or_else(|| {
+            self.error("unable to infer the type of the value, a type annotation is needed")
+        })?;
+        // E.g dictionary keys must be basic types.
+        ParsedSignature::parse_str(&signature.to_string())
+            .map_err(|e| self.error(format!("invalid type `{signature}`: {e}")))?;
+
+        Ok(signature)
+    }
+
+    fn ty(&self) -> Result<Ty> {
+        let ty = match &self.kind {
+            Kind::Bool(_) => Ty::Basic(SignatureEntry::Bool),
+            Kind::Number(n) if is_float(n) => Ty::Float,
+            Kind::Number(_) => Ty::Integer,
+            Kind::String(_) => Ty::String,
+            Kind::ByteString(_) => Ty::Array(Box::new(Ty::Basic(SignatureEntry::U8))),
+            Kind::Array(elements) => Ty::Array(Box::new(unify_all(elements.iter())?)),
+            Kind::Dict(entries) => {
+                let key = unify_all(entries.iter().map(|(k, _)| k))?;
+                let value = unify_all(entries.iter().map(|(_, v)| v))?;
+
+                Ty::Array(Box::new(Ty::DictEntry(Box::new(key), Box::new(value))))
+            }
+            Kind::DictEntry(key, value) => {
+                Ty::DictEntry(Box::new(key.ty()?), Box::new(value.ty()?))
+            }
+            Kind::Tuple(fields) => Ty::Tuple(fields.iter().map(Node::ty).collect::<Result<_>>()?),
+            Kind::Variant(_) => Ty::Basic(SignatureEntry::Variant),
+            Kind::Nothing => Ty::Maybe(Box::new(Ty::Unknown)),
+            Kind::Just(inner) => Ty::Maybe(Box::new(inner.ty()?)),
+            Kind::Typed(signature, inner) => Ty::from_signature(signature)
+                .unify(inner.ty()?)
+                .ok_or_else(|| inner.error(format!("the value can't be of type `{signature}`")))?,
+        };
+
+        Ok(ty)
+    }
+
+    /// Build the value as the complete type `signature`.
+    fn build(&self, signature: &SignatureEntry) -> Result<Value<'static>> {
+        use SignatureEntry as E;
+
+        if let Kind::Typed(annotation, inner) = &self.kind {
+            if annotation == signature {
+                return inner.build(signature);
+            }
+        }
+
+        let value = match (signature, &self.kind) {
+            (E::Bool, Kind::Bool(b)) => Value::Bool(*b),
+            (E::U8, Kind::Number(n)) => Value::U8(self.integer(n, "byte")?),
+            (E::I16, Kind::Number(n)) => Value::I16(self.integer(n, "int16")?),
+            (E::U16, Kind::Number(n)) => Value::U16(self.integer(n, "uint16")?),
+            (E::I32, Kind::Number(n)) => Value::I32(self.integer(n, "int32")?),
+            (E::U32, Kind::Number(n)) => Value::U32(self.integer(n, "uint32")?),
+            (E::I64, Kind::Number(n)) => Value::I64(self.integer(n, "int64")?),
+            (E::U64, Kind::Number(n)) => Value::U64(self.integer(n, "uint64")?),
+            (E::F64, Kind::Number(n)) => Value::F64(self.float(n)?),
+            #[cfg(unix)]
+            (E::Fd, Kind::Number(_)) => {
+                return Err(self.error("file descriptors are not supported"));
+            }
+            (E::Str, Kind::String(s)) => Value::from(s.clone()),
+            (E::ObjectPath, Kind::String(s)) => ObjectPath::try_from(s.clone())
+                .map_err(|e| self.error(format!("invalid object path: {e}")))?
+                .into(),
+            (E::Signature, Kind::String(s)) => Signature::try_from(s.clone())
+                .map_err(|e| self.error(format!("invalid signature: {e}")))?
+                .into(),
+            (E::Variant, Kind::Variant(inner)) => {
+                Value::Value(Box::new(inner.build(&inner.signature()?)?))
+            }
+            (E::Array(element), Kind::ByteString(bytes)) if **element == E::U8 => {
+                let mut array = Array::new_full_signature(Signature::from(signature));
+                for b in bytes {
+                    array
+                        .append(Value::U8(*b))
+                        .map_err(|e| self.error(e.to_string()))?;
+                }
+
+                Value::Array(array)
+            }
+            (E::Array(element), Kind::Array(elements)) if matches!(**element, E::DictEntry(..)) => {
+                let mut dict = Dict::new_full_signature(Signature::from(signature));
+                for node in elements {
+                    let (key, value) = node.dict_entry(element)?;
+                    dict.append(key, value)
+                        .map_err(|e| node.error(e.to_string()))?;
+                }
+
+                Value::Dict(dict)
+            }
+            (E::Array(element), Kind::Array(elements)) => {
+                let mut array = Array::new_full_signature(Signature::from(signature));
+                for node in elements {
+                    array
+                        .append(node.build(element)?)
+                        .map_err(|e| node.error(e.to_string()))?;
+                }
+
+                Value::Array(array)
+            }
+            (E::Array(element), Kind::Dict(entries)) => {
+                let (key_signature, value_signature) = match &**element {
+                    E::DictEntry(key, value) => (key, value),
+                    _ => return Err(self.error(format!("expected a value of type `{signature}`"))),
+                };
+                let mut dict = Dict::new_full_signature(Signature::from(signature));
+                for (key, value) in entries {
+                    dict.append(key.build(key_signature)?, value.build(value_signature)?)
+                        .map_err(|e| key.error(e.to_string()))?;
+                }
+
+                Value::Dict(dict)
+            }
+            (E::Struct(field_signatures), Kind::Tuple(fields)) => {
+                if fields.len() != field_signatures.len() {
+                    return Err(self.error(format!(
+                        "expected a tuple of {} items, found {}",
+                        field_signatures.len(),
+                        fields.len(),
+                    )));
+                }
+
+                let mut builder = StructureBuilder::new();
+                for (field, signature) in fields.iter().zip(field_signatures) {
+                    builder = builder.append_field(field.build(signature)?);
+                }
+
+                Value::Structure(builder.build_with_signature(Signature::from(signature)))
+            }
+            #[cfg(feature = "gvariant")]
+            (E::Maybe(_), Kind::Nothing) => {
+                Value::Maybe(Maybe::nothing_full_signature(Signature::from(signature)))
+            }
+            #[cfg(feature = "gvariant")]
+            (E::Maybe(inner_signature), Kind::Just(inner)) => {
+                Value::Maybe(Maybe::just_full_signature(
+                    inner.build(inner_signature)?,
+                    Signature::from(signature),
+                ))
+            }
+            #[cfg(feature = "gvariant")]
+            (E::Maybe(inner_signature), _) => Value::Maybe(Maybe::just_full_signature(
+                self.build(inner_signature)?,
+                Signature::from(signature),
+            )),
+            (_, Kind::Typed(annotation, _)) => {
+                return Err(self.error(format!(
+                    "expected a value of type `{signature}`, found a value of type `{annotation}`"
+                )));
+            }
+            _ => return Err(self.error(format!("expected a value of type `{signature}`"))),
+        };
+
+        Ok(value)
+    }
+
+    /// Build the key and value of a dictionary entry of type `signature`.
+    fn dict_entry(&self, signature: &SignatureEntry) -> Result<(Value<'static>, Value<'static>)> {
+        match (&self.kind, signature) {
+            (Kind::Typed(annotation, inner), _) if annotation == signature => {
+                inner.dict_entry(signature)
+            }
+            (
+                Kind::DictEntry(key, value),
+                SignatureEntry::DictEntry(key_signature, value_signature),
+            ) => Ok((key.build(key_signature)?, value.build(value_signature)?)),
+            _ => Err(self.error(format!("expected a dictionary entry of type `{signature}`"))),
+        }
+    }
+
+    fn integer<T: TryFrom<i128>>(&self, n: &str, ty: &str) -> Result<T> {
+        let (negative, digits) = match n.as_bytes().first() {
+            Some(b'-') => (true, &n[1..]),
+            Some(b'+') => (false, &n[1..]),
+            _ => (false, n),
+        };
+        let (radix, digits) = if let Some(hex) = digits
+            .strip_prefix("0x")
+            .or_else(|| digits.strip_prefix("0X"))
+        {
+            (16, hex)
+        } else if digits.len() > 1 && digits.starts_with('0') {
+            (8, &digits[1..])
+        } else {
+            (10, digits)
+        };
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return Err(self.error(format!("invalid {ty} `{n}`")));
+        }
+
+        i128::from_str_radix(digits, radix)
+            .ok()
+            .map(|n| if negative { -n } else { n })
+            .and_then(|n| T::try_from(n).ok())
+            .ok_or_else(|| self.error(format!("`{n}` is out of range for {ty}")))
+    }
+
+    fn float(&self, n: &str) -> Result<f64> {
+        if !is_float(n) {
+            return self.integer::<i64>(n, "double").map(|n| n as f64);
+        }
+
+        n.parse()
+            .map_err(|_| self.error(format!("invalid double `{n}`")))
+    }
+}
+
+/// The type of all `nodes` unified.
+fn unify_all<'n>(nodes: impl Iterator<Item = &'n Node>) -> Result<Ty> {
+    let mut ty = Ty::Unknown;
+    for node in nodes {
+        ty = ty.unify(node.ty()?).ok_or_else(|| {
+            node.error("the value doesn't have the same type as the previous ones")
+        })?;
+    }
+
+    Ok(ty)
+}
+
+fn is_float(n: &str) -> bool {
+    let n = n.trim_start_matches(['-', '+']);
+    let hex = n.starts_with("0x") || n.starts_with("0X");
+
+    (!hex && n.contains(['.', 'e', 'E'])) || is_special_float(n)
+}
+
+/// Whether `n` is infinity or NaN.
+fn is_special_float(n: &str) -> bool {
+    n.eq_ignore_ascii_case("inf")
+        || n.eq_ignore_ascii_case("infinity")
+        || n.eq_ignore_ascii_case("nan")
+}
+
+fn keyword_signature(keyword: &str) -> Option<SignatureEntry> {
+    let signature = match keyword {
+        "boolean" => SignatureEntry::Bool,
+        "byte" => SignatureEntry::U8,
+        "int16" => SignatureEntry::I16,
+        "uint16" => SignatureEntry::U16,
+        "int32" => SignatureEntry::I32,
+        "uint32" => SignatureEntry::U32,
+        #[cfg(unix)]
+        "handle" => SignatureEntry::Fd,
+        "int64" => SignatureEntry::I64,
+        "uint64" => SignatureEntry::U64,
+        "double" => SignatureEntry::F64,
+        "string" => SignatureEntry::Str,
+        "objectpath" => SignatureEntry::ObjectPath,
+        "signature" => SignatureEntry::Signature,
+        _ => return None,
+    };
+
+    Some(signature)
+}
+
+/// Whether values of type `signature` can be given as integer literals.
+fn is_numeric(signature: &SignatureEntry) -> bool {
+    use SignatureEntry as E;
+
+    #[cfg(unix)]
+    if *signature == E::Fd {
+        return true;
+    }
+
+    matches!(
+        signature,
+        E::U8 | E::I16 | E::U16 | E::I32 | E::U32 | E::I64 | E::U64 | E::F64
+    )
+}
+
+/// `depths` one maybe deeper. Maybes are counted as arrays when encoded as such.
+fn inc_maybe(depths: ContainerDepths) -> crate::Result<ContainerDepths> {
+    #[cfg(all(feature = "gvariant", not(feature = "option-as-array")))]
+    return depths.inc_maybe();
+
+    #[cfg(not(all(feature = "gvariant", not(feature = "option-as-array"))))]
+    depths.inc_array()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{Array, ObjectPath, Signature, Value};
+
+    use super::{parse, print};
+
+    fn parse_as(text: &str, signature: &str) -> Value<'static> {
+        parse(text, Some(&Signature::try_from(signature).unwrap())).unwrap()
+    }
+
+    #[test]
+    fn basic() {
+        assert_eq!(parse("true", None).unwrap(), Value::Bool(true));
+        assert_eq!(parse(" 42 ", None).unwrap(), Value::I32(42));
+        assert_eq!(parse("-1.5", None).unwrap(), Value::F64(-1.5));
+        assert_eq!(parse("3.", None).unwrap(), Value::F64(3.));
+        assert_eq!(parse("byte 0xff", None).unwrap(), Value::U8(255));
+        assert_eq!(parse("uint64 010", None).unwrap(), Value::U64(8));
+        assert_eq!(parse("@x -7", None).unwrap(), Value::I64(-7));
+        assert_eq!(parse("'it\\'s'", None).unwrap(), Value::from("it's"));
+        assert_eq!(
+            parse(r#""\té\u{1F600}\101""#, None).unwrap(),
+            Value::from("\té😀A")
+        );
+        assert_eq!(
+            parse("objectpath '/org/zbus'", None).unwrap(),
+            Value::from(ObjectPath::try_from("/org/zbus").unwrap()),
+        );
+        assert_eq!(
+            parse("@g 'a{sv}'", None).unwrap(),
+            Value::from(Signature::try_from("a{sv}").unwrap()),
+        );
+        assert_eq!(parse_as("42", "q"), Value::U16(42));
+        assert_eq!(parse_as("42", "d"), Value::F64(42.));
+        assert_eq!(parse_as("inf", "d"), Value::F64(f64::INFINITY));
+    }
+
+    #[test]
+    fn containers() {
+        let value = parse("{'brightness': <uint32 80>, 'modes': <['a', 'b']>}", None).unwrap();
+        let mut expected = HashMap::new();
+        expected.insert("brightness", Value::new(80u32));
+        expected.insert("modes", Value::new(vec!["a", "b"]));
+        assert_eq!(value, Value::from(expected));
+
+        // Elements are unified.
+        assert_eq!(parse("[1, 2.5]", None).unwrap(), Value::from(vec![1., 2.5]));
+        assert_eq!(
+            parse("[1, uint32 2]", None).unwrap(),
+            Value::from(vec![1u32, 2])
+        );
+        assert_eq!(
+            parse("[{'a', @o '/a'}, {'b', '/b'}]", None)
+                .unwrap()
+                .value_signature(),
+            "a{so}",
+        );
+
+        assert_eq!(
+            parse("@as []", None).unwrap(),
+            Value::from(Array::new(Signature::try_from("s").unwrap())),
+        );
+        assert_eq!(parse("b'ab'", None).unwrap(), Value::from(b"ab\0".to_vec()));
+        assert_eq!(
+            parse("(1, 'a', (true,))", None).unwrap(),
+            Value::from((1, "a", (true,))),
+        );
+        assert_eq!(
+            parse_as("(1, [2, 3], {4: 5})", "(yaqa{xt})").value_signature(),
+            "(yaqa{xt})",
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut dict = HashMap::new();
+        dict.insert("name", Value::new("zbus"));
+        dict.insert("bytes", Value::new(vec![0u8, 1]));
+        dict.insert("empty", Value::new(Vec::<i64>::new()));
+        dict.insert("nested", Value::new(Value::new((1u16, -2i16, 0.5))));
+        let value = Value::from((dict, "quote\"s\n", ObjectPath::try_from("/").unwrap()));
+
+        let text = print(&value, true);
+        assert_eq!(text, value.to_string());
+        assert_eq!(parse(&text, None).unwrap(), value);
+
+        let text = print(&value, false);
+        assert_eq!(parse_as(&text, "(a{sv}so)"), value);
+    }
+
+    #[cfg(all(feature = "gvariant", not(feature = "option-as-array")))]
+    #[test]
+    fn maybe() {
+        use crate::Maybe;
+
+        assert_eq!(
+            parse("[just 1, nothing, 3]", None).unwrap(),
+            Value::from(vec![Some(1), None, Some(3)]),
+        );
+        assert_eq!(
+            parse("@mi nothing", None).unwrap(),
+            Value::from(Maybe::nothing(Signature::try_from("i").unwrap())),
+        );
+
+        let value = parse_as("just nothing", "mmu");
+        assert_eq!(parse(&print(&value, true), None).unwrap(), value);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |text: &str| {
+            let e = parse(text, None).unwrap_err();
+            (e.span(), e.message().to_string())
+        };
+
+        assert_eq!(error(""), (0..0, "expected a value".into()));
+        assert_eq!(error("[1, 'two', 3]").0, 4..9);
+        assert_eq!(error("[1, 2").0, 5..5);
+        assert_eq!(error("(1 2)"), (3..4, "expected `,` or `)`".into()));
+        assert_eq!(error("[]").0, 0..2);
+        assert_eq!(error("'abc").0, 0..4);
+        assert_eq!(error("'\\u12'").0, 1..6);
+        assert_eq!(error("uint32 'a'").0, 7..10);
+        assert_eq!(error("byte 256").0, 5..8);
+        assert_eq!(error("@z 1").0, 1..2);
+        assert_eq!(error("1 2").0, 2..3);
+        assert_eq!(error("foo").0, 0..3);
+        assert_eq!(error("objectpath 'nope'").0, 11..17);
+        assert_eq!(
+            parse("[1, 2]", Some(&Signature::try_from("as").unwrap()))
+                .unwrap_err()
+                .span(),
+            1..2,
+        );
+        assert_eq!(
+            parse("1 2", None).unwrap_err().to_string(),
+            "2-3: unexpected text after the value",
+        );
+        assert_eq!(error("@y @y 1").0, 3..4);
+        assert_eq!(error("uint32 int32 1").0, 7..12);
+    }
+
+    #[test]
+    fn signature() {
+        for signature in ["", "ii"] {
+            let signature = Signature::try_from(signature).unwrap();
+            assert!(parse("1", Some(&signature)).is_err());
+        }
+        assert!(parse("@a{si {}", None).is_err());
+    }
+
+    #[test]
+    fn depth() {
+        let nested =
+            |open: &str, close: &str, n: usize| format!("{}1{}", open.repeat(n), close.repeat(n));
+
+        assert!(parse(&nested("[", "]", 32), None).is_ok());
+        assert!(parse(&nested("[", "]", 33), None).is_err());
+        assert!(parse(&nested("(", ",)", 32), None).is_ok());
+        assert!(parse(&nested("(", ",)", 33), None).is_err());
+        assert!(parse(&nested("<", ">", 64), None).is_ok());
+        assert!(parse(&nested("<", ">", 65), None).is_err());
+        assert!(parse(&format!("@{}y []", "a".repeat(40)), None).is_err());
+
+        // Way too deep for the stack, if it wasn't limited.
+        assert!(parse(&"[".repeat(200_000), None).is_err());
+        assert!(parse(&"<".repeat(200_000), None).is_err());
+        assert!(parse(&"just ".repeat(200_000), None).is_err());
+        assert!(parse(&"@y ".repeat(200_000), None).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{utils::*, Signature};
+use crate::{utils::*, Signature, StaticSignature};
 use serde::de::{Deserialize, DeserializeSeed};
 use std::{
     marker::PhantomData,
@@ -19,6 +19,10 @@ use std::{
 /// container types, such as, arrays, slices, tuples, [`Vec`] and [`HashMap`]. For easy
 /// implementation for custom types, use `Type` derive macro from [zvariant_derive] crate.
 ///
+/// Implementations should provide the signature at compile time, through [`Type::SIGNATURE`], so
+/// that [`Type::signature`] doesn't need to build it on every call. Implementations that only
+/// provide [`Type::signature`] are still supported though.
+///
 /// If your type's signature cannot be determined statically, you should implement the
 /// [DynamicType] trait instead, which is otherwise automatically implemented if you implement this
 /// trait.
@@ -32,6 +36,29 @@ use std::{
 /// [`HashMap`]: https://doc.rust-lang.org/std/collections/struct.HashMap.html
 /// [zvariant_derive]: https://docs.rs/zvariant_derive/latest/zvariant_derive/
 pub trait Type {
+    /// The signature for the implementing type, built at compile time.
+    ///
+    /// This defaults to [`StaticSignature::UNKNOWN`], in which case [`Type::signature`] must be
+    /// implemented. Note that the signatures of containers of such types (e.g `Vec<T>`) are then
+    /// unknown at compile time as well, and get built on every call to their [`Type::signature`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zvariant::{StaticSignature, Type};
+    ///
+    /// struct Point(i32, i32);
+    ///
+    /// impl Type for Point {
+    ///     const SIGNATURE: &'static StaticSignature =
+    ///         &StaticSignature::structure(&[i32::SIGNATURE, i32::SIGNATURE]);
+    /// }
+    ///
+    /// assert_eq!(Point::signature(), "(ii)");
+    /// assert_eq!(<Vec<Point>>::SIGNATURE.as_str(), Some("a(ii)"));
+    /// ```
+    const SIGNATURE: &'static StaticSignature = &StaticSignature::UNKNOWN;
+
     /// Get the signature for the implementing type.
     ///
     /// # Example
@@ -46,7 +73,27 @@ pub trait Type {
     /// assert_eq!(<(u32, &str, &[u64])>::signature(), "(usat)");
     /// assert_eq!(<HashMap<u8, &str>>::signature(), "a{ys}");
     /// ```
-    fn signature() -> Signature<'static>;
+    ///
+    /// The default implementation returns [`Type::SIGNATURE`], without allocating.
+    fn signature() -> Signature<'static> {
+        #[allow(clippy::let_unit_value)]
+        let () = KnownSignature::<Self>::ASSERT;
+
+        Self::SIGNATURE
+            .as_signature()
+            .expect("signature checked at compile time")
+    }
+}
+
+// Fails the build for implementations that provide neither `Type::SIGNATURE` nor
+// `Type::signature`.
+struct KnownSignature<T: ?Sized>(PhantomData<T>);
+
+impl<T: Type + ?Sized> KnownSignature<T> {
+    const ASSERT: () = assert!(
+        T::SIGNATURE.is_known(),
+        "`Type` implementations must provide either `SIGNATURE` or `signature`",
+    );
 }
 
 /// Types with dynamic signatures.
@@ -90,6 +137,8 @@ impl<T> Type for PhantomData<T>
 where
     T: Type + ?Sized,
 {
+    const SIGNATURE: &'static StaticSignature = T::SIGNATURE;
+
     fn signature() -> Signature<'static> {
         T::signature()
     }
@@ -146,9 +195,13 @@ macro_rules! array_type {
         where
             T: Type,
         {
+            const SIGNATURE: &'static StaticSignature = &StaticSignature::array(T::SIGNATURE);
+
             #[inline]
             fn signature() -> Signature<'static> {
-                Signature::from_string_unchecked(format!("a{}", T::signature()))
+                Self::SIGNATURE.as_signature().unwrap_or_else(|| {
+                    Signature::from_string_unchecked(format!("a{}", T::signature()))
+                })
             }
         }
     };
@@ -162,6 +215,8 @@ where
     T: Type + Eq + Hash,
     S: BuildHasher,
 {
+    const SIGNATURE: &'static StaticSignature = <[T]>::SIGNATURE;
+
     #[inline]
     fn signature() -> Signature<'static> {
         <[T]>::signature()
@@ -173,6 +228,8 @@ impl<T, const CAP: usize> Type for arrayvec::ArrayVec<T, CAP>
 where
     T: Type,
 {
+    const SIGNATURE: &'static StaticSignature = <[T]>::SIGNATURE;
+
     #[inline]
     fn signature() -> Signature<'static> {
         <[T]>::signature()
@@ -181,18 +238,12 @@ where
 
 #[cfg(feature = "arrayvec")]
 impl<const CAP: usize> Type for arrayvec::ArrayString<CAP> {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        <&str>::signature()
-    }
+    const SIGNATURE: &'static StaticSignature = <&str>::SIGNATURE;
 }
 
 // Empty type deserves empty signature
 impl Type for () {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("")
-    }
+    const SIGNATURE: &'static StaticSignature = &StaticSignature::from_str_unchecked("");
 }
 
 macro_rules! deref_impl {
@@ -201,6 +252,8 @@ macro_rules! deref_impl {
         <$($desc:tt)+
     ) => {
         impl <$($desc)+ {
+            const SIGNATURE: &'static StaticSignature = <$type>::SIGNATURE;
+
             #[inline]
             fn signature() -> Signature<'static> {
                 <$type>::signature()
@@ -223,9 +276,13 @@ impl<T> Type for Option<T>
 where
     T: Type,
 {
+    const SIGNATURE: &'static StaticSignature = &StaticSignature::maybe(T::SIGNATURE);
+
     #[inline]
     fn signature() -> Signature<'static> {
-        Signature::from_string_unchecked(format!("m{}", T::signature()))
+        Self::SIGNATURE
+            .as_signature()
+            .unwrap_or_else(|| Signature::from_string_unchecked(format!("m{}", T::signature())))
     }
 }
 
@@ -234,9 +291,13 @@ impl<T> Type for Option<T>
 where
     T: Type,
 {
+    const SIGNATURE: &'static StaticSignature = &StaticSignature::array(T::SIGNATURE);
+
     #[inline]
     fn signature() -> Signature<'static> {
-        Signature::from_string_unchecked(format!("a{}", T::signature()))
+        Self::SIGNATURE
+            .as_signature()
+            .unwrap_or_else(|| Signature::from_string_unchecked(format!("a{}", T::signature())))
     }
 }
 
@@ -249,7 +310,14 @@ macro_rules! tuple_impls {
             where
                 $($name: Type,)+
             {
+                const SIGNATURE: &'static StaticSignature =
+                    &StaticSignature::structure(&[$($name::SIGNATURE),+]);
+
                 fn signature() -> Signature<'static> {
+                    if let Some(signature) = Self::SIGNATURE.as_signature() {
+                        return signature;
+                    }
+
                     let mut sig = String::with_capacity(255);
                     sig.push(STRUCT_SIG_START_CHAR);
                     $(
@@ -292,8 +360,15 @@ impl<T, const N: usize> Type for [T; N]
 where
     T: Type,
 {
+    const SIGNATURE: &'static StaticSignature =
+        &StaticSignature::repeated_structure(T::SIGNATURE, N);
+
     #[allow(clippy::reversed_empty_ranges)]
     fn signature() -> Signature<'static> {
+        if let Some(signature) = Self::SIGNATURE.as_signature() {
+            return signature;
+        }
+
         let mut sig = String::with_capacity(255);
         sig.push(STRUCT_SIG_START_CHAR);
         for _ in 0..N {
@@ -322,9 +397,14 @@ macro_rules! map_impl {
             V: Type,
             $($typaram: $bound,)*
         {
+            const SIGNATURE: &'static StaticSignature =
+                &StaticSignature::dict(K::SIGNATURE, V::SIGNATURE);
+
             #[inline]
             fn signature() -> Signature<'static> {
-                Signature::from_string_unchecked(format!("a{{{}{}}}", K::signature(), V::signature()))
+                Self::SIGNATURE.as_signature().unwrap_or_else(|| {
+                    Signature::from_string_unchecked(format!("a{{{}{}}}", K::signature(), V::signature()))
+                })
             }
         }
     }
@@ -334,42 +414,28 @@ map_impl!(BTreeMap<K: Ord, V>);
 map_impl!(HashMap<K: Eq + Hash, V, H: BuildHasher>);
 
 impl Type for Duration {
-    fn signature() -> Signature<'static> {
-        <(u64, u32)>::signature()
-    }
+    const SIGNATURE: &'static StaticSignature = <(u64, u32)>::SIGNATURE;
 }
 
 impl Type for SystemTime {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        <(
-            // seconds
-            u64,
-            // nano
-            u32,
-        )>::signature()
-    }
+    const SIGNATURE: &'static StaticSignature = <(
+        // seconds
+        u64,
+        // nano
+        u32,
+    )>::SIGNATURE;
 }
 
 impl Type for Ipv4Addr {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        <[u8; 4]>::signature()
-    }
+    const SIGNATURE: &'static StaticSignature = <[u8; 4]>::SIGNATURE;
 }
 
 impl Type for Ipv6Addr {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        <[u8; 16]>::signature()
-    }
+    const SIGNATURE: &'static StaticSignature = <[u8; 16]>::SIGNATURE;
 }
 
 impl Type for IpAddr {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        <(u32, &[u8])>::signature()
-    }
+    const SIGNATURE: &'static StaticSignature = <(u32, &[u8])>::SIGNATURE;
 }
 
 // BitFlags
@@ -378,6 +444,8 @@ impl<F> Type for enumflags2::BitFlags<F>
 where
     F: Type + enumflags2::BitFlag,
 {
+    const SIGNATURE: &'static StaticSignature = F::SIGNATURE;
+
     #[inline]
     fn signature() -> Signature<'static> {
         F::signature()
@@ -386,25 +454,19 @@ where
 
 #[cfg(feature = "serde_bytes")]
 impl Type for serde_bytes::Bytes {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("ay")
-    }
+    const SIGNATURE: &'static StaticSignature = &StaticSignature::from_str_unchecked("ay");
 }
 
 #[cfg(feature = "serde_bytes")]
 impl Type for serde_bytes::ByteBuf {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("ay")
-    }
+    const SIGNATURE: &'static StaticSignature = &StaticSignature::from_str_unchecked("ay");
 }
 
 #[allow(unused)]
 macro_rules! static_str_type {
     ($ty:ty) => {
         impl Type for $ty {
-            fn signature() -> Signature<'static> {
-                <&str>::signature()
-            }
+            const SIGNATURE: &'static StaticSignature = <&str>::SIGNATURE;
         }
     };
 }
@@ -414,9 +476,7 @@ static_str_type!(PathBuf);
 
 #[cfg(feature = "uuid")]
 impl Type for uuid::Uuid {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("ay")
-    }
+    const SIGNATURE: &'static StaticSignature = &StaticSignature::from_str_unchecked("ay");
 }
 
 #[cfg(feature = "url")]
@@ -426,122 +486,104 @@ static_str_type!(url::Url);
 // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L110
 #[cfg(feature = "time")]
 impl Type for time::Date {
-    fn signature() -> Signature<'static> {
-        // Serialized as a (year, ordinal) tuple:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L92
-        <(i32, u16)>::signature()
-    }
+    // Serialized as a (year, ordinal) tuple:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L92
+    const SIGNATURE: &'static StaticSignature = <(i32, u16)>::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::Duration {
-    fn signature() -> Signature<'static> {
-        // Serialized as a (whole seconds, nanoseconds) tuple:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L119
-        <(i64, i32)>::signature()
-    }
+    // Serialized as a (whole seconds, nanoseconds) tuple:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L119
+    const SIGNATURE: &'static StaticSignature = <(i64, i32)>::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::OffsetDateTime {
-    fn signature() -> Signature<'static> {
-        // Serialized as a tuple:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L155
-        <(
-            // year
-            i32,
-            // ordinal
-            u16,
-            // hour
-            u8,
-            // minute
-            u8,
-            // second
-            u8,
-            // nanosecond
-            u32,
-            // offset.whole_hours
-            i8,
-            // offset.minutes_past_hour
-            i8,
-            // offset.seconds_past_minute
-            i8,
-        )>::signature()
-    }
+    // Serialized as a tuple:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L155
+    const SIGNATURE: &'static StaticSignature = <(
+        // year
+        i32,
+        // ordinal
+        u16,
+        // hour
+        u8,
+        // minute
+        u8,
+        // second
+        u8,
+        // nanosecond
+        u32,
+        // offset.whole_hours
+        i8,
+        // offset.minutes_past_hour
+        i8,
+        // offset.seconds_past_minute
+        i8,
+    )>::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::PrimitiveDateTime {
-    fn signature() -> Signature<'static> {
-        // Serialized as a tuple:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L200
-        <(
-            // year
-            i32,
-            // ordinal
-            u16,
-            // hour
-            u8,
-            // minute
-            u8,
-            // second
-            u8,
-            // nanosecond
-            u32,
-        )>::signature()
-    }
+    // Serialized as a tuple:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L200
+    const SIGNATURE: &'static StaticSignature = <(
+        // year
+        i32,
+        // ordinal
+        u16,
+        // hour
+        u8,
+        // minute
+        u8,
+        // second
+        u8,
+        // nanosecond
+        u32,
+    )>::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::Time {
-    fn signature() -> Signature<'static> {
-        // Serialized as a tuple:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L246
-        <(
-            // hour
-            u8,
-            // minute
-            u8,
-            // second
-            u8,
-            // nanosecond
-            u32,
-        )>::signature()
-    }
+    // Serialized as a tuple:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L246
+    const SIGNATURE: &'static StaticSignature = <(
+        // hour
+        u8,
+        // minute
+        u8,
+        // second
+        u8,
+        // nanosecond
+        u32,
+    )>::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::UtcOffset {
-    fn signature() -> Signature<'static> {
-        // Serialized as a (whole hours, minutes past hour, seconds past minute) tuple:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L282
-        <(i8, i8, i8)>::signature()
-    }
+    // Serialized as a (whole hours, minutes past hour, seconds past minute) tuple:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L282
+    const SIGNATURE: &'static StaticSignature = <(i8, i8, i8)>::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::Weekday {
-    fn signature() -> Signature<'static> {
-        // Serialized as number from Monday:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L312
-        u8::signature()
-    }
+    // Serialized as number from Monday:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L312
+    const SIGNATURE: &'static StaticSignature = u8::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::Month {
-    fn signature() -> Signature<'static> {
-        // Serialized as month number:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L337
-        u8::signature()
-    }
+    // Serialized as month number:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L337
+    const SIGNATURE: &'static StaticSignature = u8::SIGNATURE;
 }
 
 #[cfg(feature = "chrono")]
 impl<Tz: chrono::TimeZone> Type for chrono::DateTime<Tz> {
-    fn signature() -> Signature<'static> {
-        <&str>::signature()
-    }
+    const SIGNATURE: &'static StaticSignature = <&str>::SIGNATURE;
 }
 
 #[cfg(feature = "chrono")]
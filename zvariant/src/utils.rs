@@ -1,11 +1,11 @@
 use std::slice::SliceIndex;
 
 #[cfg(feature = "gvariant")]
-use crate::{serialized::Format, signature_parser::SignatureParser, Basic, ObjectPath, Signature};
+use crate::signature_parser::SignatureParser;
+use crate::{serialized::Format, Basic, ObjectPath, Signature};
 use crate::{Error, Result};
 
 #[cfg(unix)]
-#[cfg(feature = "gvariant")]
 use crate::Fd;
 
 /// The prefix of ARRAY type signature, as a character. Provided for manual signature creation.
@@ -35,7 +35,6 @@ pub const DICT_ENTRY_SIG_START_STR: &str = "{";
 /// creation.
 pub const DICT_ENTRY_SIG_END_STR: &str = "}";
 
-#[cfg(feature = "gvariant")]
 pub(crate) const DICT_ENTRY_ALIGNMENT_DBUS: usize = 8;
 /// The VARIANT type signature. Provided for manual signature creation.
 pub const VARIANT_SIGNATURE_CHAR: char = 'v';
@@ -91,7 +90,6 @@ pub(crate) fn f64_to_f32(value: f64) -> f32 {
 }
 
 // `signature` must be **one** complete and correct signature. Expect panics otherwise!
-#[cfg(feature = "gvariant")]
 pub(crate) fn alignment_for_signature(signature: &Signature<'_>, format: Format) -> Result<usize> {
     let alignment = match signature
         .as_bytes()
@@ -183,7 +181,6 @@ macro_rules! check_child_value_signature {
     }};
 }
 
-#[cfg(feature = "gvariant")]
 fn alignment_for_single_child_type_signature(
     #[allow(unused)] signature: &Signature<'_>,
     format: Format,
@@ -200,7 +197,6 @@ fn alignment_for_single_child_type_signature(
     }
 }
 
-#[cfg(feature = "gvariant")]
 fn alignment_for_array_signature(signature: &Signature<'_>, format: Format) -> Result<usize> {
     alignment_for_single_child_type_signature(signature, format, ARRAY_ALIGNMENT_DBUS)
 }
@@ -210,7 +206,6 @@ fn alignment_for_maybe_signature(signature: &Signature<'_>, format: Format) -> R
     alignment_for_single_child_type_signature(signature, format, 1)
 }
 
-#[cfg(feature = "gvariant")]
 fn alignment_for_struct_signature(
     #[allow(unused)] signature: &Signature<'_>,
     format: Format,
@@ -248,7 +243,6 @@ fn alignment_for_struct_signature(
     }
 }
 
-#[cfg(feature = "gvariant")]
 fn alignment_for_dict_entry_signature(
     #[allow(unused)] signature: &Signature<'_>,
     format: Format,
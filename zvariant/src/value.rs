@@ -22,8 +22,8 @@ use static_assertions::assert_impl_all;
 
 use crate::{
     array_display_fmt, dict_display_fmt, signature_parser::SignatureParser, structure_display_fmt,
-    utils::*, Array, Basic, Dict, DynamicType, ObjectPath, OwnedValue, Signature, Str, Structure,
-    StructureBuilder, Type,
+    utils::*, Array, Basic, Dict, DynamicType, ObjectPath, OwnedValue, Signature, StaticSignature,
+    Str, Structure, StructureBuilder, Type,
 };
 
 #[cfg(feature = "gvariant")]
@@ -737,6 +737,14 @@ struct ValueSeed<'de, T> {
     phantom: PhantomData<T>,
 }
 
+/// A seed for deserializing an encoded value of type `signature` as a [`Value`].
+pub(crate) fn value_seed(signature: Signature<'_>) -> impl DeserializeSeed<'_, Value = Value<'_>> {
+    ValueSeed::<Value<'_>> {
+        signature,
+        phantom: PhantomData,
+    }
+}
+
 impl<'de, T> ValueSeed<'de, T>
 where
     T: Deserialize<'de>,
@@ -860,6 +868,7 @@ where
         self.visit_string(String::from(value))
     }
 
+    value_seed_str_method!(visit_string, String, from_string_unchecked);
     value_seed_str_method!(visit_borrowed_str, &'de str, from_str_unchecked);
 
     fn visit_seq<V>(self, visitor: V) -> Result<Value<'de>, V::Error>
@@ -986,9 +995,8 @@ where
 }
 
 impl<'a> Type for Value<'a> {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked(VARIANT_SIGNATURE_STR)
-    }
+    const SIGNATURE: &'static StaticSignature =
+        &StaticSignature::from_str_unchecked(VARIANT_SIGNATURE_STR);
 }
 
 impl<'a> TryFrom<&Value<'a>> for Value<'a> {
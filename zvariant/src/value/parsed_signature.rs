@@ -5,7 +5,7 @@
 //! represents the full type signature being reprsented by a given string
 //! or sequence of bytes.
 
-use std::collections::VecDeque;
+use std::{collections::VecDeque, fmt, str::FromStr};
 
 use nom::{
     branch::alt, bytes::complete::tag, character::complete::anychar, combinator::all_consuming,
@@ -13,43 +13,83 @@ use nom::{
 };
 
 use crate::{
-    serialized::Format, Basic, Fd, ObjectPath, Signature, Type, ARRAY_SIGNATURE_STR,
-    STRUCT_SIG_END_STR, STRUCT_SIG_START_STR, VARIANT_SIGNATURE_CHAR, VARIANT_SIGNATURE_STR,
+    container_depths::ContainerDepths, serialized::Format, signature_parser::SignatureParser,
+    Basic, Fd, ObjectPath, Signature, Type, ARRAY_SIGNATURE_STR, STRUCT_SIG_END_STR,
+    STRUCT_SIG_START_STR, VARIANT_SIGNATURE_CHAR, VARIANT_SIGNATURE_STR,
 };
 
 /// A parsed DBus/GVariant type signature.
-#[derive(Debug, PartialEq, Clone)]
+///
+/// Unlike [`Signature`], which is a validated string, this is a tree of [`SignatureEntry`], one for
+/// each complete type of the signature. This makes it possible to walk the types of a signature
+/// without any string manipulation.
+///
+/// The `Display` implementation gives back the signature string, while the alternate form (`{:#}`)
+/// gives a human-readable description of the types.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{ParsedSignature, Signature, SignatureEntry};
+///
+/// let parsed: ParsedSignature = "sa{sv}".parse()?;
+/// assert_eq!(parsed.len(), 2);
+/// assert_eq!(
+///     parsed.iter().collect::<Vec<_>>(),
+///     [
+///         &SignatureEntry::Str,
+///         &SignatureEntry::dict(SignatureEntry::Str, SignatureEntry::Variant),
+///     ],
+/// );
+/// assert_eq!(format!("{parsed:#}"), "string, dict<string, variant>");
+///
+/// // And back to a `Signature`.
+/// assert_eq!(Signature::from(&parsed), "sa{sv}");
+/// # Ok::<(), zvariant::Error>(())
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
 pub struct ParsedSignature(VecDeque<SignatureEntry>);
 
 impl ParsedSignature {
+    /// Parse `signature`.
+    ///
+    /// Since [`Signature`] is always valid, this can't fail.
     pub fn new(signature: &Signature<'_>) -> Self {
-        match Self::parse_str(signature.as_str()) {
-            Ok(sig) => sig,
+        match SignatureEntry::parse(signature.as_bytes()) {
+            Ok(entries) => ParsedSignature(entries),
             Err(err) => panic!("A `Signature` should always produce a valid parseable signature string - signature: {:?}, error: {:?}", signature, err)
         }
     }
 
-    /// Parse a byte slice into a `ParsedSignature`. If the entire
-    /// slice is not consumed by the parsing, an error is returned.
+    /// The parsed signature of `T`.
+    pub fn from_type<T: Type + ?Sized>() -> Self {
+        Self::new(&T::signature())
+    }
+
+    /// Parse a byte slice into a `ParsedSignature`.
+    ///
+    /// The input is validated the same way as by [`Signature::try_from`], and the nesting of
+    /// containers is checked against the limits of the D-Bus specification, so an error is returned
+    /// for invalid signatures, including those exceeding the length or nesting depth limits.
     pub fn parse_bytes(input: &[u8]) -> crate::Result<ParsedSignature> {
-        match SignatureEntry::parse(input) {
-            Ok(entries) => Ok(ParsedSignature(entries)),
-            Err(err) => {
-                let reason = format!("Failed to parse signature. Reason: {:?}", err);
-                Err(crate::Error::Message(reason))
-            }
+        SignatureParser::validate(input)?;
+        let entries = SignatureEntry::parse(input)?;
+        for entry in &entries {
+            entry.check_depths(ContainerDepths::default())?;
         }
+
+        Ok(ParsedSignature(entries))
     }
 
-    /// Parse a string slice into a `ParsedSignature`. If the entire
-    /// string is not consumed by the parsing, an error is returned.
+    /// Parse a string slice into a `ParsedSignature`.
+    ///
+    /// See [`ParsedSignature::parse_bytes`] for details.
     pub fn parse_str(input: &str) -> crate::Result<ParsedSignature> {
         Self::parse_bytes(input.as_bytes())
     }
 
-    /// Get the next entry in the signature to
-    /// be processed.
-    pub fn next(&mut self) -> Option<SignatureEntry> {
+    /// Get the next entry in the signature to be processed, removing it from the queue.
+    pub(crate) fn next(&mut self) -> Option<SignatureEntry> {
         self.0.pop_front()
     }
 
@@ -59,6 +99,26 @@ impl ParsedSignature {
         self.0.front()
     }
 
+    /// Iterate over the complete types of the signature.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &SignatureEntry> + DoubleEndedIterator {
+        self.0.iter()
+    }
+
+    /// The number of complete types in the signature.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether the signature is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Append `entry` as a new complete type.
+    pub fn push(&mut self, entry: SignatureEntry) {
+        self.0.push_back(entry);
+    }
+
     /// Check if the signature matches a given
     /// type `T`.
     pub fn matches<T: Type>(&self) -> bool {
@@ -66,10 +126,45 @@ impl ParsedSignature {
     }
 }
 
-impl std::fmt::Display for ParsedSignature {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for entry in &self.0 {
-            write!(f, "{}", entry)?;
+impl IntoIterator for ParsedSignature {
+    type Item = SignatureEntry;
+    type IntoIter = std::collections::vec_deque::IntoIter<SignatureEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'p> IntoIterator for &'p ParsedSignature {
+    type Item = &'p SignatureEntry;
+    type IntoIter = std::collections::vec_deque::Iter<'p, SignatureEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl FromIterator<SignatureEntry> for ParsedSignature {
+    fn from_iter<I: IntoIterator<Item = SignatureEntry>>(iter: I) -> Self {
+        ParsedSignature(iter.into_iter().collect())
+    }
+}
+
+impl FromStr for ParsedSignature {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        Self::parse_str(s)
+    }
+}
+
+impl fmt::Display for ParsedSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, entry) in self.0.iter().enumerate() {
+            if f.alternate() && i > 0 {
+                f.write_str(", ")?;
+            }
+            entry.fmt(f)?;
         }
 
         Ok(())
@@ -88,6 +183,18 @@ impl<'a> From<&ParsedSignature> for Signature<'a> {
     }
 }
 
+impl From<&Signature<'_>> for ParsedSignature {
+    fn from(signature: &Signature<'_>) -> Self {
+        ParsedSignature::new(signature)
+    }
+}
+
+impl From<Signature<'_>> for ParsedSignature {
+    fn from(signature: Signature<'_>) -> Self {
+        ParsedSignature::new(&signature)
+    }
+}
+
 impl From<SignatureEntry> for ParsedSignature {
     fn from(entry: SignatureEntry) -> Self {
         ParsedSignature(vec![entry].into())
@@ -106,38 +213,124 @@ impl From<VecDeque<SignatureEntry>> for ParsedSignature {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// A single complete type of a [`ParsedSignature`].
+///
+/// Dictionaries are represented as they are encoded, i.e an [`Array`](SignatureEntry::Array) of
+/// [`DictEntry`](SignatureEntry::DictEntry). [`SignatureEntry::dict`] creates one.
+///
+/// Note that the construction helpers don't check that the result is a valid signature (e.g that
+/// the key of a dictionary is a basic type). Use [`Signature::try_from`] on the string
+/// representation to validate it.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[non_exhaustive]
 pub enum SignatureEntry {
+    /// `y`: a byte.
     U8,
+    /// `q`: an unsigned 16-bit integer.
     U16,
+    /// `u`: an unsigned 32-bit integer.
     U32,
+    /// `t`: an unsigned 64-bit integer.
     U64,
+    /// `n`: a signed 16-bit integer.
     I16,
+    /// `i`: a signed 32-bit integer.
     I32,
+    /// `x`: a signed 64-bit integer.
     I64,
+    /// `d`: a double precision floating point number.
     F64,
+    /// `b`: a boolean.
     Bool,
+    /// `s`: a string.
     Str,
+    /// `o`: an object path.
     ObjectPath,
+    /// `g`: a signature.
     Signature,
+    /// `v`: a variant.
     Variant,
+    /// `a`: an array of the given element type.
     Array(Box<SignatureEntry>),
+    /// `(...)`: a structure of the given field types.
     Struct(VecDeque<SignatureEntry>),
+    /// `{..}`: a dictionary entry of the given key and value types.
     DictEntry(Box<SignatureEntry>, Box<SignatureEntry>),
 
+    /// `h`: a file descriptor.
     #[cfg(unix)]
     Fd,
 
+    /// `m`: a GVariant maybe of the given type.
     #[cfg(feature = "gvariant")]
     Maybe(Box<SignatureEntry>),
 }
 
 impl SignatureEntry {
+    /// An array of `element`.
+    pub fn array(element: SignatureEntry) -> Self {
+        SignatureEntry::Array(Box::new(element))
+    }
+
+    /// A dictionary with `key` keys and `value` values.
+    pub fn dict(key: SignatureEntry, value: SignatureEntry) -> Self {
+        SignatureEntry::array(SignatureEntry::dict_entry(key, value))
+    }
+
+    /// A dictionary entry of `key` and `value`.
+    pub fn dict_entry(key: SignatureEntry, value: SignatureEntry) -> Self {
+        SignatureEntry::DictEntry(Box::new(key), Box::new(value))
+    }
+
+    /// A structure of `fields`.
+    pub fn structure<I>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = SignatureEntry>,
+    {
+        SignatureEntry::Struct(fields.into_iter().collect())
+    }
+
+    /// A maybe of `inner`.
+    #[cfg(feature = "gvariant")]
+    pub fn maybe(inner: SignatureEntry) -> Self {
+        SignatureEntry::Maybe(Box::new(inner))
+    }
+
+    /// Whether this is a basic type, i.e one that can be used as a dictionary key.
+    pub fn is_basic(&self) -> bool {
+        !self.is_container()
+    }
+
+    /// Whether this is a container type (array, structure, dictionary entry, variant or maybe).
+    pub fn is_container(&self) -> bool {
+        match self {
+            SignatureEntry::Variant
+            | SignatureEntry::Array(_)
+            | SignatureEntry::Struct(_)
+            | SignatureEntry::DictEntry(_, _) => true,
+            #[cfg(feature = "gvariant")]
+            SignatureEntry::Maybe(_) => true,
+            _ => false,
+        }
+    }
+
+    /// The key and value types if this is a dictionary.
+    pub fn as_dict(&self) -> Option<(&SignatureEntry, &SignatureEntry)> {
+        match self {
+            SignatureEntry::Array(element) => match &**element {
+                SignatureEntry::DictEntry(key, value) => Some((key, value)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Check if the entry matches a given type `T`.
     pub fn matches<T: Type>(&self) -> bool {
         T::signature() == signature_string!(&self.to_string())
     }
 
-    pub fn alignment(&self, format: Format) -> usize {
+    pub(crate) fn alignment(&self, format: Format) -> usize {
         match self {
             SignatureEntry::U8 => u8::alignment(format),
             SignatureEntry::U16 => u16::alignment(format),
@@ -165,7 +358,48 @@ impl SignatureEntry {
         }
     }
 
-    pub fn parse(input: &[u8]) -> crate::Result<VecDeque<SignatureEntry>> {
+    /// Parse the complete type at the start of `input`, returning it along with its length.
+    ///
+    /// The type is validated the same way as by [`ParsedSignature::parse_bytes`].
+    pub(crate) fn parse_prefix(input: &[u8]) -> crate::Result<(SignatureEntry, usize)> {
+        // Longer signatures are invalid anyway, and this bounds the recursion of the parser.
+        let input = &input[..input.len().min(255)];
+        let (rest, entry) = Self::parse_one(input).map_err(|err| {
+            crate::Error::Message(format!("Failed to parse signature. Reason: {:?}", err))
+        })?;
+        let len = input.len() - rest.len();
+        SignatureParser::validate(&input[..len])?;
+        entry.check_depths(ContainerDepths::default())?;
+
+        Ok((entry, len))
+    }
+
+    // Check the nesting of containers against the limits of the specification.
+    fn check_depths(&self, depths: ContainerDepths) -> crate::Result<()> {
+        match self {
+            SignatureEntry::Array(element) => element.check_depths(depths.inc_array()?),
+            SignatureEntry::Struct(fields) => {
+                let depths = depths.inc_structure()?;
+
+                fields
+                    .iter()
+                    .try_for_each(|field| field.check_depths(depths))
+            }
+            SignatureEntry::DictEntry(key, value) => {
+                let depths = depths.inc_structure()?;
+                key.check_depths(depths)?;
+
+                value.check_depths(depths)
+            }
+            #[cfg(all(feature = "gvariant", not(feature = "option-as-array")))]
+            SignatureEntry::Maybe(child) => child.check_depths(depths.inc_maybe()?),
+            #[cfg(all(feature = "gvariant", feature = "option-as-array"))]
+            SignatureEntry::Maybe(child) => child.check_depths(depths.inc_array()?),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn parse(input: &[u8]) -> crate::Result<VecDeque<SignatureEntry>> {
         match Self::parse_all(input) {
             Ok((_, parsed)) => Ok(parsed),
             Err(err) => {
@@ -236,12 +470,12 @@ impl SignatureEntry {
             Self::container_struct,
             Self::container_dict_entry,
             #[cfg(feature = "gvariant")]
-            Self::maybe,
+            Self::container_maybe,
         ))(input)
     }
 
     #[cfg(feature = "gvariant")]
-    fn maybe(input: &[u8]) -> IResult<&[u8], SignatureEntry> {
+    fn container_maybe(input: &[u8]) -> IResult<&[u8], SignatureEntry> {
         let (input, _) = tag("m")(input)?;
         let (input, sig) = Self::parse_one(input)?;
 
@@ -255,7 +489,7 @@ impl SignatureEntry {
             Self::container_struct,
             Self::container_dict_entry,
             #[cfg(feature = "gvariant")]
-            Self::maybe,
+            Self::container_maybe,
         ))(input)
     }
 
@@ -287,8 +521,27 @@ impl<'a> From<&mut SignatureEntry> for Signature<'a> {
     }
 }
 
-impl std::fmt::Display for SignatureEntry {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl FromStr for SignatureEntry {
+    type Err = crate::Error;
+
+    /// Parse a single complete type.
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let mut parsed = ParsedSignature::parse_str(s)?;
+        match (parsed.next(), parsed.next()) {
+            (Some(entry), None) => Ok(entry),
+            _ => Err(crate::Error::Message(format!(
+                "`{s}` is not a single complete type"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for SignatureEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return self.fmt_pretty(f);
+        }
+
         match self {
             SignatureEntry::U8 => f.write_str(u8::SIGNATURE_STR),
             SignatureEntry::U16 => f.write_str(u16::SIGNATURE_STR),
@@ -324,6 +577,50 @@ impl std::fmt::Display for SignatureEntry {
     }
 }
 
+impl SignatureEntry {
+    /// The human-readable form, using the GVariant type keywords for basic types.
+    fn fmt_pretty(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureEntry::U8 => f.write_str("byte"),
+            SignatureEntry::U16 => f.write_str("uint16"),
+            SignatureEntry::U32 => f.write_str("uint32"),
+            SignatureEntry::U64 => f.write_str("uint64"),
+            SignatureEntry::I16 => f.write_str("int16"),
+            SignatureEntry::I32 => f.write_str("int32"),
+            SignatureEntry::I64 => f.write_str("int64"),
+            SignatureEntry::F64 => f.write_str("double"),
+            SignatureEntry::Bool => f.write_str("boolean"),
+            SignatureEntry::Str => f.write_str("string"),
+            SignatureEntry::ObjectPath => f.write_str("objectpath"),
+            SignatureEntry::Signature => f.write_str("signature"),
+            SignatureEntry::Variant => f.write_str("variant"),
+            SignatureEntry::Array(element) => match &**element {
+                SignatureEntry::DictEntry(key, value) => write!(f, "dict<{key:#}, {value:#}>"),
+                element => write!(f, "array<{element:#}>"),
+            },
+            SignatureEntry::Struct(fields) => {
+                f.write_str("(")?;
+
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{field:#}")?;
+                }
+
+                f.write_str(")")
+            }
+            SignatureEntry::DictEntry(key, value) => write!(f, "{{{key:#}, {value:#}}}"),
+
+            #[cfg(unix)]
+            SignatureEntry::Fd => f.write_str("handle"),
+
+            #[cfg(feature = "gvariant")]
+            SignatureEntry::Maybe(inner) => write!(f, "maybe<{inner:#}>"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::Value;
@@ -700,4 +997,86 @@ mod test {
         assert!(!parsed.matches::<String>());
         assert!(!parsed.matches::<f64>());
     }
+
+    #[test]
+    fn iterate() {
+        let parsed: ParsedSignature = "ia{sv}(ss)".parse().unwrap();
+        assert_eq!(parsed.len(), 3);
+        let entries: Vec<_> = parsed.iter().map(ToString::to_string).collect();
+        assert_eq!(entries, ["i", "a{sv}", "(ss)"]);
+        assert_eq!(
+            parsed.iter().nth(1).unwrap().as_dict(),
+            Some((&SignatureEntry::Str, &SignatureEntry::Variant)),
+        );
+        assert_eq!(
+            parsed.clone().into_iter().collect::<ParsedSignature>(),
+            parsed
+        );
+        assert_eq!((&parsed).into_iter().count(), 3);
+
+        assert!(ParsedSignature::parse_str("").unwrap().is_empty());
+        assert_eq!(
+            ParsedSignature::from_type::<(u32, Vec<String>)>(),
+            ParsedSignature::from(SignatureEntry::structure([
+                SignatureEntry::U32,
+                SignatureEntry::array(SignatureEntry::Str),
+            ])),
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for signature in ["()", "a", "a{}", "(ss", "z"] {
+            assert!(
+                ParsedSignature::parse_str(signature).is_err(),
+                "{signature}"
+            );
+        }
+        // Too long.
+        assert!(ParsedSignature::parse_str(&"(y)".repeat(100)).is_err());
+        // Too deep.
+        assert!(ParsedSignature::parse_str(&("a".repeat(500000) + "y")).is_err());
+        assert!(ParsedSignature::parse_str(&("a".repeat(32) + "y")).is_ok());
+        assert!(ParsedSignature::parse_str(&("a".repeat(33) + "y")).is_err());
+        assert!(ParsedSignature::parse_str(&("(".repeat(33) + "y" + &")".repeat(33))).is_err());
+    }
+
+    #[test]
+    fn single_complete_type() {
+        assert_eq!(
+            "a{sv}".parse::<SignatureEntry>().unwrap(),
+            SignatureEntry::dict(SignatureEntry::Str, SignatureEntry::Variant),
+        );
+        assert!("ss".parse::<SignatureEntry>().is_err());
+        assert!("".parse::<SignatureEntry>().is_err());
+        assert!(SignatureEntry::U8.is_basic());
+        assert!(SignatureEntry::Variant.is_container());
+    }
+
+    #[test]
+    fn build_and_convert() {
+        let mut parsed = ParsedSignature::default();
+        parsed.push(SignatureEntry::ObjectPath);
+        parsed.push(SignatureEntry::dict(
+            SignatureEntry::Str,
+            SignatureEntry::dict(SignatureEntry::Str, SignatureEntry::Variant),
+        ));
+        let signature = Signature::from(&parsed);
+        assert_eq!(signature, "oa{sa{sv}}");
+        assert_eq!(ParsedSignature::from(&signature), parsed);
+    }
+
+    #[test]
+    fn pretty_print() {
+        let pretty = |s: &str| format!("{:#}", ParsedSignature::parse_str(s).unwrap());
+
+        assert_eq!(pretty("y"), "byte");
+        assert_eq!(pretty("sub"), "string, uint32, boolean");
+        assert_eq!(
+            pretty("a(sa{sv}ao)"),
+            "array<(string, dict<string, variant>, array<objectpath>)>"
+        );
+        #[cfg(feature = "gvariant")]
+        assert_eq!(pretty("mai"), "maybe<array<int32>>");
+    }
 }
@@ -1,7 +1,7 @@
 use proc_macro2::{Span, TokenStream};
-use quote::{format_ident, quote, ToTokens};
-use syn::{punctuated::Punctuated, spanned::Spanned, Data, DeriveInput, Error, Field};
-use zvariant_utils::{case, macros};
+use quote::{format_ident, quote};
+use syn::{punctuated::Punctuated, spanned::Spanned, Data, DeriveInput, Error, ExprPath, Field};
+use zvariant_utils::macros;
 
 use crate::utils::*;
 
@@ -15,19 +15,65 @@ fn dict_name_for_field(
     } else {
         let ident = f.ident.as_ref().unwrap().to_string();
 
-        match rename_all_attr {
-            Some("lowercase") => Ok(ident.to_ascii_lowercase()),
-            Some("UPPERCASE") => Ok(ident.to_ascii_uppercase()),
-            Some("PascalCase") => Ok(case::pascal_or_camel_case(&ident, true)),
-            Some("camelCase") => Ok(case::pascal_or_camel_case(&ident, false)),
-            Some("snake_case") => Ok(case::snake_case(&ident)),
-            None => Ok(ident),
-            Some(other) => Err(Error::new(
+        rename_identifier(ident, f.span(), rename_all_attr)
+    }
+}
+
+fn parse_path(path: &str, f: &Field, attr: &str) -> Result<ExprPath, Error> {
+    syn::parse_str(path).map_err(|e| {
+        Error::new(
+            f.span(),
+            format!("invalid path `{path}` in `{attr}` attribute: {e}"),
+        )
+    })
+}
+
+/// The expression giving the value of a field missing from the dictionary, if it has a default.
+fn default_for_field(
+    default: Option<Option<String>>,
+    f: &Field,
+) -> Result<Option<TokenStream>, Error> {
+    match default {
+        Some(Some(path)) => {
+            let path = parse_path(&path, f, "default")?;
+
+            Ok(Some(quote! { #path() }))
+        }
+        Some(None) => Ok(Some(quote! { ::std::default::Default::default() })),
+        None => Ok(None),
+    }
+}
+
+/// Ensure at most one field is flattened and the attributes of flattened fields make sense.
+fn check_flatten<'f>(
+    fields: impl IntoIterator<Item = &'f Field>,
+    deny_unknown_fields: bool,
+) -> Result<(), Error> {
+    let mut flattened = None;
+    for f in fields {
+        let attrs = FieldAttributes::parse(&f.attrs)?;
+        if !attrs.flatten {
+            continue;
+        }
+
+        if flattened.replace(f).is_some() {
+            return Err(Error::new(f.span(), "only one field can be flattened"));
+        }
+        if deny_unknown_fields {
+            return Err(Error::new(
+                f.span(),
+                "`flatten` can't be combined with `deny_unknown_fields`",
+            ));
+        }
+        if attrs.rename.is_some() || attrs.default.is_some() || attrs.skip {
+            return Err(Error::new(
                 f.span(),
-                format!("invalid `rename_all` attribute value {other}"),
-            )),
+                "`flatten` can't be combined with `rename`, `default` or `skip`",
+            ));
         }
     }
+
+    Ok(())
 }
 
 pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error> {
@@ -36,40 +82,80 @@ pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error>
         _ => return Err(Error::new(input.span(), "only structs supported")),
     };
 
-    let StructAttributes { rename_all, .. } = StructAttributes::parse(&input.attrs)?;
+    let StructAttributes {
+        rename_all,
+        deny_unknown_fields,
+        ..
+    } = StructAttributes::parse(&input.attrs)?;
+    check_flatten(&data.fields, deny_unknown_fields)?;
 
     let zv = zvariant_path();
     let mut entries = quote! {};
-    let mut num_entries: usize = 0;
+    let mut num_entries = Some(0usize);
 
     for f in &data.fields {
-        let FieldAttributes { rename } = FieldAttributes::parse(&f.attrs)?;
+        let FieldAttributes {
+            rename,
+            flatten,
+            skip,
+            skip_serializing_if,
+            ..
+        } = FieldAttributes::parse(&f.attrs)?;
+        if skip {
+            continue;
+        }
 
         let name = &f.ident;
-        let dict_name = dict_name_for_field(f, rename, rename_all.as_deref())?;
 
-        let is_option = macros::ty_is_option(&f.ty);
+        let e = if flatten {
+            num_entries = None;
 
-        let e = if is_option {
             quote! {
-                if self.#name.is_some() {
-                    map.serialize_entry(#dict_name, &#zv::SerializeValue(self.#name.as_ref().unwrap()))?;
-                }
+                #zv::export::serde::ser::Serialize::serialize(
+                    &self.#name,
+                    #zv::export::FlatMapSerializer(&mut map),
+                )?;
             }
         } else {
-            quote! {
-                map.serialize_entry(#dict_name, &#zv::SerializeValue(&self.#name))?;
+            let dict_name = dict_name_for_field(f, rename, rename_all.as_deref())?;
+            num_entries = num_entries.map(|n| n + 1);
+
+            if macros::ty_is_option(&f.ty) {
+                quote! {
+                    if self.#name.is_some() {
+                        map.serialize_entry(#dict_name, &#zv::SerializeValue(self.#name.as_ref().unwrap()))?;
+                    }
+                }
+            } else {
+                quote! {
+                    map.serialize_entry(#dict_name, &#zv::SerializeValue(&self.#name))?;
+                }
+            }
+        };
+
+        let e = match skip_serializing_if {
+            Some(path) => {
+                let path = parse_path(&path, f, "skip_serializing_if")?;
+
+                quote! {
+                    if !#path(&self.#name) {
+                        #e
+                    }
+                }
             }
+            None => e,
         };
 
         entries.extend(e);
-        num_entries += 1;
     }
 
     let generics = input.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let num_entries = num_entries.to_token_stream();
+    let num_entries = match num_entries {
+        Some(n) => quote! { ::std::option::Option::Some(#n) },
+        None => quote! { ::std::option::Option::None },
+    };
     Ok(quote! {
         #[allow(deprecated)]
         impl #impl_generics #zv::export::serde::ser::Serialize for #name #ty_generics
@@ -82,7 +168,7 @@ pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error>
                 use #zv::export::serde::ser::SerializeMap;
 
                 // zbus doesn't care about number of entries (it would need bytes instead)
-                let mut map = serializer.serialize_map(::std::option::Option::Some(#num_entries))?;
+                let mut map = serializer.serialize_map(#num_entries)?;
                 #entries
                 map.end()
             }
@@ -101,18 +187,45 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
         deny_unknown_fields,
         ..
     } = StructAttributes::parse(&input.attrs)?;
+    check_flatten(&data.fields, deny_unknown_fields)?;
 
     let visitor = format_ident!("{}Visitor", name);
     let zv = zvariant_path();
     let mut fields = Vec::new();
     let mut req_fields = Vec::new();
+    let mut default_fields = Vec::new();
+    let mut defaults = Vec::new();
+    let mut skipped_fields = Vec::new();
+    let mut skipped_defaults = Vec::new();
+    let mut flattened_field = None;
     let mut dict_names = Vec::new();
     let mut entries = Vec::new();
 
     for f in &data.fields {
-        let FieldAttributes { rename } = FieldAttributes::parse(&f.attrs)?;
+        let FieldAttributes {
+            rename,
+            flatten,
+            default,
+            skip,
+            ..
+        } = FieldAttributes::parse(&f.attrs)?;
 
         let name = &f.ident;
+        let default = default_for_field(default, f)?;
+
+        if skip {
+            skipped_fields.push(name);
+            skipped_defaults
+                .push(default.unwrap_or_else(|| quote! { ::std::default::Default::default() }));
+
+            continue;
+        }
+        if flatten {
+            flattened_field = Some(name);
+
+            continue;
+        }
+
         let dict_name = dict_name_for_field(f, rename, rename_all.as_deref())?;
 
         let is_option = macros::ty_is_option(&f.ty);
@@ -127,8 +240,18 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
         dict_names.push(dict_name);
         fields.push(name);
 
-        if !is_option {
-            req_fields.push(name);
+        match default {
+            // A missing `Option` field is `None` already, unless the default says otherwise.
+            Some(default) if is_option => {
+                default_fields.push(name);
+                defaults.push(quote! { #name.or_else(|| #default) });
+            }
+            Some(default) => {
+                default_fields.push(name);
+                defaults.push(quote! { #name.unwrap_or_else(|| #default) });
+            }
+            None if is_option => (),
+            None => req_fields.push(name),
         }
     }
 
@@ -143,6 +266,12 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
                 );
             }
         }
+    } else if flattened_field.is_some() {
+        quote! {
+            key => {
+                __zv_flattened.push((key, access.next_value::<#zv::Value<'de>>()?));
+            }
+        }
     } else {
         quote! {
             unknown => {
@@ -152,6 +281,21 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
     };
     entries.push(fallback);
 
+    let (flattened_init, flattened) = match flattened_field {
+        Some(name) => (
+            quote! { let mut __zv_flattened = ::std::vec::Vec::new(); },
+            quote! {
+                let #name = #zv::export::deserialize_flattened(__zv_flattened)
+                    .map_err(<M::Error as #zv::export::serde::de::Error>::custom)?;
+            },
+        ),
+        None => (quote! {}, quote! {}),
+    };
+    let all_fields = fields
+        .iter()
+        .chain(&skipped_fields)
+        .chain(flattened_field.as_ref());
+
     let (_, ty_generics, _) = input.generics.split_for_impl();
     let mut generics = input.generics.clone();
     let def = syn::LifetimeDef {
@@ -193,6 +337,7 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
                         M: #zv::export::serde::de::MapAccess<'de>,
                     {
                         #( let mut #fields = ::std::default::Default::default(); )*
+                        #flattened_init
 
                         // does not check duplicated fields, since those shouldn't exist in stream
                         while let ::std::option::Option::Some(key) = access.next_key::<&str>()? {
@@ -211,7 +356,11 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
                             );
                         };)*
 
-                        ::std::result::Result::Ok(#name { #(#fields),* })
+                        #(let #default_fields = #defaults;)*
+                        #(let #skipped_fields = #skipped_defaults;)*
+                        #flattened
+
+                        ::std::result::Result::Ok(#name { #(#all_fields),* })
                     }
                 }
 
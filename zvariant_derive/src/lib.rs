@@ -15,6 +15,7 @@ use proc_macro::TokenStream;
 use syn::DeriveInput;
 
 mod dict;
+mod tagged;
 mod r#type;
 mod utils;
 mod value;
@@ -107,7 +108,9 @@ mod value;
 /// There are times when you'd find yourself wanting to specify a hardcoded signature yourself for
 /// the type. The `signature` attribute exists for this purpose. A typical use case is when you'd
 /// need to encode your type as a dictionary (signature `a{sv}`) type. For convenience, `dict` is
-/// an alias for `a{sv}`. Here is an example:
+/// an alias for `a{sv}`. The signature is validated at build time and, if it's a structure
+/// signature (or a list of complete types) for a struct with more than one field, the number of
+/// field types must match the number of fields. Here is an example:
 ///
 /// ```
 /// use zvariant::{SerializeDict, DeserializeDict, serialized::Context, to_bytes, Type, LE};
@@ -155,9 +158,15 @@ mod value;
 /// assert_eq!(decoded, StrEnum::Variant2);
 /// ```
 ///
+/// Enums whose variants have differing fields can't be represented as `(u<fields>)`. Such enums
+/// can use a `(sv)`, `(uv)` or `a{sv}` signature, along with the [`SerializeTagged`] and
+/// [`DeserializeTagged`] macros.
+///
 /// [`Type`]: https://docs.rs/zvariant/latest/zvariant/trait.Type.html
 /// [`Serialize`]: https://docs.serde.rs/serde/trait.Serialize.html
 /// [`Deserialize`]: https://docs.serde.rs/serde/de/trait.Deserialize.html
+/// [`SerializeTagged`]: derive.SerializeTagged.html
+/// [`DeserializeTagged`]: derive.DeserializeTagged.html
 /// [serde_repr]: https://crates.io/crates/serde_repr
 #[proc_macro_derive(Type, attributes(zvariant))]
 pub fn type_macro_derive(input: TokenStream) -> TokenStream {
@@ -225,6 +234,34 @@ pub fn type_macro_derive(input: TokenStream) -> TokenStream {
 /// * `"camelCase"`
 /// * `"snake_case"`
 ///
+/// # Flattening, defaults and skipping fields
+///
+/// A field marked with `#[zvariant(flatten)]` has its entries added to the dictionary, rather than
+/// being added as a single entry. The field can either be a map, like
+/// `HashMap<String, OwnedValue>`, or a struct that derives `SerializeDict` itself. Only one field
+/// of a struct can be flattened.
+///
+/// Fields marked with `#[zvariant(skip)]` are never serialized. Fields marked with
+/// `#[zvariant(skip_serializing_if = "path")]` are omitted whenever the function at `path`
+/// returns `true` for them, much like `Option` fields are omitted when `None`:
+///
+/// ```
+/// use std::collections::HashMap;
+/// use zvariant::{OwnedValue, SerializeDict, Type};
+///
+/// #[derive(SerializeDict, Type)]
+/// #[zvariant(signature = "a{sv}")]
+/// struct Options {
+///     modal: bool,
+///     #[zvariant(skip_serializing_if = "Vec::is_empty")]
+///     choices: Vec<String>,
+///     #[zvariant(skip)]
+///     id: u64,
+///     #[zvariant(flatten)]
+///     extra: HashMap<String, OwnedValue>,
+/// }
+/// ```
+///
 /// [`Serialize`]: https://docs.serde.rs/serde/trait.Serialize.html
 #[proc_macro_derive(SerializeDict, attributes(zvariant))]
 pub fn serialize_dict_macro_derive(input: TokenStream) -> TokenStream {
@@ -293,6 +330,42 @@ pub fn serialize_dict_macro_derive(input: TokenStream) -> TokenStream {
 /// * `"camelCase"`
 /// * `"snake_case"`
 ///
+/// # Flattening, defaults and skipping fields
+///
+/// A field marked with `#[zvariant(flatten)]` collects all the entries not matching any other
+/// field. The field can either be a map, like `HashMap<String, OwnedValue>`, or a struct that
+/// derives `DeserializeDict` itself. Only one field of a struct can be flattened and it can't be
+/// combined with the `deny_unknown_fields` attribute.
+///
+/// A missing entry is an error, unless the field is an `Option` or is marked with
+/// `#[zvariant(default)]`, in which case it gets its [`Default`] value, or with
+/// `#[zvariant(default = "path")]`, in which case it gets the value returned by the function at
+/// `path`. Fields marked with `#[zvariant(skip)]` are never deserialized and get their default
+/// value in the same way:
+///
+/// ```
+/// use std::collections::HashMap;
+/// use zvariant::{DeserializeDict, OwnedValue, Type};
+///
+/// fn default_mtu() -> u32 {
+///     1500
+/// }
+///
+/// #[derive(DeserializeDict, Type)]
+/// #[zvariant(signature = "a{sv}")]
+/// ##[allow(unused)]
+/// struct Settings {
+///     #[zvariant(default)]
+///     autoconnect: bool,
+///     #[zvariant(default = "default_mtu")]
+///     mtu: u32,
+///     #[zvariant(skip)]
+///     cache: Vec<u8>,
+///     #[zvariant(flatten)]
+///     extra: HashMap<String, OwnedValue>,
+/// }
+/// ```
+///
 /// [`Deserialize`]: https://docs.serde.rs/serde/de/trait.Deserialize.html
 #[proc_macro_derive(DeserializeDict, attributes(zvariant))]
 pub fn deserialize_dict_macro_derive(input: TokenStream) -> TokenStream {
@@ -302,6 +375,123 @@ pub fn deserialize_dict_macro_derive(input: TokenStream) -> TokenStream {
         .into()
 }
 
+/// Adds [`Serialize`] implementation to enums with differing variants.
+///
+/// Unlike serde's [`Serialize`] macro, which requires all variants of an enum to have the same
+/// fields to be encoded, this macro tags each variant with its name or index and encodes its
+/// fields in a variant. The encoding is selected by the `signature` attribute, which also gives
+/// the [`Type`] signature:
+///
+/// * `"(sv)"`: a structure of the variant name and a variant holding its fields.
+/// * `"(uv)"`: a structure of the variant index (starting from 0) and a variant holding its
+///   fields.
+/// * `"a{sv}"` (or `"dict"`): a dictionary with the variant name under the `"type"` key and the
+///   fields as the other entries. The key can be changed with the `#[zvariant(tag = "key")]`
+///   attribute.
+///
+/// In the structure encodings, a variant with a single field is encoded as a variant of that
+/// field, a variant with multiple fields as a variant of a structure of these fields and a unit
+/// variant as a variant of a zero byte. In the dictionary encoding, named fields use their names
+/// as keys, unnamed fields use their position and `None` fields are omitted, just like with
+/// [`SerializeDict`].
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{serialized::Context, to_bytes, DeserializeTagged, SerializeTagged, Type, LE};
+///
+/// #[derive(SerializeTagged, DeserializeTagged, Type, PartialEq, Debug)]
+/// #[zvariant(signature = "(sv)")]
+/// enum Event {
+///     Added(String),
+///     Moved { from: u32, to: u32 },
+///     Cleared,
+/// }
+///
+/// assert_eq!(Event::signature(), "(sv)");
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let encoded = to_bytes(ctxt, &Event::Moved { from: 1, to: 2 }).unwrap();
+/// let (name, value): (String, zvariant::Value<'_>) = encoded.deserialize().unwrap().0;
+/// assert_eq!(name, "Moved");
+/// assert_eq!(value.value_signature(), "(uu)");
+/// ```
+///
+/// Variants are renamed with the `#[zvariant(rename = "name")]` and
+/// `#[zvariant(rename_all = "case")]` attributes, which take the same cases as for
+/// [`SerializeDict`]. Fields in the dictionary encoding are renamed with
+/// `#[zvariant(rename = "name")]`:
+///
+/// ```
+/// use zvariant::{SerializeTagged, Type};
+///
+/// #[derive(SerializeTagged, Type)]
+/// #[zvariant(signature = "dict", tag = "kind", rename_all = "lowercase")]
+/// enum Event {
+///     Added(String),
+///     Moved {
+///         from: u32,
+///         #[zvariant(rename = "destination")]
+///         to: u32,
+///     },
+///     #[zvariant(rename = "reset")]
+///     Cleared,
+/// }
+/// ```
+///
+/// The serialized D-Bus version of `Event::Moved { from: 1, to: 2 }` will be
+/// `{"kind": Value::Str("moved"), "from": Value::U32(1), "destination": Value::U32(2)}`.
+///
+/// [`Serialize`]: https://docs.serde.rs/serde/trait.Serialize.html
+/// [`Type`]: https://docs.rs/zvariant/latest/zvariant/trait.Type.html
+/// [`SerializeDict`]: derive.SerializeDict.html
+#[proc_macro_derive(SerializeTagged, attributes(zvariant))]
+pub fn serialize_tagged_macro_derive(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = syn::parse(input).unwrap();
+    tagged::expand_serialize_derive(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Adds [`Deserialize`] implementation to enums with differing variants.
+///
+/// This is the counterpart of [`SerializeTagged`] and supports the same signatures and attributes.
+/// In the dictionary encoding, the tag doesn't need to be the first entry, missing `Option` fields
+/// are deserialized as `None` and unknown entries are ignored, unless the
+/// `#[zvariant(deny_unknown_fields)]` attribute is given.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use zvariant::{serialized::Context, to_bytes, DeserializeTagged, Type, Value, LE};
+///
+/// #[derive(DeserializeTagged, Type, PartialEq, Debug)]
+/// #[zvariant(signature = "dict")]
+/// enum Event {
+///     Added(String),
+///     Moved { from: u32, to: Option<u32> },
+///     Cleared,
+/// }
+///
+/// let mut dict = HashMap::new();
+/// dict.insert("from", Value::from(1u32));
+/// dict.insert("type", Value::from("Moved"));
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let encoded = to_bytes(ctxt, &dict).unwrap();
+/// let decoded: Event = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded, Event::Moved { from: 1, to: None });
+/// ```
+///
+/// [`Deserialize`]: https://docs.serde.rs/serde/de/trait.Deserialize.html
+/// [`SerializeTagged`]: derive.SerializeTagged.html
+#[proc_macro_derive(DeserializeTagged, attributes(zvariant))]
+pub fn deserialize_tagged_macro_derive(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = syn::parse(input).unwrap();
+    tagged::expand_deserialize_derive(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
 /// Implements conversions for your type to/from [`Value`].
 ///
 /// Implements `TryFrom<Value>` and `Into<Value>` for your type.
@@ -0,0 +1,510 @@
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    punctuated::Punctuated, spanned::Spanned, Data, DataEnum, DeriveInput, Error, Fields, Ident,
+    Type, Variant,
+};
+use zvariant_utils::macros;
+
+use crate::utils::*;
+
+/// The encodings supported for enums with differing variants.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    /// `(sv)`: the variant name, followed by its fields in a variant.
+    NameValue,
+    /// `(uv)`: the variant index, followed by its fields in a variant.
+    IndexValue,
+    /// `a{sv}`: the variant name under the tag key, along with the fields as entries.
+    Dict,
+}
+
+impl Encoding {
+    fn parse(signature: Option<&str>, span: Span) -> Result<Self, Error> {
+        match signature {
+            Some("(sv)") => Ok(Self::NameValue),
+            Some("(uv)") => Ok(Self::IndexValue),
+            Some("a{sv}") | Some("dict") => Ok(Self::Dict),
+            Some(other) => Err(Error::new(
+                span,
+                format!(
+                    "unsupported signature `{other}` for tagged enums, \
+                     expected `(sv)`, `(uv)` or `a{{sv}}`"
+                ),
+            )),
+            None => Err(Error::new(
+                span,
+                "tagged enums require a `signature` attribute of `(sv)`, `(uv)` or `a{sv}`",
+            )),
+        }
+    }
+}
+
+/// The information needed about each variant to generate the (de)serialization code.
+struct TaggedVariant<'a> {
+    ident: &'a Ident,
+    /// The name of the variant, as used in `(sv)` and `a{sv}` encodings.
+    name: String,
+    /// The index of the variant, as used in the `(uv)` encoding.
+    index: Literal,
+    /// The dictionary keys of the fields, as used in the `a{sv}` encoding.
+    keys: Vec<String>,
+    bindings: Vec<Ident>,
+    types: Vec<&'a Type>,
+    fields: &'a Fields,
+}
+
+impl<'a> TaggedVariant<'a> {
+    fn new(variant: &'a Variant, index: usize, rename_all: Option<&str>) -> Result<Self, Error> {
+        let VariantAttributes { rename } = VariantAttributes::parse(&variant.attrs)?;
+        let name = match rename {
+            Some(name) => name,
+            None => rename_identifier(variant.ident.to_string(), variant.span(), rename_all)?,
+        };
+
+        let mut keys = Vec::new();
+        for (i, f) in variant.fields.iter().enumerate() {
+            let FieldAttributes {
+                rename,
+                flatten,
+                default,
+                skip,
+                skip_serializing_if,
+            } = FieldAttributes::parse(&f.attrs)?;
+            if flatten || default.is_some() || skip || skip_serializing_if.is_some() {
+                return Err(Error::new(
+                    f.span(),
+                    "only the `rename` attribute is supported on fields of tagged enums",
+                ));
+            }
+            let key = match (rename, &f.ident) {
+                (Some(name), _) => name,
+                (None, Some(ident)) => ident.to_string(),
+                (None, None) => i.to_string(),
+            };
+            keys.push(key);
+        }
+
+        let index = u32::try_from(index)
+            .map(Literal::u32_suffixed)
+            .map_err(|_| Error::new(variant.span(), "too many variants"))?;
+
+        Ok(Self {
+            ident: &variant.ident,
+            name,
+            index,
+            keys,
+            bindings: (0..variant.fields.len())
+                .map(|i| format_ident!("__field{}", i))
+                .collect(),
+            types: variant.fields.iter().map(|f| &f.ty).collect(),
+            fields: &variant.fields,
+        })
+    }
+
+    /// The expression building the variant from the bindings, or the pattern destructuring the
+    /// variant into them.
+    fn construct(&self, prefix: TokenStream) -> TokenStream {
+        let ident = self.ident;
+        let bindings = &self.bindings;
+
+        match self.fields {
+            Fields::Named(_) => {
+                let names = self.fields.iter().map(|f| &f.ident);
+                quote! { #prefix::#ident { #(#names: #bindings),* } }
+            }
+            Fields::Unnamed(_) => quote! { #prefix::#ident(#(#bindings),*) },
+            Fields::Unit => quote! { #prefix::#ident },
+        }
+    }
+}
+
+fn parse_enum(input: &DeriveInput) -> Result<(&DataEnum, Encoding, StructAttributes), Error> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => return Err(Error::new(input.span(), "only enums supported")),
+    };
+    if data.variants.is_empty() {
+        return Err(Error::new(
+            input.span(),
+            "enums without variants are not supported",
+        ));
+    }
+
+    let attrs = StructAttributes::parse(&input.attrs)?;
+    let encoding = Encoding::parse(attrs.signature.as_deref(), input.span())?;
+    if encoding != Encoding::Dict && attrs.tag.is_some() {
+        return Err(Error::new(
+            input.span(),
+            "`tag` attribute is only supported with the `a{sv}` signature",
+        ));
+    }
+
+    Ok((data, encoding, attrs))
+}
+
+fn tagged_variants<'a>(
+    data: &'a DataEnum,
+    rename_all: Option<&str>,
+) -> Result<Vec<TaggedVariant<'a>>, Error> {
+    data.variants
+        .iter()
+        .enumerate()
+        .map(|(i, variant)| TaggedVariant::new(variant, i, rename_all))
+        .collect()
+}
+
+pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error> {
+    let (data, encoding, attrs) = parse_enum(&input)?;
+    let variants = tagged_variants(data, attrs.rename_all.as_deref())?;
+
+    let zv = zvariant_path();
+    let name = &input.ident;
+    let tag_key = attrs.tag.as_deref().unwrap_or("type");
+
+    let arms = variants.iter().map(|v| {
+        let pattern = v.construct(quote! { Self });
+        let bindings = &v.bindings;
+
+        match encoding {
+            Encoding::NameValue | Encoding::IndexValue => {
+                let tag = if encoding == Encoding::NameValue {
+                    let name = &v.name;
+                    quote! { #name }
+                } else {
+                    let index = &v.index;
+                    quote! { #index }
+                };
+                // Unit variants carry a dummy byte, since a variant can't be empty.
+                let value = match bindings.as_slice() {
+                    [] => quote! { 0u8 },
+                    [binding] => quote! { #binding },
+                    bindings => quote! { (#(#bindings),*) },
+                };
+
+                quote! {
+                    #pattern => {
+                        tuple.serialize_element(&#tag)?;
+                        tuple.serialize_element(&#zv::SerializeValue(&#value))?;
+                    }
+                }
+            }
+            Encoding::Dict => {
+                let variant_name = &v.name;
+                let entries =
+                    v.fields
+                        .iter()
+                        .zip(bindings)
+                        .zip(&v.keys)
+                        .map(|((f, binding), key)| {
+                            if macros::ty_is_option(&f.ty) {
+                                quote! {
+                                    if let ::std::option::Option::Some(value) = #binding {
+                                        map.serialize_entry(#key, &#zv::SerializeValue(value))?;
+                                    }
+                                }
+                            } else {
+                                quote! {
+                                    map.serialize_entry(#key, &#zv::SerializeValue(#binding))?;
+                                }
+                            }
+                        });
+
+                quote! {
+                    #pattern => {
+                        map.serialize_entry(#tag_key, &#zv::SerializeValue(&#variant_name))?;
+                        #(#entries)*
+                    }
+                }
+            }
+        }
+    });
+
+    let body = match encoding {
+        Encoding::NameValue | Encoding::IndexValue => quote! {
+            use #zv::export::serde::ser::SerializeTuple;
+
+            let mut tuple = serializer.serialize_tuple(2)?;
+            match self {
+                #(#arms)*
+            }
+            tuple.end()
+        },
+        Encoding::Dict => quote! {
+            use #zv::export::serde::ser::SerializeMap;
+
+            let mut map = serializer.serialize_map(::std::option::Option::None)?;
+            match self {
+                #(#arms)*
+            }
+            map.end()
+        },
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[allow(deprecated)]
+        impl #impl_generics #zv::export::serde::ser::Serialize for #name #ty_generics
+        #where_clause
+        {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where
+                S: #zv::export::serde::ser::Serializer,
+            {
+                #body
+            }
+        }
+    })
+}
+
+pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Error> {
+    let (data, encoding, attrs) = parse_enum(&input)?;
+    let variants = tagged_variants(data, attrs.rename_all.as_deref())?;
+
+    let zv = zvariant_path();
+    let name = &input.ident;
+    let visitor = format_ident!("{}Visitor", name);
+    let (visitor_generics, ty_generics, visitor_where_clause) = input.generics.split_for_impl();
+    let variant_names: Vec<_> = variants.iter().map(|v| &v.name).collect();
+
+    let (expecting, visit) = match encoding {
+        Encoding::NameValue | Encoding::IndexValue => {
+            let arms = variants.iter().map(|v| {
+                let tag = if encoding == Encoding::NameValue {
+                    let name = &v.name;
+                    quote! { #name }
+                } else {
+                    let index = &v.index;
+                    quote! { #index }
+                };
+                let construct = v.construct(quote! { #name });
+                let bindings = &v.bindings;
+                let types = &v.types;
+                let value = match bindings.as_slice() {
+                    [] => quote! {
+                        let _ = seq.next_element::<#zv::Value<'de>>()?.ok_or_else(missing_value)?;
+                    },
+                    [binding] => quote! {
+                        let #binding = seq
+                            .next_element::<#zv::DeserializeValue<'de, #(#types)*>>()?
+                            .ok_or_else(missing_value)?
+                            .0;
+                    },
+                    bindings => quote! {
+                        let (#(#bindings),*) = seq
+                            .next_element::<#zv::DeserializeValue<'de, (#(#types),*)>>()?
+                            .ok_or_else(missing_value)?
+                            .0;
+                    },
+                };
+
+                quote! {
+                    #tag => {
+                        #value
+                        ::std::result::Result::Ok(#construct)
+                    }
+                }
+            });
+
+            let (tag_ty, unknown) = if encoding == Encoding::NameValue {
+                (
+                    quote! { &str },
+                    quote! {
+                        <A::Error as #zv::export::serde::de::Error>::unknown_variant(
+                            tag,
+                            &[#(#variant_names),*],
+                        )
+                    },
+                )
+            } else {
+                let n_variants = variants.len();
+                (
+                    quote! { u32 },
+                    quote! {
+                        <A::Error as #zv::export::serde::de::Error>::invalid_value(
+                            #zv::export::serde::de::Unexpected::Unsigned(tag.into()),
+                            &::std::format!("a variant index less than {}", #n_variants).as_str(),
+                        )
+                    },
+                )
+            };
+
+            let visit = quote! {
+                fn visit_seq<A>(self, mut seq: A) -> ::std::result::Result<Self::Value, A::Error>
+                where
+                    A: #zv::export::serde::de::SeqAccess<'de>,
+                {
+                    let missing_value = || {
+                        <A::Error as #zv::export::serde::de::Error>::invalid_length(1, &"a tag and a value")
+                    };
+                    let tag = seq.next_element::<#tag_ty>()?.ok_or_else(|| {
+                        <A::Error as #zv::export::serde::de::Error>::invalid_length(0, &"a tag and a value")
+                    })?;
+
+                    match tag {
+                        #(#arms)*
+                        tag => ::std::result::Result::Err(#unknown),
+                    }
+                }
+            };
+
+            ("a tag and a value", visit)
+        }
+        Encoding::Dict => {
+            let tag_key = attrs.tag.as_deref().unwrap_or("type");
+            let arms = variants.iter().map(|v| {
+                let variant_name = &v.name;
+                let construct = v.construct(quote! { #name });
+                let fields = v.fields.iter().zip(&v.bindings).zip(&v.keys).map(|((f, binding), key)| {
+                    let value = quote! {
+                        #zv::from_value(value)
+                            .map_err(<M::Error as #zv::export::serde::de::Error>::custom)?
+                    };
+                    // `Option` fields are omitted when `None`, rather than encoded as such.
+                    let (present, missing) = if macros::ty_is_option(&f.ty) {
+                        (
+                            quote! { ::std::option::Option::Some(#value) },
+                            quote! { ::std::option::Option::None },
+                        )
+                    } else {
+                        (
+                            value,
+                            quote! {
+                                return ::std::result::Result::Err(
+                                    <M::Error as #zv::export::serde::de::Error>::missing_field(#key),
+                                )
+                            },
+                        )
+                    };
+
+                    quote! {
+                        let #binding = match entries.remove(#key) {
+                            ::std::option::Option::Some(value) => #present,
+                            ::std::option::Option::None => #missing,
+                        };
+                    }
+                });
+                let deny_unknown_fields = attrs.deny_unknown_fields.then(|| {
+                    let keys = &v.keys;
+
+                    quote! {
+                        if let ::std::option::Option::Some(key) = entries.keys().next() {
+                            return ::std::result::Result::Err(
+                                <M::Error as #zv::export::serde::de::Error>::unknown_field(
+                                    key,
+                                    &[#(#keys),*],
+                                ),
+                            );
+                        }
+                    }
+                });
+
+                quote! {
+                    #variant_name => {
+                        #(#fields)*
+                        #deny_unknown_fields
+                        ::std::result::Result::Ok(#construct)
+                    }
+                }
+            });
+
+            let visit = quote! {
+                fn visit_map<M>(self, mut access: M) -> ::std::result::Result<Self::Value, M::Error>
+                where
+                    M: #zv::export::serde::de::MapAccess<'de>,
+                {
+                    let mut tag = ::std::option::Option::<::std::string::String>::None;
+                    // The tag isn't necessarily the first entry, so keep the others around until
+                    // we know which variant they belong to.
+                    let mut entries = ::std::collections::HashMap::<&str, #zv::Value<'de>>::new();
+
+                    while let ::std::option::Option::Some(key) = access.next_key::<&str>()? {
+                        if key == #tag_key {
+                            tag = ::std::option::Option::Some(
+                                access.next_value::<#zv::DeserializeValue<'de, _>>()?.0,
+                            );
+                        } else {
+                            entries.insert(key, access.next_value()?);
+                        }
+                    }
+
+                    let tag = tag.ok_or_else(|| {
+                        <M::Error as #zv::export::serde::de::Error>::missing_field(#tag_key)
+                    })?;
+                    match tag.as_str() {
+                        #(#arms)*
+                        tag => ::std::result::Result::Err(
+                            <M::Error as #zv::export::serde::de::Error>::unknown_variant(
+                                tag,
+                                &[#(#variant_names),*],
+                            ),
+                        ),
+                    }
+                }
+            };
+
+            ("a dictionary", visit)
+        }
+    };
+
+    let deserialize = match encoding {
+        Encoding::NameValue | Encoding::IndexValue => quote! {
+            deserializer.deserialize_tuple(2, #visitor(::std::marker::PhantomData))
+        },
+        Encoding::Dict => quote! {
+            deserializer.deserialize_map(#visitor(::std::marker::PhantomData))
+        },
+    };
+
+    let mut generics = input.generics.clone();
+    let def = syn::LifetimeDef {
+        attrs: Vec::new(),
+        lifetime: syn::Lifetime::new("'de", Span::call_site()),
+        colon_token: None,
+        bounds: Punctuated::new(),
+    };
+    generics.params = Some(syn::GenericParam::Lifetime(def))
+        .into_iter()
+        .chain(generics.params)
+        .collect();
+    // Fields are deserialized through `DeserializeValue<'de, T>`, which requires `T: 'de`.
+    let type_params: Vec<_> = generics.type_params().map(|p| p.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for param in type_params {
+        where_clause
+            .predicates
+            .push(syn::parse_quote! { #param: 'de });
+    }
+
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        #[allow(deprecated)]
+        impl #impl_generics #zv::export::serde::de::Deserialize<'de> for #name #ty_generics
+        #where_clause
+        {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: #zv::export::serde::de::Deserializer<'de>,
+            {
+                struct #visitor #visitor_generics(::std::marker::PhantomData<#name #ty_generics>)
+                #visitor_where_clause;
+
+                impl #impl_generics #zv::export::serde::de::Visitor<'de> for #visitor #ty_generics
+                #where_clause
+                {
+                    type Value = #name #ty_generics;
+
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        formatter.write_str(#expecting)
+                    }
+
+                    #visit
+                }
+
+                #deserialize
+            }
+        }
+    })
+}
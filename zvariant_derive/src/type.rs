@@ -4,6 +4,8 @@ use syn::{
     spanned::Spanned, Attribute, Data, DataEnum, DeriveInput, Error, Fields, Generics, Ident,
 };
 
+use zvariant_utils::signature;
+
 use crate::utils::*;
 
 pub fn expand_derive(ast: DeriveInput) -> Result<TokenStream, Error> {
@@ -16,20 +18,15 @@ pub fn expand_derive(ast: DeriveInput) -> Result<TokenStream, Error> {
             _ => signature,
         };
 
+        check_signature(&signature, &ast)?;
+
         // Signature already provided, easy then!
         let name = ast.ident;
         let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
         return Ok(quote! {
             impl #impl_generics #zv::Type for #name #ty_generics #where_clause {
-                #[inline]
-                fn signature() -> #zv::Signature<'static> {
-                    // FIXME: Would be nice if we had a parsed `Signature` in the macro code already so
-                    // it's checked at the build time but currently that's not easily possible w/o
-                    // zvariant_derive requiring zvaraint and we don't want it as it creates a cyclic
-                    // dep. Maybe we can find a way to share the `Signature` type between the two
-                    // crates?
-                    #zv::Signature::from_static_str(#signature).unwrap()
-                }
+                const SIGNATURE: &'static #zv::StaticSignature =
+                    &#zv::StaticSignature::from_str_unchecked(#signature);
             }
         });
     }
@@ -58,69 +55,154 @@ pub fn expand_derive(ast: DeriveInput) -> Result<TokenStream, Error> {
     })
 }
 
+/// Check that a custom signature is valid and, for structs with multiple fields, that it has a type
+/// for each field.
+fn check_signature(signature: &str, ast: &DeriveInput) -> Result<(), Error> {
+    let types = signature::complete_types(signature)
+        .map_err(|e| Error::new(ast.span(), format!("invalid signature `{signature}`: {e}")))?;
+
+    let n_fields = match &ast.data {
+        Data::Struct(ds) => ds.fields.len(),
+        _ => return Ok(()),
+    };
+    // Single-field structs are often newtypes, with the signature of the wrapped type.
+    if n_fields < 2 {
+        return Ok(());
+    }
+    let n_types = match types.as_slice() {
+        [t] if t.starts_with('(') => signature::complete_types(&t[1..t.len() - 1])
+            .expect("already validated")
+            .len(),
+        // A single non-structure type, like `a{sv}`, can represent any number of fields.
+        [_] => return Ok(()),
+        types => types.len(),
+    };
+    if n_types != n_fields {
+        return Err(Error::new(
+            ast.span(),
+            format!(
+                "signature `{signature}` has {n_types} field type(s) but the struct has {n_fields} fields"
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
 fn impl_struct(
     name: Ident,
     generics: Generics,
     fields: Fields,
     zv: &TokenStream,
 ) -> Result<TokenStream, Error> {
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let signature = signature_for_struct(&fields, zv, false);
 
-    Ok(quote! {
+    Ok(impl_type(&name, &generics, signature, zv))
+}
+
+/// The signature of a type, both as a `&'static StaticSignature` constant expression and as code
+/// building the `Signature` at runtime, for when the former is unknown.
+struct TypeSignature {
+    constant: TokenStream,
+    dynamic: TokenStream,
+}
+
+fn impl_type(
+    name: &Ident,
+    generics: &Generics,
+    signature: TypeSignature,
+    zv: &TokenStream,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let TypeSignature { constant, dynamic } = signature;
+
+    quote! {
         impl #impl_generics #zv::Type for #name #ty_generics #where_clause {
+            const SIGNATURE: &'static #zv::StaticSignature = #constant;
+
             #[inline]
             fn signature() -> #zv::Signature<'static> {
-                #signature
+                match <Self as #zv::Type>::SIGNATURE.as_signature() {
+                    ::std::option::Option::Some(signature) => signature,
+                    ::std::option::Option::None => {
+                        #dynamic
+                    }
+                }
             }
         }
-    })
+    }
 }
 
 fn signature_for_struct(
     fields: &Fields,
     zv: &TokenStream,
     insert_enum_variant: bool,
-) -> TokenStream {
-    let field_types = fields.iter().map(|field| field.ty.to_token_stream());
+) -> TypeSignature {
+    let field_types: Vec<_> = fields
+        .iter()
+        .map(|field| field.ty.to_token_stream())
+        .collect();
     let new_type = match fields {
         Fields::Named(_) => false,
         Fields::Unnamed(_) if field_types.len() == 1 => true,
         Fields::Unnamed(_) => false,
         Fields::Unit => panic!("signature_for_struct must not be called for unit fields"),
     };
-    let inner_impl = if new_type {
-        quote! {
-            #(
-                <#field_types as #zv::Type>::signature()
-             )*
+    let inner = if new_type {
+        TypeSignature {
+            constant: quote! {
+                #(
+                    <#field_types as #zv::Type>::SIGNATURE
+                 )*
+            },
+            dynamic: quote! {
+                #(
+                    <#field_types as #zv::Type>::signature()
+                 )*
+            },
         }
     } else {
-        quote! {
-            let mut s = <::std::string::String as ::std::convert::From<_>>::from("(");
-            #(
-                s.push_str(<#field_types as #zv::Type>::signature().as_str());
-            )*
-            s.push_str(")");
+        TypeSignature {
+            constant: quote! {
+                &#zv::StaticSignature::structure(&[
+                    #(
+                        <#field_types as #zv::Type>::SIGNATURE,
+                    )*
+                ])
+            },
+            dynamic: quote! {
+                let mut s = <::std::string::String as ::std::convert::From<_>>::from("(");
+                #(
+                    s.push_str(<#field_types as #zv::Type>::signature().as_str());
+                )*
+                s.push_str(")");
 
-            #zv::Signature::from_string_unchecked(s)
+                #zv::Signature::from_string_unchecked(s)
+            },
         }
     };
 
     if insert_enum_variant {
-        quote! {
-            let inner_signature = {
-                #inner_impl
-            };
-            let mut s = <::std::string::String as ::std::convert::From<_>>::from("(");
-            s.push_str(<u32 as #zv::Type>::signature().as_str());
-            s.push_str(inner_signature.as_str());
-            s.push_str(")");
+        let TypeSignature { constant, dynamic } = inner;
+
+        TypeSignature {
+            constant: quote! {
+                &#zv::StaticSignature::structure(&[<u32 as #zv::Type>::SIGNATURE, #constant])
+            },
+            dynamic: quote! {
+                let inner_signature = {
+                    #dynamic
+                };
+                let mut s = <::std::string::String as ::std::convert::From<_>>::from("(");
+                s.push_str(<u32 as #zv::Type>::signature().as_str());
+                s.push_str(inner_signature.as_str());
+                s.push_str(")");
 
-            #zv::Signature::from_string_unchecked(s)
+                #zv::Signature::from_string_unchecked(s)
+            },
         }
     } else {
-        inner_impl
+        inner
     }
 }
 
@@ -133,10 +215,8 @@ fn impl_unit_struct(
 
     Ok(quote! {
         impl #impl_generics #zv::Type for #name #ty_generics #where_clause {
-            #[inline]
-            fn signature() -> #zv::Signature<'static> {
-                #zv::Signature::from_static_str_unchecked("")
-            }
+            const SIGNATURE: &'static #zv::StaticSignature =
+                &#zv::StaticSignature::from_str_unchecked("");
         }
     })
 }
@@ -150,10 +230,8 @@ fn impl_empty_struct(
 
     Ok(quote! {
         impl #impl_generics #zv::Type for #name #ty_generics #where_clause {
-            #[inline]
-            fn signature() -> #zv::Signature<'static> {
-                #zv::Signature::from_static_str_unchecked("y")
-            }
+            const SIGNATURE: &'static #zv::StaticSignature =
+                &#zv::StaticSignature::from_str_unchecked("y");
         }
     })
 }
@@ -165,7 +243,7 @@ fn impl_enum(
     data: DataEnum,
     zv: &TokenStream,
 ) -> Result<TokenStream, Error> {
-    let mut all_signatures: Vec<Result<TokenStream, Error>> = data
+    let mut all_signatures: Vec<Result<TypeSignature, Error>> = data
         .variants
         .iter()
         .map(|variant| signature_for_variant(variant, &attrs, zv))
@@ -173,31 +251,24 @@ fn impl_enum(
     let signature = all_signatures.pop().unwrap()?;
     // Ensure all variants of the enum have the same number and type of fields.
     for sig in all_signatures {
-        if sig?.to_string() != signature.to_string() {
+        if sig?.dynamic.to_string() != signature.dynamic.to_string() {
             return Err(Error::new(
                 name.span(),
-                "all variants must have the same number and type of fields",
+                "all variants must have the same number and type of fields, \
+                 otherwise use `(sv)`, `(uv)` or `a{sv}` signature with `SerializeTagged` and \
+                 `DeserializeTagged`",
             ));
         }
     }
 
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-
-    Ok(quote! {
-        impl #impl_generics #zv::Type for #name #ty_generics #where_clause {
-            #[inline]
-            fn signature() -> #zv::Signature<'static> {
-                #signature
-            }
-        }
-    })
+    Ok(impl_type(&name, &generics, signature, zv))
 }
 
 fn signature_for_variant(
     variant: &syn::Variant,
     attrs: &[Attribute],
     zv: &TokenStream,
-) -> Result<TokenStream, Error> {
+) -> Result<TypeSignature, Error> {
     let repr = attrs.iter().find(|attr| attr.path.is_ident("repr"));
     match &variant.fields {
         Fields::Unit => {
@@ -206,7 +277,10 @@ fn signature_for_variant(
                 None => quote! { u32 },
             };
 
-            Ok(quote! { <#repr as #zv::Type>::signature() })
+            Ok(TypeSignature {
+                constant: quote! { <#repr as #zv::Type>::SIGNATURE },
+                dynamic: quote! { <#repr as #zv::Type>::signature() },
+            })
         }
         Fields::Named(_) => Ok(signature_for_struct(&variant.fields, zv, true)),
         Fields::Unnamed(_) => Ok(signature_for_struct(&variant.fields, zv, true)),
@@ -1,7 +1,8 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use proc_macro_crate::{crate_name, FoundCrate};
 use quote::{format_ident, quote};
-use zvariant_utils::def_attrs;
+use syn::Error;
+use zvariant_utils::{case, def_attrs};
 
 pub fn zvariant_path() -> TokenStream {
     if let Ok(FoundCrate::Name(name)) = crate_name("zvariant") {
@@ -19,7 +20,41 @@ def_attrs! {
     crate zvariant;
 
     /// Attributes defined on structures.
-    pub StructAttributes("struct") { signature str, rename_all str, deny_unknown_fields none };
+    pub StructAttributes("struct") {
+        signature str,
+        rename_all str,
+        deny_unknown_fields none,
+        tag str
+    };
+    /// Attributes defined on enum variants.
+    pub VariantAttributes("variant") { rename str };
     /// Attributes defined on fields.
-    pub FieldAttributes("field") { rename str };
+    pub FieldAttributes("field") {
+        rename str,
+        flatten none,
+        default none_or_str,
+        skip none,
+        skip_serializing_if str
+    };
+}
+
+/// Rename a snake case field or Pascal case variant identifier according to a `rename_all`
+/// attribute value.
+pub fn rename_identifier(
+    ident: String,
+    span: Span,
+    rename_all_attr: Option<&str>,
+) -> Result<String, Error> {
+    match rename_all_attr {
+        Some("lowercase") => Ok(ident.to_ascii_lowercase()),
+        Some("UPPERCASE") => Ok(ident.to_ascii_uppercase()),
+        Some("PascalCase") => Ok(case::pascal_or_camel_case(&ident, true)),
+        Some("camelCase") => Ok(case::pascal_or_camel_case(&ident, false)),
+        Some("snake_case") => Ok(case::snake_case(&ident)),
+        None => Ok(ident),
+        Some(other) => Err(Error::new(
+            span,
+            format!("invalid `rename_all` attribute value {other}"),
+        )),
+    }
 }
@@ -3,7 +3,8 @@
 use std::collections::HashMap;
 use zvariant::{
     serialized::{Context, Format},
-    DeserializeDict, OwnedValue, SerializeDict, Type, Value, LE,
+    DeserializeDict, DeserializeTagged, OwnedValue, SerializeDict, SerializeTagged, Type, Value,
+    LE,
 };
 
 #[test]
@@ -23,7 +24,36 @@ fn derive_struct() {
         blob: Vec<u8>,
     }
 
-    assert_eq!(TestStruct::signature(), "(syay)")
+    assert_eq!(TestStruct::signature(), "(syay)");
+    assert_eq!(TestStruct::SIGNATURE.as_str(), Some("(syay)"));
+}
+
+#[test]
+fn derive_static_signature() {
+    #[derive(Type)]
+    enum Shape {
+        Point(f64, f64),
+        Size { width: f64, height: f64 },
+    }
+
+    #[derive(Type)]
+    struct Wrapper<T: Type>(Vec<T>);
+
+    struct Dynamic;
+
+    impl Type for Dynamic {
+        fn signature() -> zvariant::Signature<'static> {
+            zvariant::Signature::from_static_str_unchecked("x")
+        }
+    }
+
+    const SHAPE: &zvariant::StaticSignature = Shape::SIGNATURE;
+    assert_eq!(SHAPE.as_str(), Some("(u(dd))"));
+    assert_eq!(Wrapper::<u16>::SIGNATURE.as_str(), Some("aq"));
+
+    // Types without a compile-time signature still work, the signature is built at runtime.
+    assert!(!Wrapper::<Dynamic>::SIGNATURE.is_known());
+    assert_eq!(Wrapper::<Dynamic>::signature(), "ax");
 }
 
 #[test]
@@ -82,3 +112,401 @@ fn derive_dict() {
 
     assert_eq!(Test::signature(), "a{sv}")
 }
+
+#[test]
+fn derive_tagged_enum() {
+    #[derive(SerializeTagged, DeserializeTagged, Type, PartialEq, Debug)]
+    #[zvariant(signature = "(sv)")]
+    enum Event {
+        Added(String),
+        Moved { from: u32, to: u32 },
+        Swapped(u8, u8),
+        Cleared,
+    }
+
+    #[derive(SerializeTagged, DeserializeTagged, Type, PartialEq, Debug)]
+    #[zvariant(signature = "(uv)")]
+    enum IndexedEvent {
+        Added(String),
+        Moved { from: u32, to: u32 },
+        Cleared,
+    }
+
+    assert_eq!(Event::signature(), "(sv)");
+    assert_eq!(IndexedEvent::signature(), "(uv)");
+
+    let ctxt = Context::new(Format::DBus, LE, 0);
+    for event in [
+        Event::Added("foo".to_string()),
+        Event::Moved { from: 1, to: 2 },
+        Event::Swapped(3, 4),
+        Event::Cleared,
+    ] {
+        let serialized = zvariant::to_bytes(ctxt, &event).unwrap();
+        let deserialized: Event = serialized.deserialize().unwrap().0;
+        assert_eq!(deserialized, event);
+    }
+
+    let serialized = zvariant::to_bytes(ctxt, &Event::Moved { from: 1, to: 2 }).unwrap();
+    let (name, value): (String, OwnedValue) = serialized.deserialize().unwrap().0;
+    assert_eq!(name, "Moved");
+    assert_eq!(value, Value::from((1u32, 2u32)).try_into().unwrap());
+
+    let serialized = zvariant::to_bytes(ctxt, &("Added", Value::from("foo"))).unwrap();
+    let deserialized: Event = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized, Event::Added("foo".to_string()));
+
+    let serialized = zvariant::to_bytes(ctxt, &("Removed", Value::from("foo"))).unwrap();
+    assert!(serialized.deserialize::<Event>().is_err());
+    // Wrong payload type for the variant.
+    let serialized = zvariant::to_bytes(ctxt, &("Added", Value::from(1u32))).unwrap();
+    assert!(serialized.deserialize::<Event>().is_err());
+
+    for event in [
+        IndexedEvent::Added("foo".to_string()),
+        IndexedEvent::Moved { from: 1, to: 2 },
+        IndexedEvent::Cleared,
+    ] {
+        let serialized = zvariant::to_bytes(ctxt, &event).unwrap();
+        let deserialized: IndexedEvent = serialized.deserialize().unwrap().0;
+        assert_eq!(deserialized, event);
+    }
+
+    let serialized = zvariant::to_bytes(ctxt, &IndexedEvent::Cleared).unwrap();
+    let (index, _): (u32, OwnedValue) = serialized.deserialize().unwrap().0;
+    assert_eq!(index, 2);
+
+    let serialized = zvariant::to_bytes(ctxt, &(3u32, Value::from(0u8))).unwrap();
+    assert!(serialized.deserialize::<IndexedEvent>().is_err());
+}
+
+#[test]
+fn derive_tagged_dict_enum() {
+    #[derive(SerializeTagged, DeserializeTagged, Type, PartialEq, Debug)]
+    #[zvariant(signature = "dict", tag = "kind", rename_all = "lowercase")]
+    enum Event {
+        Added(String),
+        Moved {
+            from: u32,
+            #[zvariant(rename = "destination")]
+            to: Option<u32>,
+        },
+        #[zvariant(rename = "reset")]
+        Cleared,
+    }
+
+    #[derive(DeserializeTagged, Type, PartialEq, Debug)]
+    #[zvariant(signature = "a{sv}", deny_unknown_fields)]
+    enum StrictEvent {
+        Added(String),
+    }
+
+    assert_eq!(Event::signature(), "a{sv}");
+
+    let ctxt = Context::new(Format::DBus, LE, 0);
+    for event in [
+        Event::Added("foo".to_string()),
+        Event::Moved {
+            from: 1,
+            to: Some(2),
+        },
+        Event::Moved { from: 1, to: None },
+        Event::Cleared,
+    ] {
+        let serialized = zvariant::to_bytes(ctxt, &event).unwrap();
+        let deserialized: Event = serialized.deserialize().unwrap().0;
+        assert_eq!(deserialized, event);
+    }
+
+    let event = Event::Moved {
+        from: 1,
+        to: Some(2),
+    };
+    let serialized = zvariant::to_bytes(ctxt, &event).unwrap();
+    let deserialized: HashMap<String, OwnedValue> = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized.len(), 3);
+    assert_eq!(
+        deserialized["kind"],
+        Value::from("moved").try_into().unwrap()
+    );
+    assert_eq!(deserialized["from"], Value::from(1u32).try_into().unwrap());
+    assert_eq!(
+        deserialized["destination"],
+        Value::from(2u32).try_into().unwrap()
+    );
+
+    let serialized = zvariant::to_bytes(ctxt, &Event::Cleared).unwrap();
+    let deserialized: HashMap<String, OwnedValue> = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized.len(), 1);
+    assert_eq!(
+        deserialized["kind"],
+        Value::from("reset").try_into().unwrap()
+    );
+
+    let serialized = zvariant::to_bytes(ctxt, &Event::Added("foo".to_string())).unwrap();
+    let deserialized: HashMap<String, OwnedValue> = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized["0"], Value::from("foo").try_into().unwrap());
+
+    // The tag may come after the fields and unknown entries are ignored by default.
+    let mut dict = HashMap::new();
+    dict.insert("from", Value::from(1u32));
+    dict.insert("extra", Value::from(1u32));
+    dict.insert("kind", Value::from("moved"));
+    let serialized = zvariant::to_bytes(ctxt, &dict).unwrap();
+    let deserialized: Event = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized, Event::Moved { from: 1, to: None });
+
+    let mut dict = HashMap::new();
+    dict.insert("0", Value::from("foo"));
+    dict.insert("extra", Value::from(1u32));
+    dict.insert("type", Value::from("Added"));
+    let serialized = zvariant::to_bytes(ctxt, &dict).unwrap();
+    assert!(serialized.deserialize::<StrictEvent>().is_err());
+
+    dict.remove("extra");
+    let serialized = zvariant::to_bytes(ctxt, &dict).unwrap();
+    let deserialized: StrictEvent = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized, StrictEvent::Added("foo".to_string()));
+
+    // Missing required field.
+    let mut dict = HashMap::new();
+    dict.insert("kind", Value::from("moved"));
+    let serialized = zvariant::to_bytes(ctxt, &dict).unwrap();
+    assert!(serialized.deserialize::<Event>().is_err());
+}
+
+#[test]
+fn derive_tagged_generic_enum() {
+    #[derive(SerializeTagged, DeserializeTagged, Type, PartialEq, Debug)]
+    #[zvariant(signature = "(sv)")]
+    enum Event<T>
+    where
+        T: Type + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        Added(T),
+        Cleared,
+    }
+
+    #[derive(SerializeTagged, DeserializeTagged, Type, PartialEq, Debug)]
+    #[zvariant(signature = "a{sv}")]
+    enum DictEvent<T: Type + serde::Serialize + serde::de::DeserializeOwned> {
+        Added { item: T },
+        Cleared,
+    }
+
+    let ctxt = Context::new(Format::DBus, LE, 0);
+    for event in [Event::Added(42u32), Event::Cleared] {
+        let serialized = zvariant::to_bytes(ctxt, &event).unwrap();
+        let deserialized: Event<u32> = serialized.deserialize().unwrap().0;
+        assert_eq!(deserialized, event);
+    }
+
+    for event in [
+        DictEvent::Added {
+            item: "foo".to_string(),
+        },
+        DictEvent::Cleared,
+    ] {
+        let serialized = zvariant::to_bytes(ctxt, &event).unwrap();
+        let deserialized: DictEvent<String> = serialized.deserialize().unwrap().0;
+        assert_eq!(deserialized, event);
+    }
+}
+
+#[test]
+fn derive_dict_flatten() {
+    #[derive(SerializeDict, DeserializeDict, Type, PartialEq, Debug)]
+    #[zvariant(signature = "a{sv}")]
+    struct Ipv4 {
+        method: String,
+        gateway: Option<String>,
+    }
+
+    #[derive(SerializeDict, DeserializeDict, Type, PartialEq, Debug)]
+    #[zvariant(signature = "a{sv}")]
+    struct Settings {
+        id: String,
+        #[zvariant(flatten)]
+        ipv4: Ipv4,
+    }
+
+    #[derive(SerializeDict, DeserializeDict, Type, PartialEq, Debug)]
+    #[zvariant(signature = "a{sv}")]
+    struct Options {
+        modal: bool,
+        #[zvariant(flatten)]
+        rest: HashMap<String, OwnedValue>,
+    }
+
+    let ctxt = Context::new(Format::DBus, LE, 0);
+
+    let settings = Settings {
+        id: "eth0".to_string(),
+        ipv4: Ipv4 {
+            method: "auto".to_string(),
+            gateway: None,
+        },
+    };
+    let serialized = zvariant::to_bytes(ctxt, &settings).unwrap();
+    let deserialized: HashMap<String, OwnedValue> = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized.len(), 2);
+    assert_eq!(deserialized["id"], Value::from("eth0").try_into().unwrap());
+    assert_eq!(
+        deserialized["method"],
+        Value::from("auto").try_into().unwrap()
+    );
+
+    let deserialized: Settings = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized, settings);
+
+    let mut rest = HashMap::new();
+    rest.insert(
+        "handle_token".to_string(),
+        Value::from("t1").try_into().unwrap(),
+    );
+    rest.insert(
+        "choices".to_string(),
+        Value::from(vec![1u32, 2]).try_into().unwrap(),
+    );
+    let options = Options { modal: true, rest };
+    let serialized = zvariant::to_bytes(ctxt, &options).unwrap();
+    let deserialized: HashMap<String, OwnedValue> = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized.len(), 3);
+    assert_eq!(deserialized["modal"], Value::from(true).try_into().unwrap());
+    assert_eq!(
+        deserialized["handle_token"],
+        Value::from("t1").try_into().unwrap()
+    );
+
+    let deserialized: Options = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized, options);
+
+    // A field named like the generated locals doesn't clash with them.
+    #[derive(SerializeDict, DeserializeDict, Type, PartialEq, Debug)]
+    #[zvariant(signature = "a{sv}")]
+    struct Layer {
+        flattened: bool,
+        #[zvariant(flatten)]
+        rest: HashMap<String, OwnedValue>,
+    }
+
+    let mut rest = HashMap::new();
+    rest.insert(
+        "opacity".to_string(),
+        Value::from(0.5f64).try_into().unwrap(),
+    );
+    let layer = Layer {
+        flattened: true,
+        rest,
+    };
+    let serialized = zvariant::to_bytes(ctxt, &layer).unwrap();
+    let deserialized: Layer = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized, layer);
+
+    // Structs in the flattened entries are deserialized from the contents of their variants.
+    #[derive(serde::Deserialize, Type, PartialEq, Debug)]
+    struct Position {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(serde::Deserialize, PartialEq, Debug)]
+    struct Geometry {
+        position: Position,
+        scale: f64,
+    }
+
+    #[derive(DeserializeDict, Type, PartialEq, Debug)]
+    #[zvariant(signature = "a{sv}")]
+    struct Window {
+        title: String,
+        #[zvariant(flatten)]
+        geometry: Geometry,
+    }
+
+    let mut dict = HashMap::new();
+    dict.insert("title", Value::from("zbus"));
+    dict.insert("position", Value::from((1i32, 2i32)));
+    dict.insert("scale", Value::from(1.5f64));
+    let serialized = zvariant::to_bytes(ctxt, &dict).unwrap();
+    let deserialized: Window = serialized.deserialize().unwrap().0;
+    assert_eq!(
+        deserialized,
+        Window {
+            title: "zbus".to_string(),
+            geometry: Geometry {
+                position: Position { x: 1, y: 2 },
+                scale: 1.5,
+            },
+        }
+    );
+}
+
+#[test]
+fn derive_dict_default_and_skip() {
+    fn default_mtu() -> u32 {
+        1500
+    }
+
+    #[derive(SerializeDict, DeserializeDict, Type, PartialEq, Debug)]
+    #[zvariant(signature = "a{sv}")]
+    struct Test {
+        name: String,
+        #[zvariant(default)]
+        autoconnect: bool,
+        #[zvariant(default = "default_mtu")]
+        mtu: u32,
+        #[zvariant(skip_serializing_if = "Vec::is_empty", default)]
+        dns: Vec<String>,
+        #[zvariant(skip)]
+        cache: Option<u64>,
+    }
+
+    let ctxt = Context::new(Format::DBus, LE, 0);
+
+    let mut dict = HashMap::new();
+    dict.insert("name", Value::from("eth0"));
+    dict.insert("cache", Value::from(1u64));
+    let serialized = zvariant::to_bytes(ctxt, &dict).unwrap();
+    let deserialized: Test = serialized.deserialize().unwrap().0;
+    assert_eq!(
+        deserialized,
+        Test {
+            name: "eth0".to_string(),
+            autoconnect: false,
+            mtu: 1500,
+            dns: vec![],
+            cache: None,
+        }
+    );
+
+    let test = Test {
+        name: "eth0".to_string(),
+        autoconnect: true,
+        mtu: 9000,
+        dns: vec![],
+        cache: Some(1),
+    };
+    let serialized = zvariant::to_bytes(ctxt, &test).unwrap();
+    let deserialized: HashMap<String, OwnedValue> = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized.len(), 3);
+    assert!(!deserialized.contains_key("dns"));
+    assert!(!deserialized.contains_key("cache"));
+
+    let deserialized: Test = serialized.deserialize().unwrap().0;
+    assert_eq!(
+        deserialized,
+        Test {
+            cache: None,
+            ..test
+        }
+    );
+
+    let test = Test {
+        dns: vec!["1.1.1.1".to_string()],
+        ..deserialized
+    };
+    let serialized = zvariant::to_bytes(ctxt, &test).unwrap();
+    let deserialized: Test = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized, test);
+}
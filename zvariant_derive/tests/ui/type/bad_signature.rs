@@ -0,0 +1,10 @@
+use zvariant::Type;
+
+#[derive(Type)]
+#[zvariant(signature = "a{sv")]
+struct BadSignature {
+    name: String,
+    value: u32,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use zvariant::Type;
+
+#[derive(Type)]
+#[zvariant(signature = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaay")]
+struct DeepSignature(Vec<u8>);
+
+fn main() {}
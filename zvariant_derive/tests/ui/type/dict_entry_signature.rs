@@ -0,0 +1,10 @@
+use zvariant::Type;
+
+#[derive(Type)]
+#[zvariant(signature = "{sv}")]
+struct DictEntry {
+    key: String,
+    value: zvariant::OwnedValue,
+}
+
+fn main() {}
@@ -2,3 +2,5 @@
 
 pub mod case;
 pub mod macros;
+pub mod object_path;
+pub mod signature;
@@ -80,6 +80,37 @@ pub fn match_attribute_with_bool_value<'a>(
     }
 }
 
+/// Compares `ident` and `attr` and in case they match ensures `value` is either `None` or contains
+/// a [`struct@LitStr`]. Returns `Some` in case `ident` and `attr` match, otherwise `None`.
+///
+/// # Errors
+///
+/// Returns an error in case `ident` and `attr` match but the value is neither `None` nor a
+/// [`struct@LitStr`].
+pub fn match_attribute_with_optional_str_value<'a>(
+    meta: &'a Meta,
+    attr: &str,
+) -> Result<Option<Option<&'a LitStr>>> {
+    if meta.path().is_ident(attr) {
+        match meta {
+            Meta::Path(_) => Ok(Some(None)),
+            Meta::NameValue(name_value) => match &name_value.lit {
+                Lit::Str(value) => Ok(Some(Some(value))),
+                _ => Err(syn::Error::new(
+                    meta.span(),
+                    format!("value of the `{attr}` attribute must be a string literal"),
+                )),
+            },
+            Meta::List(_) => Err(syn::Error::new(
+                meta.span(),
+                format!("attribute {attr} is not a list"),
+            )),
+        }
+    } else {
+        Ok(None)
+    }
+}
+
 pub fn match_attribute_with_str_list_value(meta: &Meta, attr: &str) -> Result<Option<Vec<String>>> {
     if meta.path().is_ident(attr) {
         match meta {
@@ -215,6 +246,8 @@ pub fn iter_meta_lists(
 /// * `bool` - boolean literals;
 /// * `[str]` - lists of string literals (`#[macro_name(foo("bar", "baz"))]`);
 /// * `none` - no literal at all, the attribute is specified alone.
+/// * `none_or_str` - either no literal at all or a string literal, yielding an
+///   `Option<Option<String>>`.
 ///
 /// The strings between braces are embedded into error messages produced when an attribute defined
 /// for one attribute group is used on another group where it is not defined. For example, if the
@@ -266,6 +299,7 @@ macro_rules! def_attrs {
     (@attr_ty bool) => {::std::option::Option<bool>};
     (@attr_ty [str]) => {::std::option::Option<::std::vec::Vec<::std::string::String>>};
     (@attr_ty none) => {bool};
+    (@attr_ty none_or_str) => {::std::option::Option<::std::option::Option<::std::string::String>>};
     (@attr_ty {
         $(#[$m:meta])*
         $vis:vis $name:ident($what:literal) {
@@ -325,6 +359,24 @@ macro_rules! def_attrs {
             }
         }
     };
+    (@match_attr none_or_str $attr_name:ident, $meta:ident, $self:ident) => {
+        if let ::std::option::Option::Some(value) =
+            $crate::macros::match_attribute_with_optional_str_value(
+                $meta,
+                ::std::stringify!($attr_name),
+            )?
+        {
+            if $self.$attr_name.is_none() {
+                $self.$attr_name = ::std::option::Option::Some(value.map(|value| value.value()));
+                return Ok(());
+            } else {
+                return ::std::result::Result::Err(::syn::Error::new(
+                    $meta.span(),
+                    concat!("duplicate `", stringify!($attr_name), "` attribute")
+                ));
+            }
+        }
+    };
     (@match_attr none $attr_name:ident, $meta:ident, $self:ident) => {
         if $crate::macros::match_attribute_without_value(
             $meta,
@@ -378,6 +430,7 @@ macro_rules! def_attrs {
     (@def_ty $list_name:ident bool) => {};
     (@def_ty $list_name:ident [str]) => {};
     (@def_ty $list_name:ident none) => {};
+    (@def_ty $list_name:ident none_or_str) => {};
     (
         @def_ty $list_name:ident {
             $(#[$m:meta])*
@@ -0,0 +1,66 @@
+//! Object path validation for use in macros.
+//!
+//! Like [`signature`](crate::signature), this follows the rules of `zvariant::ObjectPath`, so that
+//! paths given in macro attributes can be checked at build time.
+
+/// Check that `path` is a valid object path.
+///
+/// The rules are:
+///
+/// * At least 1 character.
+/// * First character must be `/`.
+/// * No trailing `/` (unless it's the root path).
+/// * No `//`.
+/// * Only ASCII alphanumeric, `_` or `/`.
+pub fn validate(path: &str) -> Result<(), String> {
+    let bytes = path.as_bytes();
+    match bytes.first() {
+        None => return Err("object path can't be empty".to_string()),
+        Some(b'/') => (),
+        Some(_) => return Err("object path must start with `/`".to_string()),
+    }
+    if bytes.len() > 1 && bytes.ends_with(b"/") {
+        return Err("object path can't end with `/`".to_string());
+    }
+    if path.contains("//") {
+        return Err("object path can't contain `//`".to_string());
+    }
+    if let Some(c) = path
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && *c != '/' && *c != '_')
+    {
+        return Err(format!(
+            "invalid character `{c}` in object path, expected an alphanumeric character, `_` or `/`"
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate;
+
+    #[test]
+    fn valid() {
+        for path in ["/", "/a", "/org/freedesktop/zbus_1", "/A/b/C"] {
+            assert!(validate(path).is_ok(), "`{path}` should be valid");
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for path in [
+            "",
+            "a",
+            "org/zbus",
+            "/org/",
+            "//",
+            "/org//zbus",
+            "/org/z-bus",
+            "/é",
+        ] {
+            assert!(validate(path).is_err(), "`{path}` should be invalid");
+        }
+    }
+}
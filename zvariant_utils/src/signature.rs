@@ -0,0 +1,181 @@
+//! Signature validation for use in macros.
+//!
+//! The `zvariant::Signature` type can't be used in our proc-macro crates, since `zvariant` itself
+//! depends on them. This module provides a minimal parser following the same rules, so that
+//! signatures given in macro attributes can be checked at build time.
+
+/// Maximum length of a signature, as per the D-Bus specification.
+const MAX_LEN: usize = 255;
+/// Maximum nesting depth of structures (including dict-entries), as per the D-Bus specification.
+const MAX_STRUCT_DEPTH: u8 = 32;
+/// Maximum nesting depth of arrays (including maybe types), as per the D-Bus specification.
+const MAX_ARRAY_DEPTH: u8 = 32;
+
+/// Validate `signature` and split it into its complete types.
+///
+/// The returned slices borrow from `signature`. An empty signature is valid and has no complete
+/// types.
+pub fn complete_types(signature: &str) -> Result<Vec<&str>, String> {
+    if signature.len() > MAX_LEN {
+        return Err(format!(
+            "signature is {} bytes long, the maximum is {MAX_LEN}",
+            signature.len()
+        ));
+    }
+
+    let bytes = signature.as_bytes();
+    let mut types = vec![];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let end = parse_complete_type(bytes, pos, Depths::default())?;
+        types.push(&signature[pos..end]);
+        pos = end;
+    }
+
+    Ok(types)
+}
+
+/// The nesting depths of the containers enclosing the type being parsed.
+#[derive(Debug, Default, Clone, Copy)]
+struct Depths {
+    structure: u8,
+    array: u8,
+}
+
+impl Depths {
+    fn inc_structure(mut self) -> Result<Self, String> {
+        self.structure += 1;
+        if self.structure > MAX_STRUCT_DEPTH {
+            return Err(format!(
+                "structures nested more than {MAX_STRUCT_DEPTH} levels deep"
+            ));
+        }
+
+        Ok(self)
+    }
+
+    fn inc_array(mut self) -> Result<Self, String> {
+        self.array += 1;
+        if self.array > MAX_ARRAY_DEPTH {
+            return Err(format!(
+                "arrays nested more than {MAX_ARRAY_DEPTH} levels deep"
+            ));
+        }
+
+        Ok(self)
+    }
+}
+
+/// Parse the complete type starting at `pos` and return the position right after it.
+fn parse_complete_type(bytes: &[u8], pos: usize, depths: Depths) -> Result<usize, String> {
+    let c = match bytes.get(pos) {
+        Some(c) => *c,
+        None => return Err("unexpected end of signature".to_string()),
+    };
+    match c {
+        b'y' | b'b' | b'n' | b'q' | b'i' | b'u' | b'x' | b't' | b'd' | b's' | b'o' | b'g'
+        | b'v' | b'h' => Ok(pos + 1),
+        // Dict-entries are only allowed as the element type of arrays.
+        b'a' if bytes.get(pos + 1) == Some(&b'{') => {
+            parse_dict_entry(bytes, pos + 1, depths.inc_array()?)
+        }
+        // Arrays and (GVariant) maybe types have exactly one child type.
+        b'a' | b'm' => parse_complete_type(bytes, pos + 1, depths.inc_array()?),
+        b'(' => {
+            if bytes.get(pos + 1) == Some(&b')') {
+                return Err("empty structure `()`".to_string());
+            }
+
+            let depths = depths.inc_structure()?;
+            let mut end = pos + 1;
+            while bytes.get(end) != Some(&b')') {
+                if end >= bytes.len() {
+                    return Err("unterminated structure".to_string());
+                }
+                end = parse_complete_type(bytes, end, depths)?;
+            }
+
+            Ok(end + 1)
+        }
+        b'{' => Err("dict-entry `{..}` outside of an array".to_string()),
+        c => Err(format!("invalid signature character `{}`", c as char)),
+    }
+}
+
+/// Parse the dict-entry starting at `pos` and return the position right after it.
+fn parse_dict_entry(bytes: &[u8], pos: usize, depths: Depths) -> Result<usize, String> {
+    let depths = depths.inc_structure()?;
+    match bytes.get(pos + 1) {
+        Some(
+            b'y' | b'b' | b'n' | b'q' | b'i' | b'u' | b'x' | b't' | b'd' | b's' | b'o' | b'g'
+            | b'h',
+        ) => (),
+        Some(b'}') | None => return Err("dict-entry without a key type".to_string()),
+        Some(_) => return Err("dict-entry key must be a basic type".to_string()),
+    }
+    let end = parse_complete_type(bytes, pos + 2, depths)?;
+    if bytes.get(end) != Some(&b'}') {
+        return Err("dict-entry must have exactly one value type".to_string());
+    }
+
+    Ok(end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::complete_types;
+
+    #[test]
+    fn valid() {
+        assert_eq!(complete_types("").unwrap(), Vec::<&str>::new());
+        assert_eq!(complete_types("s").unwrap(), ["s"]);
+        assert_eq!(complete_types("a{sv}").unwrap(), ["a{sv}"]);
+        assert_eq!(
+            complete_types("ua(yb)a{s(ii)}mx").unwrap(),
+            ["u", "a(yb)", "a{s(ii)}", "mx"]
+        );
+        assert_eq!(complete_types("(u(qxs))").unwrap(), ["(u(qxs))"]);
+    }
+
+    #[test]
+    fn invalid() {
+        for sig in [
+            "z", "a", "()", "(ss", "a{ss", "a{(s)s}", "a{sss}", "ss)", "a{}", "a{s}",
+        ] {
+            assert!(complete_types(sig).is_err(), "`{sig}` should be invalid");
+        }
+    }
+
+    #[test]
+    fn dict_entries() {
+        // Only allowed as the element type of arrays.
+        for sig in ["{sv}", "u{sv}", "({sv})", "m{sv}", "aa{sv}}"] {
+            assert!(complete_types(sig).is_err(), "`{sig}` should be invalid");
+        }
+        assert_eq!(complete_types("aa{sv}").unwrap(), ["aa{sv}"]);
+
+        // Keys must be basic types.
+        for sig in ["a{vs}", "a{ays}", "a{a{ss}s}"] {
+            assert!(complete_types(sig).is_err(), "`{sig}` should be invalid");
+        }
+        assert_eq!(complete_types("a{hv}").unwrap(), ["a{hv}"]);
+    }
+
+    #[test]
+    fn limits() {
+        assert!(complete_types(&"y".repeat(255)).is_ok());
+        assert!(complete_types(&"y".repeat(256)).is_err());
+
+        let arrays = |n| format!("{}y", "a".repeat(n));
+        assert!(complete_types(&arrays(32)).is_ok());
+        assert!(complete_types(&arrays(33)).is_err());
+
+        let structs = |n| format!("{}y{}", "(".repeat(n), ")".repeat(n));
+        assert!(complete_types(&structs(32)).is_ok());
+        assert!(complete_types(&structs(33)).is_err());
+        // Dict-entries count as structures.
+        let dicts = format!("{}y{}", "a{s".repeat(32), "}".repeat(32));
+        assert!(complete_types(&dicts).is_ok());
+        assert!(complete_types(&format!("({dicts})")).is_err());
+    }
+}